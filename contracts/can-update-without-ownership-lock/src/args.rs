@@ -0,0 +1,113 @@
+//! The args of this lock script.
+//!
+//! ```text
+//! owner public key hash (20 bytes) | type binding (optional) | max reward (optional)
+//! ```
+//!
+//! The type binding restricts which cells could be updated without the
+//! ownership:
+//! - No bytes: any cells.
+//! - 32 bytes: cells whose type script hash is it.
+//! - 33 bytes: cells whose type script has the code hash (32 bytes) and the
+//!   hash type (1 byte).
+//!
+//! The max reward (8 bytes, little-endian) enables the reward mode, it's only
+//! available with a type hash binding, which should be the type hash of the
+//! Bitcoin SPV instance.
+
+use ckb_bitcoin_spv_lock_utils::signature::PUBKEY_HASH_SIZE;
+use ckb_std::{ckb_constants::Source, ckb_types::prelude::*, debug, high_level as hl};
+
+use crate::error::{Error, Result};
+
+const TYPE_HASH_SIZE: usize = 32;
+const REWARD_SIZE: usize = 8;
+
+pub struct Args<'a> {
+    pub owner_pubkey_hash: &'a [u8],
+    pub binding: Option<TypeBinding<'a>>,
+    pub max_reward: Option<u64>,
+}
+
+pub enum TypeBinding<'a> {
+    TypeHash(&'a [u8]),
+    CodeHash { code_hash: &'a [u8], hash_type: u8 },
+}
+
+impl<'a> Args<'a> {
+    pub fn parse(args: &'a [u8]) -> Result<Self> {
+        if args.len() < PUBKEY_HASH_SIZE {
+            return Err(Error::ArgsMalformed);
+        }
+        let (owner_pubkey_hash, binding) = args.split_at(PUBKEY_HASH_SIZE);
+        let (binding, max_reward) = match binding.len() {
+            0 => (None, None),
+            32 => (Some(TypeBinding::TypeHash(binding)), None),
+            33 => {
+                let binding = TypeBinding::CodeHash {
+                    code_hash: &binding[..32],
+                    hash_type: binding[32],
+                };
+                (Some(binding), None)
+            }
+            40 => {
+                let (type_hash, max_reward) = binding.split_at(TYPE_HASH_SIZE);
+                let mut buf = [0u8; REWARD_SIZE];
+                buf.copy_from_slice(max_reward);
+                let max_reward = u64::from_le_bytes(buf);
+                (Some(TypeBinding::TypeHash(type_hash)), Some(max_reward))
+            }
+            _ => return Err(Error::ArgsMalformed),
+        };
+        Ok(Self {
+            owner_pubkey_hash,
+            binding,
+            max_reward,
+        })
+    }
+}
+
+impl TypeBinding<'_> {
+    /// Checks that the type script of the cell is the bound one.
+    pub fn check(&self, index: usize, source: Source) -> Result<()> {
+        let is_matched = match self {
+            Self::TypeHash(type_hash) => hl::load_cell_type_hash(index, source)?
+                .map(|actual| &actual[..] == *type_hash)
+                .unwrap_or(false),
+            Self::CodeHash {
+                code_hash,
+                hash_type,
+            } => hl::load_cell_type(index, source)?
+                .map(|script| {
+                    script.code_hash().as_slice() == *code_hash
+                        && script.hash_type().as_slice() == [*hash_type]
+                })
+                .unwrap_or(false),
+        };
+        if !is_matched {
+            debug!("the type of cell {index} is not the bound one");
+            return Err(Error::TypeIsNotMatched);
+        }
+        Ok(())
+    }
+
+    /// Checks if the cell is a reward pool cell, which has no type script,
+    /// and its data is the bound type hash.
+    pub fn is_reward_pool(&self, index: usize, source: Source) -> Result<bool> {
+        let Self::TypeHash(type_hash) = self else {
+            return Ok(false);
+        };
+        let is_pool = hl::load_cell_type_hash(index, source)?.is_none()
+            && hl::load_cell_data(index, source)? == *type_hash;
+        Ok(is_pool)
+    }
+
+    /// Returns the bound type hash, which is the type hash of the Bitcoin SPV
+    /// instance in the reward mode.
+    pub fn type_hash(&self) -> Option<[u8; 32]> {
+        match self {
+            Self::TypeHash(type_hash) => type_hash.try_into().ok(),
+            Self::CodeHash { .. } => None,
+        }
+    }
+}
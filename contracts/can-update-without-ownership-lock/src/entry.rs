@@ -1,21 +1,49 @@
 use alloc::vec::Vec;
 
-use ckb_hash::blake2b_256;
+use ckb_bitcoin_spv_lock_utils::{reward, signature};
 #[cfg(debug_assertions)]
 use ckb_std::ckb_types::prelude::*;
 use ckb_std::{ckb_constants::Source, debug, high_level as hl};
 
-use crate::error::{Error, Result};
+use crate::{
+    args::Args,
+    error::{Error, Result},
+};
+
+// Cells which use current lock.
+enum Cell {
+    // Cells which match the type binding, or any cells without a binding.
+    Bound,
+    // Cells which have no type script, and their data is the bound type
+    // hash, only available with the reward mode.
+    RewardPool,
+}
+
+// The total capacity of cells which use current lock.
+#[derive(Default)]
+struct Capacity {
+    bound_cells: u64,
+    reward_pool: u64,
+}
 
 pub fn main() -> Result<()> {
     debug!("{} Starting ...", module_path!());
 
     let script_hash = hl::load_script_hash()?;
     debug!("script hash = {:#x}", script_hash.pack());
-    let args = hl::load_script()?.args();
-    let expected_proof = args.raw_data().iter().cloned().collect::<Vec<u8>>();
+    let script_args = hl::load_script()?.args();
+    let args_data = script_args.raw_data();
+    let args = Args::parse(&args_data)?;
 
-    let mut checked = false;
+    let checked = if let Some((witness_args, signature)) = signature::load_signed_witness()? {
+        signature::verify(witness_args, &signature, args.owner_pubkey_hash)?;
+        debug!(">>> passed to check the signature of the owner");
+        true
+    } else {
+        debug!(">>> no signature of the owner");
+        false
+    };
+    debug!("checked: {checked}");
 
     // Indexes of all inputs which use this lock.
     let mut inputs_indexes = Vec::new();
@@ -30,70 +58,53 @@ pub fn main() -> Result<()> {
                 ">>> skipping input {index} since it's lock is {:#x}",
                 lock_hash.pack()
             );
-            continue;
-        }
-
-        if let Ok(witness_args) = hl::load_witness_args(index, Source::Input) {
-            if let Some(lock_witness) = witness_args.lock().to_opt() {
-                let actual_proof = blake2b_256(lock_witness.raw_data().as_ref());
-                if actual_proof[..] == expected_proof[..] {
-                    checked = true;
-                    debug!(">>> >>> passed to check witness");
-                } else {
-                    debug!(
-                        ">>> >>> failed to check witness: args: {}, witness: {}",
-                        args, lock_witness
-                    );
-                    return Err(Error::WitnessIsIncorrect);
-                }
-            } else {
-                debug!(">>> >>> failed to check witness: witness is empty");
-            }
-        } else {
-            debug!(">>> >>> failed to load {index}-th witness");
         }
     }
-    debug!("checked: {checked}");
 
     if !checked {
+        // In the reward mode, the reward pool cells are not bound to the type,
+        // and at most the max reward could leave them.
+        let classify = |index: usize, source: Source| -> Result<Cell> {
+            let Some(binding) = &args.binding else {
+                return Ok(Cell::Bound);
+            };
+            if args.max_reward.is_some() && binding.is_reward_pool(index, source)? {
+                return Ok(Cell::RewardPool);
+            }
+            binding.check(index, source)?;
+            Ok(Cell::Bound)
+        };
         debug!("calculating inputs capacity ...");
-        let total_inputs_capacity = inputs_indexes.into_iter().try_fold(0u64, |total, index| {
+        let mut inputs = Capacity::default();
+        for index in inputs_indexes {
+            let cell = classify(index, Source::Input)?;
             let added = hl::load_cell_capacity(index, Source::Input)?;
-            let (tmp, of) = total.overflowing_add(added);
-            debug!(">>> total = {tmp} (index: {index}, added: {added}, overflow: {of})");
-            if of {
-                Err(Error::InputsCapacityOverflow)
-            } else {
-                Ok(tmp)
-            }
-        })?;
+            inputs
+                .add(cell, added)
+                .ok_or(Error::InputsCapacityOverflow)?;
+            debug!(">>> added {added} (index: {index})");
+        }
         debug!("calculating outputs capacity ...");
-        let total_outputs_capacity = hl::QueryIter::new(hl::load_cell_lock_hash, Source::Output)
-            .enumerate()
-            .try_fold(0u64, |total, (index, lock_hash)| {
-                if lock_hash == script_hash {
-                    debug!(">>> checking output {index}");
-                } else {
-                    debug!(
-                        ">>> skipping output {index} since it's lock is {:#x}",
-                        lock_hash.pack()
-                    );
-                    return Ok(total);
-                }
-
-                let added = hl::load_cell_capacity(index, Source::Output)?;
-                let (tmp, of) = total.overflowing_add(added);
-                debug!(">>> >>> total = {tmp} (index: {index}, added: {added}, overflow: {of})");
-                if of {
-                    Err(Error::OutputsCapacityOverflow)
-                } else {
-                    Ok(tmp)
-                }
-            })?;
-        if total_inputs_capacity > total_outputs_capacity {
-            debug!("lost capacity without ownership ({total_inputs_capacity} -> {total_outputs_capacity})");
-            return Err(Error::LostCapacityWithoutOwnership);
+        let mut outputs = Capacity::default();
+        for (index, lock_hash) in
+            hl::QueryIter::new(hl::load_cell_lock_hash, Source::Output).enumerate()
+        {
+            if lock_hash != script_hash {
+                debug!(
+                    ">>> skipping output {index} since it's lock is {:#x}",
+                    lock_hash.pack()
+                );
+                continue;
+            }
+            debug!(">>> checking output {index}");
+            let cell = classify(index, Source::Output)?;
+            let added = hl::load_cell_capacity(index, Source::Output)?;
+            outputs
+                .add(cell, added)
+                .ok_or(Error::OutputsCapacityOverflow)?;
+            debug!(">>> >>> added {added} (index: {index})");
         }
+        check_capacity(&args, inputs, outputs)?;
     }
 
     for (_index, type_hash_opt) in
@@ -111,3 +122,51 @@ pub fn main() -> Result<()> {
 
     Ok(())
 }
+
+// Without ownership, the total capacity of bound cells should not be
+// decreased.
+//
+// With the reward mode, at most `max_reward` capacity could leave the cells
+// which use current lock, as the reward for the relayer, but only when the
+// tip of the Bitcoin SPV instance is advanced.
+fn check_capacity(args: &Args, inputs: Capacity, outputs: Capacity) -> Result<()> {
+    if inputs.bound_cells > outputs.bound_cells {
+        debug!(
+            "lost capacity without ownership ({} -> {})",
+            inputs.bound_cells, outputs.bound_cells
+        );
+        return Err(Error::LostCapacityWithoutOwnership);
+    }
+    let inputs_total = inputs.total().ok_or(Error::InputsCapacityOverflow)?;
+    let outputs_total = outputs.total().ok_or(Error::OutputsCapacityOverflow)?;
+    if inputs_total > outputs_total {
+        let reward = inputs_total - outputs_total;
+        debug!("reward: {reward}, max reward: {:?}", args.max_reward);
+        let max_reward = args.max_reward.ok_or(Error::LostCapacityWithoutOwnership)?;
+        if reward > max_reward {
+            return Err(Error::RewardExceeded);
+        }
+        let spv_type_hash = args
+            .binding
+            .as_ref()
+            .and_then(|binding| binding.type_hash())
+            .ok_or(Error::ArgsMalformed)?;
+        reward::check_tip_advanced(&spv_type_hash)?;
+    }
+    Ok(())
+}
+
+impl Capacity {
+    fn add(&mut self, cell: Cell, capacity: u64) -> Option<()> {
+        let total = match cell {
+            Cell::Bound => &mut self.bound_cells,
+            Cell::RewardPool => &mut self.reward_pool,
+        };
+        *total = total.checked_add(capacity)?;
+        Some(())
+    }
+
+    fn total(&self) -> Option<u64> {
+        self.bound_cells.checked_add(self.reward_pool)
+    }
+}
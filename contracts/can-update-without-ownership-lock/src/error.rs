@@ -1,5 +1,6 @@
 use core::result;
 
+use ckb_bitcoin_spv_lock_utils::error::Error as LockUtilsError;
 use ckb_std::error::SysError;
 
 pub type Result<T> = result::Result<T, Error>;
@@ -19,6 +20,14 @@ pub enum Error {
     InputsCapacityOverflow,
     OutputsCapacityOverflow,
     LostCapacityWithoutOwnership,
+    WitnessMalformed,
+    SignatureMalformed,
+    ArgsMalformed,
+    TypeIsNotMatched,
+    RewardExceeded,
+    SpvInfoNotFound,
+    SpvClientNotFound,
+    TipNotAdvanced,
 }
 
 impl From<SysError> for Error {
@@ -29,7 +38,23 @@ impl From<SysError> for Error {
             ItemMissing => Self::ItemMissing,
             LengthNotEnough(_) => Self::LengthNotEnough,
             Encoding => Self::Encoding,
-            Unknown(_) => Self::Unknown,
+            // Spawn is not used by this script.
+            WaitFailure | InvalidFd | OtherEndClosed | MaxVmsSpawned | MaxFdsCreated
+            | Unknown(_) => Self::Unknown,
+        }
+    }
+}
+
+impl From<LockUtilsError> for Error {
+    fn from(err: LockUtilsError) -> Self {
+        match err {
+            LockUtilsError::Sys(err) => err.into(),
+            LockUtilsError::WitnessMalformed => Self::WitnessMalformed,
+            LockUtilsError::SignatureMalformed => Self::SignatureMalformed,
+            LockUtilsError::SignatureIsIncorrect => Self::WitnessIsIncorrect,
+            LockUtilsError::SpvInfoNotFound => Self::SpvInfoNotFound,
+            LockUtilsError::SpvClientNotFound => Self::SpvClientNotFound,
+            LockUtilsError::TipNotAdvanced => Self::TipNotAdvanced,
         }
     }
 }
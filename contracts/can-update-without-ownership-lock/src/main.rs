@@ -11,6 +11,7 @@ ckb_std::entry!(program_entry);
 #[cfg(not(test))]
 default_alloc!();
 
+mod args;
 mod entry;
 mod error;
 
@@ -0,0 +1,45 @@
+use ckb_bitcoin_spv_consumer::registry::{check_no_key_inserted, registry_type_script};
+use ckb_std::{ckb_constants::Source, debug};
+
+use crate::{
+    error::{InternalError, Result},
+    mint, utilities,
+};
+
+pub fn main() -> Result<()> {
+    debug!("{} Starting ...", module_path!());
+
+    let args = utilities::load_mint_args()?;
+    let registry = registry_type_script(
+        &args.registry_code_hash,
+        args.registry_hash_type,
+        &args.registry_type_id,
+    )?;
+
+    debug!("calculating inputs amount ...");
+    let (inputs_count, inputs_amount) =
+        utilities::load_total_amount(Source::GroupInput, InternalError::InputsAmountOverflow)?;
+    debug!("calculating outputs amount ...");
+    let (_outputs_count, outputs_amount) =
+        utilities::load_total_amount(Source::GroupOutput, InternalError::OutputsAmountOverflow)?;
+
+    debug!("cells in  inputs: {inputs_count}, amount: {inputs_amount}");
+    debug!("cells in outputs: {_outputs_count}, amount: {outputs_amount}");
+
+    if inputs_amount >= outputs_amount {
+        debug!("transfer or burn tokens");
+        // The registry accepts keys when this type script is in the
+        // transaction, so keys could only be inserted when mint.
+        check_no_key_inserted(&registry)?;
+    } else {
+        debug!("mint tokens");
+        if inputs_count > 0 {
+            return Err(InternalError::MintWithInputs.into());
+        }
+        mint::verify_mint(outputs_amount, &args, &registry)?;
+    }
+
+    debug!("{} DONE.", module_path!());
+
+    Ok(())
+}
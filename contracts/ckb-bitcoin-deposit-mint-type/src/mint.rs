@@ -0,0 +1,94 @@
+use ckb_bitcoin_spv_consumer::{
+    load_client_cell_dep, registry::check_key_inserted, transaction::Transaction,
+    verify_transaction,
+};
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{
+        packed::{BytesVecReader, Script},
+        prelude::*,
+    },
+    debug, high_level as hl,
+};
+
+use crate::{
+    error::{InternalError, Result},
+    utilities::MintArgs,
+};
+
+// `OP_RETURN` then push 32 bytes.
+const RECIPIENT_PREFIX: [u8; 2] = [0x6a, 0x20];
+const RECIPIENT_SCRIPT_SIZE: usize = 2 + 32;
+
+/// Checks the minted amount against the Bitcoin deposit transaction.
+///
+/// The witness should be set in the field `output_type` of `WitnessArgs`,
+/// at the same index of the first output cell which uses this type script.
+/// It's a `BytesVec` with 2 items:
+/// - The raw Bitcoin deposit transaction.
+/// - The `TransactionProof` of the deposit transaction.
+pub(crate) fn verify_mint(minted: u128, args: &MintArgs, registry: &Script) -> Result<()> {
+    let witness_args = hl::load_witness_args(0, Source::GroupOutput)?;
+    let witness = witness_args
+        .output_type()
+        .to_opt()
+        .ok_or(InternalError::WitnessIsNotExisted)?
+        .raw_data();
+    let items =
+        BytesVecReader::from_slice(&witness).map_err(|_| InternalError::WitnessMalformed)?;
+    if items.len() != 2 {
+        return Err(InternalError::WitnessMalformed.into());
+    }
+    let raw_tx = items.get_unchecked(0).raw_data();
+    let tx_proof = items.get_unchecked(1).raw_data();
+
+    let tx = Transaction::parse(raw_tx)?;
+    // Any client is acceptable: the confirmations are counted up to the tip
+    // of the client, so an older client only proves fewer confirmations.
+    let (_index, client) = load_client_cell_dep(&args.spv_type_hash)?;
+    debug!("verify the deposit transaction against client (index={_index})");
+    verify_transaction(&client, &tx, tx_proof, args.confirmations)?;
+
+    debug!("record the deposit transaction into the registry");
+    check_key_inserted(registry, &tx.txid())?;
+
+    let deposited = tx
+        .outputs()
+        .iter()
+        .filter(|output| output.script_pubkey == args.custody_script.as_slice())
+        .try_fold(0u64, |total, output| total.checked_add(output.value))
+        .ok_or(InternalError::DepositAmountOverflow)?;
+    debug!("deposited: {deposited}, minted: {minted}");
+    if u128::from(deposited) != minted {
+        return Err(InternalError::DepositAmountMismatch.into());
+    }
+
+    let recipient = {
+        let mut recipients = tx.outputs().iter().filter_map(|output| {
+            let script = output.script_pubkey;
+            if script.len() == RECIPIENT_SCRIPT_SIZE && script.starts_with(&RECIPIENT_PREFIX) {
+                Some(&script[RECIPIENT_PREFIX.len()..])
+            } else {
+                None
+            }
+        });
+        let recipient = recipients.next().ok_or(InternalError::RecipientNotFound)?;
+        if recipients.next().is_some() {
+            return Err(InternalError::RecipientMoreThanOne.into());
+        }
+        recipient
+    };
+    for (_index, lock_hash) in
+        hl::QueryIter::new(hl::load_cell_lock_hash, Source::GroupOutput).enumerate()
+    {
+        if lock_hash != recipient {
+            debug!(
+                "output {_index} is not sent to the recipient, its lock is {:#x}",
+                lock_hash.pack()
+            );
+            return Err(InternalError::RecipientMismatch.into());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use alloc::vec::Vec;
+
+use ckb_std::{ckb_constants::Source, debug, high_level as hl};
+
+use crate::error::{InternalError, Result};
+
+const AMOUNT_SIZE: usize = 16;
+const SPV_TYPE_HASH_SIZE: usize = 32;
+const REGISTRY_CODE_HASH_SIZE: usize = 32;
+const REGISTRY_HASH_TYPE_SIZE: usize = 1;
+const REGISTRY_TYPE_ID_SIZE: usize = 32;
+const CONFIRMATIONS_SIZE: usize = 4;
+
+/// The args of this type script.
+///
+/// - The type hash of the SPV instance, 32 bytes.
+/// - The code hash of the registry for used deposits, 32 bytes.
+/// - The hash type of the registry for used deposits, 1 byte.
+/// - The type id of the registry for used deposits, 32 bytes.
+/// - The minimum confirmations of the deposit transaction, `u32` in little-endian.
+/// - The Bitcoin custody script, all remaining bytes.
+pub(crate) struct MintArgs {
+    pub(crate) spv_type_hash: [u8; SPV_TYPE_HASH_SIZE],
+    pub(crate) registry_code_hash: [u8; REGISTRY_CODE_HASH_SIZE],
+    pub(crate) registry_hash_type: u8,
+    pub(crate) registry_type_id: [u8; REGISTRY_TYPE_ID_SIZE],
+    pub(crate) confirmations: u32,
+    pub(crate) custody_script: Vec<u8>,
+}
+
+pub(crate) fn load_mint_args() -> Result<MintArgs> {
+    let script = hl::load_script()?;
+    let args = script.args().raw_data();
+    let fixed_size = SPV_TYPE_HASH_SIZE
+        + REGISTRY_CODE_HASH_SIZE
+        + REGISTRY_HASH_TYPE_SIZE
+        + REGISTRY_TYPE_ID_SIZE
+        + CONFIRMATIONS_SIZE;
+    if args.len() <= fixed_size {
+        return Err(InternalError::ArgsMalformed.into());
+    }
+    let (spv_type_hash_slice, remained) = args.split_at(SPV_TYPE_HASH_SIZE);
+    let (registry_code_hash_slice, remained) = remained.split_at(REGISTRY_CODE_HASH_SIZE);
+    let (registry_hash_type_slice, remained) = remained.split_at(REGISTRY_HASH_TYPE_SIZE);
+    let (registry_type_id_slice, remained) = remained.split_at(REGISTRY_TYPE_ID_SIZE);
+    let (confirmations_slice, custody_script) = remained.split_at(CONFIRMATIONS_SIZE);
+    let mut spv_type_hash = [0u8; SPV_TYPE_HASH_SIZE];
+    spv_type_hash.copy_from_slice(spv_type_hash_slice);
+    let mut registry_code_hash = [0u8; REGISTRY_CODE_HASH_SIZE];
+    registry_code_hash.copy_from_slice(registry_code_hash_slice);
+    let mut registry_type_id = [0u8; REGISTRY_TYPE_ID_SIZE];
+    registry_type_id.copy_from_slice(registry_type_id_slice);
+    let mut confirmations = [0u8; CONFIRMATIONS_SIZE];
+    confirmations.copy_from_slice(confirmations_slice);
+    let args = MintArgs {
+        spv_type_hash,
+        registry_code_hash,
+        registry_hash_type: registry_hash_type_slice[0],
+        registry_type_id,
+        confirmations: u32::from_le_bytes(confirmations),
+        custody_script: custody_script.to_vec(),
+    };
+    Ok(args)
+}
+
+/// Sums the token amounts of all cells in the source, returns the count of
+/// cells and the total amount.
+///
+/// The first 16 bytes of the cell data is the amount, as `u128` in
+/// little-endian.
+pub(crate) fn load_total_amount(
+    source: Source,
+    overflow_error: InternalError,
+) -> Result<(usize, u128)> {
+    let mut count = 0;
+    let mut total = 0u128;
+    for data in hl::QueryIter::new(hl::load_cell_data, source) {
+        if data.len() < AMOUNT_SIZE {
+            return Err(InternalError::AmountMalformed.into());
+        }
+        let mut amount = [0u8; AMOUNT_SIZE];
+        amount.copy_from_slice(&data[..AMOUNT_SIZE]);
+        let added = u128::from_le_bytes(amount);
+        if let Some(tmp) = total.checked_add(added) {
+            debug!(">>> total = {tmp} (index: {count}, added: {added})");
+            total = tmp;
+        } else {
+            return Err(overflow_error.into());
+        }
+        count += 1;
+    }
+    Ok((count, total))
+}
@@ -0,0 +1,60 @@
+use ckb_bitcoin_spv_consumer::load_tip_client_cell_dep;
+use ckb_bitcoin_spv_verifier::types::prelude::*;
+use ckb_std::{ckb_constants::Source, debug, high_level as hl};
+
+use crate::error::{InternalError, Result};
+
+const SPV_TYPE_HASH_SIZE: usize = 32;
+const HEIGHT_SIZE: usize = 4;
+const CONFIRMATIONS_SIZE: usize = 4;
+const OWNER_LOCK_HASH_SIZE: usize = 32;
+const ARGS_SIZE: usize =
+    SPV_TYPE_HASH_SIZE + HEIGHT_SIZE + CONFIRMATIONS_SIZE + OWNER_LOCK_HASH_SIZE;
+
+pub fn main() -> Result<()> {
+    debug!("{} Starting ...", module_path!());
+
+    let script = hl::load_script()?;
+    let script_args = script.args();
+    let args = script_args.as_reader().raw_data();
+    if args.len() != ARGS_SIZE {
+        return Err(InternalError::ArgsMalformed.into());
+    }
+    let (spv_type_hash, args) = args.split_at(SPV_TYPE_HASH_SIZE);
+    let (height, args) = args.split_at(HEIGHT_SIZE);
+    let (confirmations, owner_lock_hash) = args.split_at(CONFIRMATIONS_SIZE);
+
+    let target_height = {
+        let mut buf = [0u8; HEIGHT_SIZE];
+        buf.copy_from_slice(height);
+        let height = u32::from_le_bytes(buf);
+        buf.copy_from_slice(confirmations);
+        let confirmations = u32::from_le_bytes(buf);
+        debug!("height: {height}, confirmations: {confirmations}");
+        height
+            .checked_add(confirmations)
+            .ok_or(InternalError::TargetHeightOverflow)?
+    };
+
+    let tip_height: u32 = {
+        let mut hash = [0u8; SPV_TYPE_HASH_SIZE];
+        hash.copy_from_slice(spv_type_hash);
+        let (_index, client) = load_tip_client_cell_dep(&hash)?;
+        debug!("tip client (index={_index}) = {client}");
+        client.headers_mmr_root().max_height().unpack()
+    };
+    debug!("target height: {target_height}, tip height: {tip_height}");
+    if tip_height < target_height {
+        return Err(InternalError::TargetHeightNotReached.into());
+    }
+
+    let has_owner = hl::QueryIter::new(hl::load_cell_lock_hash, Source::Input)
+        .any(|lock_hash| lock_hash[..] == owner_lock_hash[..]);
+    if !has_owner {
+        return Err(InternalError::OwnerNotFound.into());
+    }
+
+    debug!("{} DONE.", module_path!());
+
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use ckb_bitcoin_spv_lock_utils::{
+    reward,
+    signature::{self, PUBKEY_HASH_SIZE},
+};
+use ckb_std::{ckb_constants::Source, ckb_types::prelude::*, debug, high_level as hl};
+
+use crate::error::{Error, Result};
+
+const TYPE_HASH_SIZE: usize = 32;
+const REWARD_SIZE: usize = 8;
+const ARGS_SIZE: usize = TYPE_HASH_SIZE + PUBKEY_HASH_SIZE;
+
+// Cells which use current lock.
+enum Cell {
+    // Cells of the Bitcoin SPV instance.
+    Spv,
+    // Cells which have no type script, and their data is the type hash of
+    // the Bitcoin SPV instance, only available with the reward mode.
+    RewardPool,
+}
+
+// The total capacity of cells which use current lock.
+#[derive(Default)]
+struct Capacity {
+    spv_cells: u64,
+    reward_pool: u64,
+}
+
+pub fn main() -> Result<()> {
+    debug!("{} Starting ...", module_path!());
+
+    let script = hl::load_script()?;
+    let script_args = script.args();
+    let args = script_args.as_reader().raw_data();
+    if args.len() != ARGS_SIZE && args.len() != ARGS_SIZE + REWARD_SIZE {
+        return Err(Error::ArgsMalformed);
+    }
+    let (spv_type_hash, args) = args.split_at(TYPE_HASH_SIZE);
+    let spv_type_hash: [u8; 32] = spv_type_hash.try_into().expect("check size of the args");
+    let (owner_pubkey_hash, max_reward) = args.split_at(PUBKEY_HASH_SIZE);
+    let max_reward_opt = if max_reward.is_empty() {
+        None
+    } else {
+        let mut buf = [0u8; REWARD_SIZE];
+        buf.copy_from_slice(max_reward);
+        Some(u64::from_le_bytes(buf))
+    };
+    debug!("max reward: {max_reward_opt:?}");
+
+    let script_hash = hl::load_script_hash()?;
+    debug!("script hash = {:#x}", script_hash.pack());
+
+    for (_index, type_hash_opt) in
+        hl::QueryIter::new(hl::load_cell_type_hash, Source::Output).enumerate()
+    {
+        if type_hash_opt.as_ref() == Some(&script_hash) {
+            debug!("output {_index} uses current lock as type");
+            return Err(Error::ShouldNotBeType);
+        }
+    }
+
+    if let Some((witness_args, signature)) = signature::load_signed_witness()? {
+        signature::verify(witness_args, &signature, owner_pubkey_hash)?;
+        debug!("unlocked by the owner");
+    } else {
+        let classify = |index: usize, source: Source| -> Result<Option<Cell>> {
+            let cell = match hl::load_cell_type_hash(index, source)? {
+                Some(type_hash) if type_hash == spv_type_hash => Some(Cell::Spv),
+                None if max_reward_opt.is_some()
+                    && hl::load_cell_data(index, source)? == spv_type_hash =>
+                {
+                    Some(Cell::RewardPool)
+                }
+                _ => None,
+            };
+            Ok(cell)
+        };
+        let (inputs, outputs) = load_capacity(&script_hash, classify)?;
+        check_capacity(&spv_type_hash, inputs, outputs, max_reward_opt)?;
+        debug!("updated without ownership");
+    }
+
+    debug!("{} DONE.", module_path!());
+
+    Ok(())
+}
+
+// Without ownership, all cells which use current lock should be cells of the
+// SPV instance, both in inputs and outputs, except the reward pool cells.
+fn load_capacity<F>(script_hash: &[u8; 32], classify: F) -> Result<(Capacity, Capacity)>
+where
+    F: Fn(usize, Source) -> Result<Option<Cell>>,
+{
+    debug!("calculating inputs capacity ...");
+    let mut inputs = Capacity::default();
+    for (index, capacity) in
+        hl::QueryIter::new(hl::load_cell_capacity, Source::GroupInput).enumerate()
+    {
+        let Some(cell) = classify(index, Source::GroupInput)? else {
+            debug!("the type of input {index} (in group) is not matched");
+            return Err(Error::TypeIsNotMatched);
+        };
+        inputs
+            .add(cell, capacity)
+            .ok_or(Error::InputsCapacityOverflow)?;
+    }
+    debug!("calculating outputs capacity ...");
+    let mut outputs = Capacity::default();
+    for (index, lock_hash) in
+        hl::QueryIter::new(hl::load_cell_lock_hash, Source::Output).enumerate()
+    {
+        if &lock_hash != script_hash {
+            continue;
+        }
+        let Some(cell) = classify(index, Source::Output)? else {
+            debug!("the type of output {index} is not matched");
+            return Err(Error::TypeIsNotMatched);
+        };
+        let capacity = hl::load_cell_capacity(index, Source::Output)?;
+        outputs
+            .add(cell, capacity)
+            .ok_or(Error::OutputsCapacityOverflow)?;
+    }
+    Ok((inputs, outputs))
+}
+
+// Without ownership, the total capacity of SPV cells should not be decreased.
+//
+// With the reward mode, at most `max_reward` capacity could leave the cells
+// which use current lock, as the reward for the relayer, but only when the
+// tip of the SPV instance is advanced.
+fn check_capacity(
+    spv_type_hash: &[u8; 32],
+    inputs: Capacity,
+    outputs: Capacity,
+    max_reward_opt: Option<u64>,
+) -> Result<()> {
+    if inputs.spv_cells > outputs.spv_cells {
+        debug!(
+            "lost capacity of SPV cells without ownership ({} -> {})",
+            inputs.spv_cells, outputs.spv_cells
+        );
+        return Err(Error::LostCapacityWithoutOwnership);
+    }
+    let inputs_total = inputs.total().ok_or(Error::InputsCapacityOverflow)?;
+    let outputs_total = outputs.total().ok_or(Error::OutputsCapacityOverflow)?;
+    if inputs_total > outputs_total {
+        let reward = inputs_total - outputs_total;
+        debug!("reward: {reward}, max reward: {max_reward_opt:?}");
+        let max_reward = max_reward_opt.ok_or(Error::LostCapacityWithoutOwnership)?;
+        if reward > max_reward {
+            return Err(Error::RewardExceeded);
+        }
+        reward::check_tip_advanced(spv_type_hash)?;
+    }
+    Ok(())
+}
+
+impl Capacity {
+    fn add(&mut self, cell: Cell, capacity: u64) -> Option<()> {
+        let total = match cell {
+            Cell::Spv => &mut self.spv_cells,
+            Cell::RewardPool => &mut self.reward_pool,
+        };
+        *total = total.checked_add(capacity)?;
+        Some(())
+    }
+
+    fn total(&self) -> Option<u64> {
+        self.spv_cells.checked_add(self.reward_pool)
+    }
+}
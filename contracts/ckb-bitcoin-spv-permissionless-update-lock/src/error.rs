@@ -0,0 +1,60 @@
+use core::result;
+
+use ckb_bitcoin_spv_lock_utils::error::Error as LockUtilsError;
+use ckb_std::error::SysError;
+
+pub type Result<T> = result::Result<T, Error>;
+
+#[repr(i8)]
+pub enum Error {
+    // 0x01 ~ 0x0f: Errors from SDK, or other system errors.
+    IndexOutOfBound = 0x01,
+    ItemMissing,
+    LengthNotEnough,
+    Encoding,
+    Unknown,
+
+    // 0x10 ~ 0x2f: Errors in current crate.
+    ArgsMalformed = 0x10,
+    ShouldNotBeType,
+    WitnessMalformed,
+    SignatureMalformed,
+    SignatureIsIncorrect,
+    TypeIsNotMatched,
+    InputsCapacityOverflow,
+    OutputsCapacityOverflow,
+    LostCapacityWithoutOwnership,
+    RewardExceeded,
+    SpvInfoNotFound,
+    SpvClientNotFound,
+    TipNotAdvanced,
+}
+
+impl From<SysError> for Error {
+    fn from(err: SysError) -> Self {
+        use SysError::*;
+        match err {
+            IndexOutOfBound => Self::IndexOutOfBound,
+            ItemMissing => Self::ItemMissing,
+            LengthNotEnough(_) => Self::LengthNotEnough,
+            Encoding => Self::Encoding,
+            // Spawn is not used by this script.
+            WaitFailure | InvalidFd | OtherEndClosed | MaxVmsSpawned | MaxFdsCreated
+            | Unknown(_) => Self::Unknown,
+        }
+    }
+}
+
+impl From<LockUtilsError> for Error {
+    fn from(err: LockUtilsError) -> Self {
+        match err {
+            LockUtilsError::Sys(err) => err.into(),
+            LockUtilsError::WitnessMalformed => Self::WitnessMalformed,
+            LockUtilsError::SignatureMalformed => Self::SignatureMalformed,
+            LockUtilsError::SignatureIsIncorrect => Self::SignatureIsIncorrect,
+            LockUtilsError::SpvInfoNotFound => Self::SpvInfoNotFound,
+            LockUtilsError::SpvClientNotFound => Self::SpvClientNotFound,
+            LockUtilsError::TipNotAdvanced => Self::TipNotAdvanced,
+        }
+    }
+}
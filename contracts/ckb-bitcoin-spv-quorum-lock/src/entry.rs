@@ -0,0 +1,82 @@
+use alloc::vec::Vec;
+
+use ckb_bitcoin_spv_consumer::{quorum::Quorum, transaction::Transaction};
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{packed::BytesVecReader, prelude::*},
+    debug, high_level as hl,
+};
+
+use crate::error::{InternalError, Result};
+
+const THRESHOLD_SIZE: usize = 1;
+const CONFIRMATIONS_SIZE: usize = 4;
+// The lock script hash, then the out point of the first input.
+const COMMITMENT_SIZE: usize = 32 + 36;
+// `OP_RETURN` then push 68 bytes.
+const COMMITMENT_PREFIX: [u8; 2] = [0x6a, COMMITMENT_SIZE as u8];
+
+pub fn main() -> Result<()> {
+    debug!("{} Starting ...", module_path!());
+
+    let script = hl::load_script()?;
+    let script_args = script.args();
+    let args = script_args.as_reader().raw_data();
+    if args.len() <= THRESHOLD_SIZE + CONFIRMATIONS_SIZE {
+        return Err(InternalError::ArgsMalformed.into());
+    }
+    let threshold = usize::from(args[0]);
+    let confirmations = {
+        let mut buf = [0u8; CONFIRMATIONS_SIZE];
+        buf.copy_from_slice(&args[THRESHOLD_SIZE..][..CONFIRMATIONS_SIZE]);
+        u32::from_le_bytes(buf)
+    };
+    let quorum = Quorum::from_slice(threshold, &args[THRESHOLD_SIZE + CONFIRMATIONS_SIZE..])?;
+    debug!(
+        "quorum: {threshold} of {}, confirmations: {confirmations}",
+        quorum.spv_type_hashes().len()
+    );
+
+    // The witness is a `BytesVec`: the raw transaction, then a proof for
+    // each SPV instance.
+    let witness_args = hl::load_witness_args(0, Source::GroupInput)?;
+    let witness = witness_args
+        .lock()
+        .to_opt()
+        .ok_or(InternalError::WitnessIsNotExisted)?
+        .raw_data();
+    let items =
+        BytesVecReader::from_slice(&witness).map_err(|_| InternalError::WitnessMalformed)?;
+    if items.len() != 1 + quorum.spv_type_hashes().len() {
+        return Err(InternalError::WitnessMalformed.into());
+    }
+    let raw_tx = items.get_unchecked(0).raw_data();
+    let tx_proofs = items
+        .iter()
+        .skip(1)
+        .map(|item| item.raw_data())
+        .collect::<Vec<_>>();
+
+    let tx = Transaction::parse(raw_tx)?;
+    quorum.verify_transaction(&tx, &tx_proofs, confirmations)?;
+
+    // The Bitcoin transaction should commit to current lock script, and the
+    // first input of current transaction, which could be spent only once, so
+    // the proof couldn't be replayed in other transactions.
+    let mut commitment = Vec::with_capacity(COMMITMENT_SIZE);
+    commitment.extend_from_slice(&hl::load_script_hash()?);
+    commitment.extend_from_slice(hl::load_input_out_point(0, Source::Input)?.as_slice());
+    let committed = tx.outputs().iter().any(|output| {
+        let script = output.script_pubkey;
+        script.len() == COMMITMENT_PREFIX.len() + COMMITMENT_SIZE
+            && script.starts_with(&COMMITMENT_PREFIX)
+            && script[COMMITMENT_PREFIX.len()..] == commitment[..]
+    });
+    if !committed {
+        return Err(InternalError::CommitmentNotFound.into());
+    }
+
+    debug!("{} DONE.", module_path!());
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use alloc::vec::Vec;
+
+use ckb_bitcoin_spv_consumer::{
+    delegate::{self, VerifiedTransaction, VerifierArgs},
+    error::Result,
+    load_client_cell_dep_by_index,
+    transaction::Transaction,
+    verify_transaction,
+};
+use ckb_std::{debug, env};
+
+pub fn main() -> Result<()> {
+    debug!("{} Starting ...", module_path!());
+
+    let argv = env::argv()
+        .iter()
+        .map(|arg| arg.to_bytes())
+        .collect::<Vec<_>>();
+    let args = VerifierArgs::decode(&argv)?;
+
+    let client = load_client_cell_dep_by_index(args.cell_dep_index, &args.spv_type_hash)?;
+    let tx = Transaction::parse(&args.raw_tx)?;
+    verify_transaction(&client, &tx, &args.tx_proof, args.confirmations)?;
+
+    // Returns the verified result to the parent, if current script is spawned.
+    // When it's executed by `exec`, there is no parent, only the exit code matters.
+    let content = VerifiedTransaction::encode(&tx);
+    delegate::return_verified_result(&content)?;
+    debug!("returns {} bytes", content.len());
+
+    debug!("{} DONE.", module_path!());
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use ckb_std::{ckb_constants::Source, debug};
+
+use crate::{
+    error::{InternalError, Result},
+    operations, utilities,
+};
+
+pub fn main() -> Result<()> {
+    debug!("{} Starting ...", module_path!());
+
+    let inputs = utilities::load_registry_cells(Source::GroupInput)?;
+    let outputs = utilities::load_registry_cells(Source::GroupOutput)?;
+
+    debug!("cells in  inputs: {}", inputs.len());
+    debug!("cells in outputs: {}", outputs.len());
+
+    match (inputs.len(), outputs.len()) {
+        (0, _) => {
+            debug!("create a registry");
+            operations::create_registry(&outputs)?;
+        }
+        (_, 0) => {
+            debug!("destroy is not allowed");
+            return Err(InternalError::UnknownOperation.into());
+        }
+        (_, _) => {
+            debug!("insert keys");
+            operations::insert_keys(inputs, outputs)?;
+        }
+    }
+
+    debug!("{} DONE.", module_path!());
+
+    Ok(())
+}
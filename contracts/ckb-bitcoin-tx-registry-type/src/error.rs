@@ -0,0 +1,60 @@
+use core::result;
+
+use ckb_bitcoin_spv_consumer::error::Error as ConsumerError;
+use ckb_std::error::SysError;
+
+pub type Result<T> = result::Result<T, Error>;
+
+#[repr(i8)]
+pub enum InternalError {
+    // 0x50 ~ 0x5f: Errors before doing operations.
+    UnknownOperation = 0x50,
+    ArgsMalformed,
+
+    // 0x60 ~ 0x6f: Errors when create.
+    CreateCellsCountNotMatched = 0x60,
+    CreateIncorrectUniqueId,
+    CreateNotHeadCell,
+
+    // 0x70 ~ 0x7f: Errors when insert.
+    InsertDuplicatedKeys = 0x70,
+    InsertRangeStartNotFound,
+    InsertRangeIsBroken,
+    InsertRangeEndNotFound,
+    InsertUnknownCells,
+    InsertWithoutConsumer,
+}
+
+pub enum Error {
+    // 0x01 ~ 0x4f: Errors from the consumer crate, includes the system errors.
+    Consumer(ConsumerError),
+    // 0x50 ~ 0x7f: Errors in current crate.
+    Internal(InternalError),
+}
+
+impl From<SysError> for Error {
+    fn from(err: SysError) -> Self {
+        Self::Consumer(err.into())
+    }
+}
+
+impl From<ConsumerError> for Error {
+    fn from(err: ConsumerError) -> Self {
+        Self::Consumer(err)
+    }
+}
+
+impl From<InternalError> for Error {
+    fn from(err: InternalError) -> Self {
+        Self::Internal(err)
+    }
+}
+
+impl From<Error> for i8 {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Consumer(e) => e.into(),
+            Error::Internal(e) => e as i8,
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use ckb_bitcoin_spv_consumer::registry::RegistryCell;
+use ckb_std::{ckb_constants::Source, debug, high_level as hl};
+
+use crate::{
+    error::{InternalError, Result},
+    utilities,
+};
+
+pub(crate) fn create_registry(outputs: &[RegistryCell]) -> Result<()> {
+    if outputs.len() != 1 {
+        return Err(InternalError::CreateCellsCountNotMatched.into());
+    }
+    if outputs[0] != RegistryCell::head() {
+        return Err(InternalError::CreateNotHeadCell.into());
+    }
+    // Finds the index of the head cell in the outputs, to calculate the type id.
+    let script_hash = hl::load_script_hash()?;
+    let output_index = hl::QueryIter::new(hl::load_cell_type_hash, Source::Output)
+        .position(|type_hash_opt| type_hash_opt == Some(script_hash))
+        .ok_or(InternalError::CreateCellsCountNotMatched)?;
+    debug!("head cell is outputs[{output_index}]");
+    let type_id = utilities::load_then_calculate_type_id(output_index)?;
+    let args = utilities::load_registry_args()?;
+    if args.type_id != type_id {
+        return Err(InternalError::CreateIncorrectUniqueId.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use alloc::vec::Vec;
+
+use ckb_bitcoin_spv_consumer::registry::RegistryCell;
+use ckb_std::{ckb_constants::Source, debug, high_level as hl};
+
+use crate::{
+    error::{InternalError, Result},
+    utilities,
+};
+
+/// Checks that the output cells are the input cells with new keys inserted.
+///
+/// Each input cell is a range from its key to its next key, and it should
+/// be split into a chain of output cells, which starts with the key of the
+/// input cell and ends with the next key of the input cell.
+///
+/// Keys could not be removed, so each key could be inserted only once.
+///
+/// The consumer of the registry should be in the transaction, so only keys
+/// which are verified by the consumer could be inserted.
+pub(crate) fn insert_keys(
+    mut inputs: Vec<RegistryCell>,
+    mut outputs: Vec<RegistryCell>,
+) -> Result<()> {
+    let args = utilities::load_registry_args()?;
+    let has_consumer = [Source::Input, Source::Output].into_iter().any(|source| {
+        hl::QueryIter::new(hl::load_cell_type_hash, source)
+            .any(|type_hash_opt| type_hash_opt == Some(args.consumer_type_hash))
+    });
+    if !has_consumer {
+        debug!("the consumer is not in the transaction");
+        return Err(InternalError::InsertWithoutConsumer.into());
+    }
+
+    inputs.sort_unstable_by(|a, b| a.key.cmp(&b.key));
+    outputs.sort_unstable_by(|a, b| a.key.cmp(&b.key));
+    if outputs.windows(2).any(|pair| pair[0].key == pair[1].key) {
+        return Err(InternalError::InsertDuplicatedKeys.into());
+    }
+    let mut outputs_iter = outputs.iter();
+    for input in &inputs {
+        debug!("check range {:02x?} .. {:02x?}", input.key, input.next);
+        let mut current = outputs_iter
+            .next()
+            .ok_or(InternalError::InsertRangeStartNotFound)?;
+        if current.key != input.key {
+            return Err(InternalError::InsertRangeStartNotFound.into());
+        }
+        // Keys in the chain are strictly increasing, since the key of each
+        // cell is less than its next key.
+        while current.next != input.next {
+            let next = outputs_iter
+                .next()
+                .ok_or(InternalError::InsertRangeEndNotFound)?;
+            if next.key != current.next {
+                return Err(InternalError::InsertRangeIsBroken.into());
+            }
+            debug!(">>> inserted key {:02x?}", next.key);
+            current = next;
+        }
+    }
+    if outputs_iter.next().is_some() {
+        return Err(InternalError::InsertUnknownCells.into());
+    }
+    Ok(())
+}
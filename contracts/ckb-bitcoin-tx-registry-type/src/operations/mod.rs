@@ -0,0 +1,5 @@
+mod create;
+mod insert;
+
+pub(crate) use self::create::create_registry;
+pub(crate) use self::insert::insert_keys;
@@ -0,0 +1,57 @@
+use alloc::vec::Vec;
+
+use ckb_bitcoin_spv_consumer::registry::{RegistryCell, REGISTRY_ARGS_SIZE};
+use ckb_hash::{new_blake2b, BLAKE2B_LEN};
+use ckb_std::{ckb_constants::Source, ckb_types::prelude::*, debug, high_level as hl};
+
+use crate::error::{InternalError, Result};
+
+/// The args of this type script.
+///
+/// - The type id of the registry, 32 bytes.
+/// - The type script hash of the consumer, 32 bytes.
+pub(crate) struct RegistryArgs {
+    pub(crate) type_id: [u8; 32],
+    pub(crate) consumer_type_hash: [u8; 32],
+}
+
+pub(crate) fn load_registry_args() -> Result<RegistryArgs> {
+    let script = hl::load_script()?;
+    let args = script.args().raw_data();
+    if args.len() != REGISTRY_ARGS_SIZE {
+        return Err(InternalError::ArgsMalformed.into());
+    }
+    let mut type_id = [0u8; 32];
+    type_id.copy_from_slice(&args[..32]);
+    let mut consumer_type_hash = [0u8; 32];
+    consumer_type_hash.copy_from_slice(&args[32..]);
+    Ok(RegistryArgs {
+        type_id,
+        consumer_type_hash,
+    })
+}
+
+/// Loads all registry cells in the source.
+pub(crate) fn load_registry_cells(source: Source) -> Result<Vec<RegistryCell>> {
+    let mut cells = Vec::new();
+    for (_index, data) in hl::QueryIter::new(hl::load_cell_data, source).enumerate() {
+        let cell = RegistryCell::from_slice(&data)?;
+        debug!(
+            "registry cell (index={_index}): key = {:02x?}, next = {:02x?}",
+            cell.key, cell.next
+        );
+        cells.push(cell);
+    }
+    Ok(cells)
+}
+
+/// Calculates the type id as same as the built-in type id script.
+pub(crate) fn load_then_calculate_type_id(output_index: usize) -> Result<[u8; BLAKE2B_LEN]> {
+    let input = hl::load_input(0, Source::Input)?;
+    let mut blake2b = new_blake2b();
+    blake2b.update(input.as_slice());
+    blake2b.update(&(output_index as u64).to_le_bytes());
+    let mut ret = [0; BLAKE2B_LEN];
+    blake2b.finalize(&mut ret);
+    Ok(ret)
+}
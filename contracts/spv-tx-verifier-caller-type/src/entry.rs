@@ -0,0 +1,99 @@
+use alloc::vec::Vec;
+
+use ckb_bitcoin_spv_consumer::{
+    delegate::{self, VerifiedTransaction, VerifierArgs},
+    load_client_cell_dep,
+    transaction::Transaction,
+};
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{core::ScriptHashType, packed::BytesVecReader, prelude::*},
+    debug, high_level as hl,
+};
+
+use crate::error::{InternalError, Result};
+
+const ARGS_SIZE: usize = 32 + 32 + 4 + 1;
+const MODE_EXEC: u8 = 0;
+const MODE_SPAWN: u8 = 1;
+
+/// Verifies a Bitcoin transaction by the standalone verifier script.
+///
+/// The args is:
+/// - The type hash of the SPV instance, 32 bytes.
+/// - The data hash of the verifier script, 32 bytes.
+/// - The minimum confirmations, `u32` in little-endian.
+/// - The mode: `0` for `exec`, `1` for `spawn`.
+///
+/// The witness is the same as the deposit mint type script: a `BytesVec`
+/// of the raw transaction and its proof, in the field `output_type` of
+/// `WitnessArgs`.
+pub fn main() -> Result<()> {
+    debug!("{} Starting ...", module_path!());
+
+    let script = hl::load_script()?;
+    let script_args = script.args();
+    let args = script_args.as_reader().raw_data();
+    if args.len() != ARGS_SIZE {
+        return Err(InternalError::ArgsMalformed.into());
+    }
+    let mut spv_type_hash = [0u8; 32];
+    spv_type_hash.copy_from_slice(&args[..32]);
+    let verifier_code_hash = &args[32..64];
+    let mut confirmations = [0u8; 4];
+    confirmations.copy_from_slice(&args[64..68]);
+    let mode = args[68];
+
+    let witness_args = hl::load_witness_args(0, Source::GroupOutput)?;
+    let witness = witness_args
+        .output_type()
+        .to_opt()
+        .ok_or(InternalError::WitnessIsNotExisted)?
+        .raw_data();
+    let items =
+        BytesVecReader::from_slice(&witness).map_err(|_| InternalError::WitnessMalformed)?;
+    if items.len() != 2 {
+        return Err(InternalError::WitnessMalformed.into());
+    }
+    let raw_tx = items.get_unchecked(0).raw_data();
+    let tx_proof = items.get_unchecked(1).raw_data();
+
+    let (cell_dep_index, _) = load_client_cell_dep(&spv_type_hash)?;
+    let verifier_args = VerifierArgs {
+        cell_dep_index,
+        spv_type_hash,
+        confirmations: u32::from_le_bytes(confirmations),
+        raw_tx: raw_tx.to_vec(),
+        tx_proof: tx_proof.to_vec(),
+    };
+
+    match mode {
+        MODE_EXEC => {
+            debug!("verify the transaction by exec");
+            let argv = verifier_args.encode();
+            let argv = argv.iter().map(|arg| arg.as_c_str()).collect::<Vec<_>>();
+            // In the VM version 1, the verifier script only returns the exit
+            // code, see the README of the verifier script.
+            hl::exec_cell(verifier_code_hash, ScriptHashType::Data1, &argv)?;
+            // The current script is replaced by the verifier script when `exec`
+            // succeeded, so it should never be reached.
+            Err(InternalError::ExecReturned.into())
+        }
+        MODE_SPAWN => {
+            debug!("verify the transaction by spawn");
+            let content = delegate::spawn_verifier(
+                verifier_code_hash,
+                ScriptHashType::Data2,
+                &verifier_args,
+            )?;
+            let verified = VerifiedTransaction::decode(&content)?;
+            let tx = Transaction::parse(raw_tx)?;
+            if verified.txid != tx.txid() || verified.outputs.len() != tx.outputs().len() {
+                return Err(InternalError::ResultMismatch.into());
+            }
+            debug!("{} DONE.", module_path!());
+            Ok(())
+        }
+        _ => Err(InternalError::ArgsMalformed.into()),
+    }
+}
@@ -0,0 +1,148 @@
+//! Verify multiple Bitcoin transactions, which maybe in different blocks,
+//! with one batched proof.
+//!
+//! Instead of one MMR proof and one merkle proof for each transaction, a
+//! batched proof contains only one MMR proof for all blocks, and one merkle
+//! proof for all transactions in the same block.
+//!
+//! A batched proof is serialized as:
+//! - The MMR proof of all block headers: the count of items as a
+//!   `CompactSize`, then the packed `HeaderDigest` items.
+//! - The proofs of blocks: the count of blocks as a `CompactSize`, then for
+//!   each block:
+//!   - The height, as a 4-bytes little-endian unsigned integer.
+//!   - The `MerkleBlock` which contains the transactions, as bytes with a
+//!     `CompactSize` length prefix.
+//!
+//! The blocks should be sorted by their heights, and each block should be
+//! proven only once.
+
+use alloc::vec::Vec;
+
+use bitcoin::{consensus::deserialize, hashes::Hash as _, MerkleBlock};
+use ckb_bitcoin_spv_verifier::types::{core, packed};
+use ckb_std::debug;
+
+use crate::{
+    error::{InternalError, Result},
+    header,
+    transaction::{write_compact_size, Cursor, Txid},
+};
+
+/// A batched proof for transactions in multiple blocks.
+pub struct BatchProof<'r> {
+    /// The packed `HeaderDigest` items of the MMR proof.
+    pub header_proof: Vec<&'r [u8]>,
+    pub blocks: Vec<BlockProof<'r>>,
+}
+
+/// The proof of transactions in one block.
+pub struct BlockProof<'r> {
+    pub height: u32,
+    /// The serialized `MerkleBlock`.
+    pub merkle_block: &'r [u8],
+}
+
+/// A transaction which is verified by a batched proof.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct VerifiedTxid {
+    pub height: u32,
+    /// The position of the transaction in its block.
+    pub tx_index: u32,
+    pub txid: Txid,
+}
+
+impl<'r> BatchProof<'r> {
+    /// Parses a batched proof, all bytes should be consumed.
+    pub fn parse(raw: &'r [u8]) -> Result<Self> {
+        Self::read(raw).map_err(|_| InternalError::BatchProofMalformed.into())
+    }
+
+    fn read(raw: &'r [u8]) -> Result<Self> {
+        let mut cursor = Cursor::new(raw);
+        let header_proof = header::read_header_proof(&mut cursor)?;
+        let blocks_count = cursor.read_length()?;
+        let mut blocks = Vec::with_capacity(blocks_count);
+        for _ in 0..blocks_count {
+            let height = cursor.read_u32()?;
+            let merkle_block = cursor.read_var_bytes()?;
+            blocks.push(BlockProof {
+                height,
+                merkle_block,
+            });
+        }
+        if !cursor.is_finished() {
+            return Err(InternalError::BitcoinDataTrailingBytes.into());
+        }
+        Ok(Self {
+            header_proof,
+            blocks,
+        })
+    }
+
+    /// Serializes the batched proof.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut raw = Vec::new();
+        header::write_header_proof(&mut raw, &self.header_proof);
+        write_compact_size(&mut raw, self.blocks.len());
+        for block in &self.blocks {
+            raw.extend_from_slice(&block.height.to_le_bytes());
+            write_compact_size(&mut raw, block.merkle_block.len());
+            raw.extend_from_slice(block.merkle_block);
+        }
+        raw
+    }
+}
+
+impl<'r> BlockProof<'r> {
+    /// Extracts the transactions from the merkle proof, and checks that the
+    /// merkle proof matches the merkle root in the block header.
+    ///
+    /// Returns the block header and the transactions.
+    pub fn extract(&self) -> Result<(core::Header, Vec<VerifiedTxid>)> {
+        let merkle_block: MerkleBlock =
+            deserialize(self.merkle_block).map_err(|_| InternalError::BatchProofMalformed)?;
+        let mut matches = Vec::new();
+        let mut indexes = Vec::new();
+        let merkle_root = merkle_block
+            .txn
+            .extract_matches(&mut matches, &mut indexes)
+            .map_err(|_| InternalError::BatchProofMalformed)?;
+        if merkle_root != merkle_block.header.merkle_root || matches.is_empty() {
+            debug!("the merkle proof of block {} is malformed", self.height);
+            return Err(InternalError::BatchProofMalformed.into());
+        }
+        let txids = matches
+            .into_iter()
+            .zip(indexes)
+            .map(|(txid, tx_index)| VerifiedTxid {
+                height: self.height,
+                tx_index,
+                txid: txid.to_byte_array(),
+            })
+            .collect();
+        Ok((merkle_block.header, txids))
+    }
+}
+
+/// Verifies that all transactions in a batched proof are in the chain of an
+/// SPV client, and all of them have enough confirmations.
+///
+/// Returns all verified transactions, sorted by their heights and their
+/// positions in blocks.
+pub fn verify_batch(
+    client: &packed::SpvClient,
+    proof: &BatchProof,
+    confirmations: u32,
+) -> Result<Vec<VerifiedTxid>> {
+    debug!("verify {} blocks in batch", proof.blocks.len());
+    let mut headers = Vec::with_capacity(proof.blocks.len());
+    let mut verified = Vec::new();
+    for block in &proof.blocks {
+        let (header, txids) = block.extract()?;
+        headers.push((block.height, header));
+        verified.extend(txids);
+    }
+    header::verify_headers(client, &headers, &proof.header_proof, confirmations)?;
+    Ok(verified)
+}
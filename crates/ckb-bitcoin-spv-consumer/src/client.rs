@@ -0,0 +1,105 @@
+//! Load SPV cells from the cell deps.
+
+use alloc::vec::Vec;
+
+use ckb_bitcoin_spv_verifier::types::{
+    core::SpvInfo,
+    packed::{self, SpvClientReader, SpvInfoReader},
+    prelude::*,
+};
+#[cfg(debug_assertions)]
+use ckb_std::ckb_types::prelude::Pack as StdPack;
+use ckb_std::{ckb_constants::Source, debug, high_level as hl};
+
+use crate::error::{InternalError, Result};
+
+/// Finds the only one SPV client cell in cell deps, which belongs to the SPV
+/// instance whose type script hash is `spv_type_hash`, then loads it.
+///
+/// The SPV info cell of the same instance is allowed in the cell deps, it
+/// will be skipped.
+///
+/// Returns the index of the cell dep and the SPV client.
+pub fn load_client_cell_dep(spv_type_hash: &[u8; 32]) -> Result<(usize, packed::SpvClient)> {
+    let mut client_opt = None;
+    for (index, type_hash_opt) in
+        hl::QueryIter::new(hl::load_cell_type_hash, Source::CellDep).enumerate()
+    {
+        if let Some(type_hash) = type_hash_opt {
+            debug!(
+                "{index}-th type hash of cell-deps: {:#x}",
+                StdPack::pack(&type_hash)
+            );
+            if &type_hash != spv_type_hash {
+                continue;
+            }
+            let data = hl::load_cell_data(index, Source::CellDep)?;
+            if let Ok(client) = SpvClientReader::from_slice(&data) {
+                debug!("cell-dep client = {client} (index={index})");
+                if client_opt.is_some() {
+                    return Err(InternalError::ClientCellDepMoreThanOne.into());
+                }
+                client_opt = Some((index, client.to_entity()));
+            }
+        }
+    }
+    client_opt.ok_or_else(|| InternalError::ClientCellDepNotFound.into())
+}
+
+/// Loads the SPV client cell at the `index` of cell deps, and checks that it
+/// belongs to the SPV instance whose type script hash is `spv_type_hash`.
+pub fn load_client_cell_dep_by_index(
+    index: usize,
+    spv_type_hash: &[u8; 32],
+) -> Result<packed::SpvClient> {
+    let type_hash_opt = hl::load_cell_type_hash(index, Source::CellDep)?;
+    if type_hash_opt.as_ref() != Some(spv_type_hash) {
+        debug!("the {index}-th cell dep is not a cell of the SPV instance");
+        return Err(InternalError::ClientCellDepNotFound.into());
+    }
+    let data = hl::load_cell_data(index, Source::CellDep)?;
+    let client =
+        SpvClientReader::from_slice(&data).map_err(|_| InternalError::ClientCellDepNotFound)?;
+    debug!("cell-dep client = {client} (index={index})");
+    Ok(client.to_entity())
+}
+
+/// Finds the SPV info cell and the tip SPV client cell in cell deps, which
+/// belong to the SPV instance whose type script hash is `spv_type_hash`,
+/// then loads the tip SPV client.
+///
+/// The tip SPV client is resolved through the `tip_client_id` of the SPV
+/// info cell, so any other SPV client cells, which maybe stale, are not
+/// accepted.
+///
+/// Returns the index of the cell dep and the tip SPV client.
+pub fn load_tip_client_cell_dep(spv_type_hash: &[u8; 32]) -> Result<(usize, packed::SpvClient)> {
+    let mut info_opt: Option<SpvInfo> = None;
+    let mut clients = Vec::new();
+    for (index, type_hash_opt) in
+        hl::QueryIter::new(hl::load_cell_type_hash, Source::CellDep).enumerate()
+    {
+        if type_hash_opt.as_ref() != Some(spv_type_hash) {
+            continue;
+        }
+        let data = hl::load_cell_data(index, Source::CellDep)?;
+        if let Ok(client) = SpvClientReader::from_slice(&data) {
+            debug!("cell-dep client = {client} (index={index})");
+            clients.push((index, client.to_entity()));
+        } else if let Ok(info) = SpvInfoReader::from_slice(&data) {
+            debug!("cell-dep info = {info} (index={index})");
+            if info_opt.is_some() {
+                return Err(InternalError::InfoCellDepMoreThanOne.into());
+            }
+            info_opt = Some(info.unpack());
+        }
+    }
+    let tip_client_id = info_opt
+        .ok_or(InternalError::InfoCellDepNotFound)?
+        .tip_client_id;
+    debug!("tip client id = {tip_client_id}");
+    clients
+        .into_iter()
+        .find(|(_, client)| u8::from(client.id()) == tip_client_id)
+        .ok_or_else(|| InternalError::TipClientCellDepNotFound.into())
+}
@@ -0,0 +1,60 @@
+//! Verify commitments in `OP_RETURN` outputs of Bitcoin transactions.
+
+use alloc::vec::Vec;
+
+use ckb_bitcoin_spv_verifier::types::packed;
+use ckb_std::debug;
+
+use crate::{
+    error::{InternalError, Result},
+    script,
+    transaction::Transaction,
+    verify_transaction,
+};
+
+/// The expected payload of an `OP_RETURN` output.
+#[derive(Clone, Copy)]
+pub enum Payload<'a> {
+    /// The payload should be equal to the provided bytes.
+    Exact(&'a [u8]),
+    /// The payload should start with the provided bytes.
+    Prefix(&'a [u8]),
+}
+
+impl<'a> Payload<'a> {
+    pub fn is_matched(&self, payload: &[u8]) -> bool {
+        match self {
+            Self::Exact(expected) => payload == *expected,
+            Self::Prefix(prefix) => payload.starts_with(prefix),
+        }
+    }
+}
+
+/// Finds the first `OP_RETURN` output whose payload is matched, then returns
+/// the index of the output and the full payload.
+pub fn find_op_return(tx: &Transaction, expected: Payload) -> Option<(usize, Vec<u8>)> {
+    tx.outputs().iter().enumerate().find_map(|(index, output)| {
+        script::op_return_payload(output.script_pubkey)
+            .filter(|payload| expected.is_matched(payload))
+            .map(|payload| (index, payload))
+    })
+}
+
+/// Verifies that a transaction is in the chain of an SPV client, with enough
+/// confirmations, and it has an `OP_RETURN` output whose payload is matched.
+///
+/// Returns the full payload, so the caller could use the remaining part when
+/// only a prefix is expected.
+pub fn verify_op_return_commitment(
+    client: &packed::SpvClient,
+    tx: &Transaction,
+    tx_proof: &[u8],
+    confirmations: u32,
+    expected: Payload,
+) -> Result<Vec<u8>> {
+    let (_index, payload) =
+        find_op_return(tx, expected).ok_or(InternalError::CommitmentNotFound)?;
+    debug!("found the commitment in output {_index}: {payload:02x?}");
+    verify_transaction(client, tx, tx_proof, confirmations)?;
+    Ok(payload)
+}
@@ -0,0 +1,233 @@
+//! Delegate the verification of Bitcoin transactions to the standalone
+//! verifier script, through `exec` or `spawn`.
+//!
+//! The verifier script accepts 5 arguments, all are raw bytes:
+//! - The index of the SPV client cell in cell deps, `u32` in little-endian.
+//! - The type script hash of the SPV instance, 32 bytes.
+//! - The minimum confirmations, `u32` in little-endian.
+//! - The raw Bitcoin transaction.
+//! - The `TransactionProof` of the transaction.
+//!
+//! Since `argv` are C strings, the nul byte is escaped: `0x00` is encoded as
+//! `0x01 0x01`, and `0x01` is encoded as `0x01 0x02`, all other bytes are
+//! unchanged.
+//!
+//! If the verification passed, it exits with `0`, otherwise, it exits with
+//! the error code in [`crate::error`].
+//!
+//! When it's spawned with an inherited pipe, the verified result is written
+//! into that pipe, which requires the VM version 2: the length of the result (`u32` in little-endian), then
+//! the txid (32 bytes, internal byte order), then the outputs as they are
+//! serialized in the transaction.
+
+use alloc::{ffi::CString, vec::Vec};
+
+use crate::{
+    error::{InternalError, Result},
+    transaction::{self, Transaction, TxOut, Txid},
+};
+
+/// The count of the arguments of the verifier script.
+pub const ARGS_COUNT: usize = 5;
+
+const ESCAPE: u8 = 0x01;
+
+/// The arguments of the verifier script.
+pub struct VerifierArgs {
+    pub cell_dep_index: usize,
+    pub spv_type_hash: [u8; 32],
+    pub confirmations: u32,
+    pub raw_tx: Vec<u8>,
+    pub tx_proof: Vec<u8>,
+}
+
+/// The result of a verified transaction, which is returned by the verifier
+/// script when it's spawned.
+pub struct VerifiedTransaction<'r> {
+    pub txid: Txid,
+    pub outputs: Vec<TxOut<'r>>,
+}
+
+impl VerifierArgs {
+    /// Encodes the arguments as C strings, which could be used as `argv`.
+    pub fn encode(&self) -> Vec<CString> {
+        let cell_dep_index =
+            u32::try_from(self.cell_dep_index).expect("the index of a cell dep fits in u32");
+        let args: [&[u8]; ARGS_COUNT] = [
+            &cell_dep_index.to_le_bytes(),
+            &self.spv_type_hash,
+            &self.confirmations.to_le_bytes(),
+            &self.raw_tx,
+            &self.tx_proof,
+        ];
+        args.into_iter()
+            .map(|arg| CString::new(escape(arg)).expect("no nul byte after escaped"))
+            .collect()
+    }
+
+    /// Decodes the arguments from `argv`.
+    pub fn decode(argv: &[&[u8]]) -> Result<Self> {
+        if argv.len() != ARGS_COUNT {
+            return Err(InternalError::VerifierArgsMalformed.into());
+        }
+        let cell_dep_index = u32::from_le_bytes(decode_array(argv[0])?) as usize;
+        let spv_type_hash = decode_array(argv[1])?;
+        let confirmations = u32::from_le_bytes(decode_array(argv[2])?);
+        let raw_tx = unescape(argv[3])?;
+        let tx_proof = unescape(argv[4])?;
+        let args = Self {
+            cell_dep_index,
+            spv_type_hash,
+            confirmations,
+            raw_tx,
+            tx_proof,
+        };
+        Ok(args)
+    }
+}
+
+impl<'r> VerifiedTransaction<'r> {
+    /// Encodes the result of a verified transaction.
+    pub fn encode(tx: &Transaction) -> Vec<u8> {
+        let raw_outputs = tx.raw_outputs();
+        let mut content = Vec::with_capacity(32 + raw_outputs.len());
+        content.extend_from_slice(&tx.txid());
+        content.extend_from_slice(raw_outputs);
+        content
+    }
+
+    /// Decodes the result of a verified transaction.
+    pub fn decode(content: &'r [u8]) -> Result<Self> {
+        if content.len() < 32 {
+            return Err(InternalError::VerifierResultMalformed.into());
+        }
+        let mut txid = [0u8; 32];
+        txid.copy_from_slice(&content[..32]);
+        let outputs = transaction::parse_outputs(&content[32..])
+            .map_err(|_| InternalError::VerifierResultMalformed)?;
+        Ok(Self { txid, outputs })
+    }
+}
+
+/// Spawns the verifier script, which is in the cell deps, then returns the
+/// verified result, which is read from an inherited pipe.
+#[cfg(feature = "ckb2023")]
+pub fn spawn_verifier(
+    code_hash: &[u8],
+    hash_type: ckb_std::ckb_types::core::ScriptHashType,
+    args: &VerifierArgs,
+) -> Result<Vec<u8>> {
+    use ckb_std::{debug, high_level as hl, syscalls};
+
+    let argv = args.encode();
+    let argv = argv.iter().map(|arg| arg.as_c_str()).collect::<Vec<_>>();
+    let (read_fd, write_fd) = syscalls::pipe()?;
+    let pid = hl::spawn_cell(code_hash, hash_type, &argv, &[write_fd])?;
+    // The result should be read before waiting, otherwise, the verifier
+    // script is blocked when it writes the result.
+    // When the verifier script is failed, the pipe is closed without any
+    // result, so the exit code is checked before the result.
+    let content = read_result(read_fd);
+    let exit_code = syscalls::wait(pid)?;
+    if exit_code != 0 {
+        debug!("the verifier script is failed with exit code {exit_code}");
+        return Err(InternalError::VerifierFailed.into());
+    }
+    content
+}
+
+#[cfg(feature = "ckb2023")]
+fn read_result(fd: u64) -> Result<Vec<u8>> {
+    use alloc::vec;
+
+    let mut length = [0u8; 4];
+    read_exact(fd, &mut length)?;
+    let mut content = vec![0u8; u32::from_le_bytes(length) as usize];
+    read_exact(fd, &mut content)?;
+    Ok(content)
+}
+
+/// Returns the verified result to the parent, through the inherited pipe.
+///
+/// Does nothing if there is no inherited pipe, e.g. the verifier script is
+/// invoked by `exec`, only the exit code matters.
+///
+/// Pipes are only available in the VM version 2, so nothing is returned in
+/// older versions, e.g. the verifier script is invoked by `exec` with the
+/// hash type `data1`.
+#[cfg(feature = "ckb2023")]
+pub fn return_verified_result(content: &[u8]) -> Result<()> {
+    use ckb_std::syscalls;
+
+    if syscalls::vm_version()? < 2 {
+        return Ok(());
+    }
+    let mut fds = [0u64; 1];
+    if syscalls::inherited_fds(&mut fds) == 0 {
+        return Ok(());
+    }
+    let length = u32::try_from(content.len())
+        .map_err(|_| InternalError::VerifierResultMalformed)?
+        .to_le_bytes();
+    write_all(fds[0], &length)?;
+    write_all(fds[0], content)?;
+    syscalls::close(fds[0])?;
+    Ok(())
+}
+
+#[cfg(feature = "ckb2023")]
+fn read_exact(fd: u64, buf: &mut [u8]) -> Result<()> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let read = ckb_std::syscalls::read(fd, &mut buf[filled..])?;
+        if read == 0 {
+            return Err(InternalError::VerifierResultMalformed.into());
+        }
+        filled += read;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "ckb2023")]
+fn write_all(fd: u64, buf: &[u8]) -> Result<()> {
+    let mut written = 0;
+    while written < buf.len() {
+        written += ckb_std::syscalls::write(fd, &buf[written..])?;
+    }
+    Ok(())
+}
+
+fn escape(bytes: &[u8]) -> Vec<u8> {
+    let mut escaped = Vec::with_capacity(bytes.len());
+    for byte in bytes {
+        match *byte {
+            0x00 => escaped.extend_from_slice(&[ESCAPE, 0x01]),
+            ESCAPE => escaped.extend_from_slice(&[ESCAPE, 0x02]),
+            byte => escaped.push(byte),
+        }
+    }
+    escaped
+}
+
+fn unescape(escaped: &[u8]) -> Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(escaped.len());
+    let mut iter = escaped.iter();
+    while let Some(byte) = iter.next() {
+        match *byte {
+            ESCAPE => match iter.next() {
+                Some(0x01) => bytes.push(0x00),
+                Some(0x02) => bytes.push(ESCAPE),
+                _ => return Err(InternalError::VerifierArgsMalformed.into()),
+            },
+            0x00 => return Err(InternalError::VerifierArgsMalformed.into()),
+            byte => bytes.push(byte),
+        }
+    }
+    Ok(bytes)
+}
+
+fn decode_array<const N: usize>(escaped: &[u8]) -> Result<[u8; N]> {
+    unescape(escaped)?
+        .try_into()
+        .map_err(|_| InternalError::VerifierArgsMalformed.into())
+}
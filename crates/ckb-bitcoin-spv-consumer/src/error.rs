@@ -0,0 +1,116 @@
+use core::result;
+
+use ckb_bitcoin_spv_verifier::error::VerifyTxError;
+use ckb_std::error::SysError;
+
+pub type Result<T> = result::Result<T, Error>;
+
+/// Errors in current crate.
+///
+/// All error codes are less than `0x50`, so contracts which use this crate
+/// could use `0x50 ~ 0x7f` for their own errors.
+#[repr(i8)]
+pub enum InternalError {
+    // 0x01 ~ 0x0f: Errors from SDK, or other system errors.
+    IndexOutOfBound = 0x01,
+    ItemMissing,
+    LengthNotEnough,
+    Encoding,
+    Unknown,
+    WaitFailure,
+    InvalidFd,
+    OtherEndClosed,
+    MaxVmsSpawned,
+    MaxFdsCreated,
+
+    // 0x10 ~ 0x1f: Errors when load cells.
+    ClientCellDepNotFound = 0x10,
+    ClientCellDepMoreThanOne,
+    RegistryCellMalformed,
+    RegistryKeyIsUsed,
+    RegistryKeyIsNotInserted,
+    InfoCellDepNotFound,
+    InfoCellDepMoreThanOne,
+    TipClientCellDepNotFound,
+    RegistryKeyIsInsertedUnexpectedly,
+
+    // 0x20 ~ 0x2f: Errors when parse Bitcoin data.
+    BitcoinDataUnexpectedEnd = 0x20,
+    BitcoinDataTrailingBytes,
+    BitcoinDataNonCanonicalSize,
+    BitcoinTxUnsupportedFlag,
+    BitcoinTxNoInputs,
+    BitcoinTxSizeIs64,
+    BitcoinScriptTruncated,
+    BitcoinTxOutputNotFound,
+    BitcoinHeaderProofMalformed,
+
+    // 0x30 ~ 0x3f: Errors when verify proofs.
+    TxProofMalformed = 0x30,
+    VerifierArgsMalformed,
+    VerifierResultMalformed,
+    VerifierFailed,
+    QuorumConfigMalformed,
+    QuorumProofsCountMismatch,
+    QuorumNotReached,
+    CommitmentNotFound,
+    WitnessProofMalformed,
+    WitnessCommitmentNotFound,
+    WitnessCommitmentMismatch,
+    SpendProofMalformed,
+    OutPointNotSpent,
+    BatchProofMalformed,
+    HeaderUnconfirmed,
+    HeaderMmrProofFailed,
+}
+
+pub enum Error {
+    // 0x01 ~ 0x3f: Errors that not from external crates.
+    Internal(InternalError),
+    // 0x40 ~ 0x4f: Errors when verify a transaction against an SPV client.
+    VerifyTx(VerifyTxError),
+}
+
+impl From<SysError> for InternalError {
+    fn from(err: SysError) -> Self {
+        match err {
+            SysError::IndexOutOfBound => Self::IndexOutOfBound,
+            SysError::ItemMissing => Self::ItemMissing,
+            SysError::LengthNotEnough(_) => Self::LengthNotEnough,
+            SysError::Encoding => Self::Encoding,
+            SysError::WaitFailure => Self::WaitFailure,
+            SysError::InvalidFd => Self::InvalidFd,
+            SysError::OtherEndClosed => Self::OtherEndClosed,
+            SysError::MaxVmsSpawned => Self::MaxVmsSpawned,
+            SysError::MaxFdsCreated => Self::MaxFdsCreated,
+            SysError::Unknown(_) => Self::Unknown,
+        }
+    }
+}
+
+impl From<SysError> for Error {
+    fn from(err: SysError) -> Self {
+        Into::<InternalError>::into(err).into()
+    }
+}
+
+impl From<InternalError> for Error {
+    fn from(err: InternalError) -> Self {
+        Self::Internal(err)
+    }
+}
+
+impl From<VerifyTxError> for Error {
+    fn from(err: VerifyTxError) -> Self {
+        Self::VerifyTx(err)
+    }
+}
+
+impl From<Error> for i8 {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Internal(e) => e as i8,
+            Error::VerifyTx(e) => 0x40 + e as i8,
+        }
+    }
+}
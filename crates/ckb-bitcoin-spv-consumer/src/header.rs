@@ -0,0 +1,213 @@
+//! Prove Bitcoin block headers against the MMR of SPV clients, so the fields
+//! of headers, such as the timestamp, could be used by contracts.
+//!
+//! An SPV client only stores the hash of the tip block and the MMR root of
+//! all headers, so a header proof contains the full header:
+//! - The height, as a 4-bytes little-endian unsigned integer.
+//! - The header, 80 bytes, as it is serialized in Bitcoin.
+//! - The MMR proof of the header: the count of items as a `CompactSize`,
+//!   then the packed `HeaderDigest` items.
+
+use alloc::vec::Vec;
+
+use bitcoin::consensus::deserialize;
+use ckb_bitcoin_spv_verifier::{
+    types::{
+        core,
+        packed::{self, HeaderDigestReader},
+        prelude::*,
+    },
+    utilities::mmr::{
+        lib::{leaf_index_to_mmr_size, leaf_index_to_pos},
+        MMRProof,
+    },
+};
+use ckb_std::debug;
+
+use crate::{
+    error::{InternalError, Result},
+    transaction::{sha256, write_compact_size, Cursor},
+};
+
+/// The size of a serialized Bitcoin block header.
+pub const HEADER_SIZE: usize = 80;
+
+/// All fields of a Bitcoin block header, with its height and its hash.
+///
+/// All hashes are in internal byte order.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct HeaderFields {
+    pub height: u32,
+    pub block_hash: [u8; 32],
+    pub version: i32,
+    pub prev_blockhash: [u8; 32],
+    pub merkle_root: [u8; 32],
+    /// The timestamp, in seconds since the Unix epoch.
+    pub time: u32,
+    /// The target, in the compact format.
+    pub bits: u32,
+    pub nonce: u32,
+}
+
+/// A proof of a Bitcoin block header.
+pub struct HeaderProof<'r> {
+    pub height: u32,
+    pub header: &'r [u8; HEADER_SIZE],
+    /// The packed `HeaderDigest` items of the MMR proof.
+    pub header_proof: Vec<&'r [u8]>,
+}
+
+impl HeaderFields {
+    /// Parses the fields from a serialized header.
+    pub fn parse(height: u32, header: &[u8; HEADER_SIZE]) -> Self {
+        let field = |start: usize| -> [u8; 4] {
+            header[start..start + 4]
+                .try_into()
+                .expect("check size of the field")
+        };
+        let hash = |start: usize| -> [u8; 32] {
+            header[start..start + 32]
+                .try_into()
+                .expect("check size of the hash")
+        };
+        Self {
+            height,
+            block_hash: sha256(&sha256(header)),
+            version: i32::from_le_bytes(field(0)),
+            prev_blockhash: hash(4),
+            merkle_root: hash(36),
+            time: u32::from_le_bytes(field(68)),
+            bits: u32::from_le_bytes(field(72)),
+            nonce: u32::from_le_bytes(field(76)),
+        }
+    }
+}
+
+impl<'r> HeaderProof<'r> {
+    /// Parses a header proof, all bytes should be consumed.
+    pub fn parse(raw: &'r [u8]) -> Result<Self> {
+        Self::read(raw).map_err(|_| InternalError::BitcoinHeaderProofMalformed.into())
+    }
+
+    fn read(raw: &'r [u8]) -> Result<Self> {
+        let mut cursor = Cursor::new(raw);
+        let height = cursor.read_u32()?;
+        let header = cursor
+            .read_bytes(HEADER_SIZE)?
+            .try_into()
+            .expect("check size of the header");
+        let header_proof = read_header_proof(&mut cursor)?;
+        if !cursor.is_finished() {
+            return Err(InternalError::BitcoinDataTrailingBytes.into());
+        }
+        Ok(Self {
+            height,
+            header,
+            header_proof,
+        })
+    }
+
+    /// Serializes the header proof.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut raw = Vec::new();
+        raw.extend_from_slice(&self.height.to_le_bytes());
+        raw.extend_from_slice(self.header);
+        write_header_proof(&mut raw, &self.header_proof);
+        raw
+    }
+}
+
+/// Verifies that a block header is in the chain of an SPV client, and it has
+/// enough confirmations, then returns all fields of the header.
+pub fn verify_header(
+    client: &packed::SpvClient,
+    proof: &HeaderProof,
+    confirmations: u32,
+) -> Result<HeaderFields> {
+    let header: core::Header =
+        deserialize(&proof.header[..]).map_err(|_| InternalError::BitcoinHeaderProofMalformed)?;
+    verify_headers(
+        client,
+        &[(proof.height, header)],
+        &proof.header_proof,
+        confirmations,
+    )?;
+    Ok(HeaderFields::parse(proof.height, proof.header))
+}
+
+/// Verifies that the headers are in the MMR of an SPV client, and all of
+/// them have enough confirmations.
+///
+/// The headers should be sorted by their heights, without duplicates.
+pub(crate) fn verify_headers(
+    client: &packed::SpvClient,
+    headers: &[(u32, core::Header)],
+    header_proof: &[&[u8]],
+    confirmations: u32,
+) -> Result<()> {
+    let headers_mmr_root = client.headers_mmr_root();
+    let min_height: u32 = headers_mmr_root.min_height().unpack();
+    let max_height: u32 = headers_mmr_root.max_height().unpack();
+    debug!(
+        "verify {} headers, the client is [{min_height}, {max_height}]",
+        headers.len()
+    );
+    if headers.is_empty() {
+        return Err(InternalError::BitcoinHeaderProofMalformed.into());
+    }
+    let mut previous_height = None;
+    let mut leaves = Vec::with_capacity(headers.len());
+    for (height, header) in headers {
+        let height = *height;
+        if previous_height.is_some_and(|previous| previous >= height) || height < min_height {
+            return Err(InternalError::BitcoinHeaderProofMalformed.into());
+        }
+        previous_height = Some(height);
+        let required_height = height
+            .checked_add(confirmations)
+            .ok_or(InternalError::HeaderUnconfirmed)?;
+        if required_height > max_height {
+            debug!("block {height} doesn't have {confirmations} confirmations");
+            return Err(InternalError::HeaderUnconfirmed.into());
+        }
+        let position = leaf_index_to_pos(u64::from(height - min_height));
+        let digest = core::HeaderDigest::new_leaf(height, header).pack();
+        leaves.push((position, digest));
+    }
+    let header_proof = header_proof
+        .iter()
+        .map(|item| {
+            HeaderDigestReader::from_slice(item)
+                .map(|reader| reader.to_entity())
+                .map_err(|_| InternalError::BitcoinHeaderProofMalformed.into())
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mmr_size = leaf_index_to_mmr_size(u64::from(max_height - min_height));
+    let is_verified = MMRProof::new(mmr_size, header_proof)
+        .verify(headers_mmr_root, leaves)
+        .map_err(|_| InternalError::BitcoinHeaderProofMalformed)?;
+    if !is_verified {
+        debug!("the MMR proof of block headers is failed");
+        return Err(InternalError::HeaderMmrProofFailed.into());
+    }
+    Ok(())
+}
+
+/// Reads the MMR proof of headers: the count of items, then the packed
+/// `HeaderDigest` items.
+pub(crate) fn read_header_proof<'r>(cursor: &mut Cursor<'r>) -> Result<Vec<&'r [u8]>> {
+    let items_count = cursor.read_length()?;
+    let mut header_proof = Vec::with_capacity(items_count);
+    for _ in 0..items_count {
+        header_proof.push(cursor.read_bytes(packed::HeaderDigest::TOTAL_SIZE)?);
+    }
+    Ok(header_proof)
+}
+
+/// Writes the MMR proof of headers.
+pub(crate) fn write_header_proof(raw: &mut Vec<u8>, header_proof: &[&[u8]]) {
+    write_compact_size(raw, header_proof.len());
+    for item in header_proof {
+        raw.extend_from_slice(item);
+    }
+}
@@ -0,0 +1,31 @@
+//! Utilities for contracts which consume the Bitcoin SPV clients.
+//!
+//! The SPV clients are maintained by the CKB Bitcoin SPV type script, this
+//! crate helps other contracts to find the SPV clients in the cell deps,
+//! parse Bitcoin data, verify Bitcoin transactions and their witness data
+//! against the SPV clients, and avoid replaying the proofs with a registry.
+
+#![no_std]
+
+extern crate alloc;
+
+pub mod batch;
+mod client;
+pub mod commitment;
+pub mod delegate;
+pub mod error;
+pub mod header;
+pub mod output;
+pub mod quorum;
+pub mod registry;
+pub mod script;
+pub mod spend;
+pub mod transaction;
+mod verify;
+pub mod witness;
+
+#[cfg(test)]
+mod tests;
+
+pub use client::{load_client_cell_dep, load_client_cell_dep_by_index, load_tip_client_cell_dep};
+pub use verify::{verify_segwit_transaction, verify_transaction};
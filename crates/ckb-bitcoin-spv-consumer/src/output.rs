@@ -0,0 +1,151 @@
+//! Classify outputs of Bitcoin transactions by their script templates.
+//!
+//! All standard script templates of Bitcoin Core are recognized.
+
+use alloc::vec::Vec;
+
+use crate::{
+    error::{InternalError, Result},
+    script::{self, Instruction, Instructions, OP_1, OP_16, OP_RETURN},
+    transaction::{Transaction, TxOut},
+};
+
+const OP_DUP: u8 = 0x76;
+const OP_EQUAL: u8 = 0x87;
+const OP_EQUALVERIFY: u8 = 0x88;
+const OP_HASH160: u8 = 0xa9;
+const OP_CHECKSIG: u8 = 0xac;
+const OP_CHECKMULTISIG: u8 = 0xae;
+
+/// The max count of public keys in a standard bare multisig script.
+const MAX_MULTISIG_KEYS: usize = 3;
+
+/// The destination of an output, which is decided by its script pubkey.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Destination<'r> {
+    /// Pay to a public key, which is compressed or uncompressed.
+    P2pk(&'r [u8]),
+    /// Pay to the hash of a public key.
+    P2pkh(&'r [u8; 20]),
+    /// Pay to the hash of a script.
+    P2sh(&'r [u8; 20]),
+    /// Pay to a bare multisig, `required` of `keys`.
+    Multisig { required: u8, keys: Vec<&'r [u8]> },
+    /// Pay to the hash of a public key, witness version 0.
+    P2wpkh(&'r [u8; 20]),
+    /// Pay to the hash of a script, witness version 0.
+    P2wsh(&'r [u8; 32]),
+    /// Pay to a taproot output key, witness version 1.
+    P2tr(&'r [u8; 32]),
+    /// A witness program of the versions which are not defined yet.
+    WitnessUnknown { version: u8, program: &'r [u8] },
+    /// A data carrier output, the whole script is kept.
+    ///
+    /// The payload could be extracted by [`crate::script::op_return_payload`].
+    NullData(&'r [u8]),
+    /// Any other script.
+    NonStandard(&'r [u8]),
+}
+
+/// An output with its destination.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ClassifiedOutput<'r> {
+    /// The amount, in satoshis.
+    pub value: u64,
+    pub destination: Destination<'r>,
+}
+
+impl<'r> TxOut<'r> {
+    pub fn classify(&self) -> ClassifiedOutput<'r> {
+        ClassifiedOutput {
+            value: self.value,
+            destination: classify(self.script_pubkey),
+        }
+    }
+}
+
+impl<'r> Transaction<'r> {
+    /// Classifies the output at `index`.
+    pub fn classify_output(&self, index: usize) -> Result<ClassifiedOutput<'r>> {
+        self.outputs()
+            .get(index)
+            .map(TxOut::classify)
+            .ok_or_else(|| InternalError::BitcoinTxOutputNotFound.into())
+    }
+}
+
+/// Classifies a script pubkey.
+pub fn classify(script: &[u8]) -> Destination<'_> {
+    match script {
+        [OP_DUP, OP_HASH160, 0x14, hash @ .., OP_EQUALVERIFY, OP_CHECKSIG] if hash.len() == 20 => {
+            Destination::P2pkh(to_array(hash))
+        }
+        [OP_HASH160, 0x14, hash @ .., OP_EQUAL] if hash.len() == 20 => {
+            Destination::P2sh(to_array(hash))
+        }
+        [0x00, 0x14, hash @ ..] if hash.len() == 20 => Destination::P2wpkh(to_array(hash)),
+        [0x00, 0x20, hash @ ..] if hash.len() == 32 => Destination::P2wsh(to_array(hash)),
+        [OP_1, 0x20, key @ ..] if key.len() == 32 => Destination::P2tr(to_array(key)),
+        [version @ OP_1..=OP_16, length @ 0x02..=0x28, program @ ..]
+            if program.len() == usize::from(*length) =>
+        {
+            Destination::WitnessUnknown {
+                version: version - OP_1 + 1,
+                program,
+            }
+        }
+        [OP_RETURN, ..] => {
+            if script::op_return_payload(script).is_some() {
+                Destination::NullData(script)
+            } else {
+                Destination::NonStandard(script)
+            }
+        }
+        [length @ (33 | 65), key @ .., OP_CHECKSIG]
+            if key.len() == usize::from(*length) && is_public_key(key) =>
+        {
+            Destination::P2pk(key)
+        }
+        [.., OP_CHECKMULTISIG] => classify_multisig(script),
+        _ => Destination::NonStandard(script),
+    }
+}
+
+fn classify_multisig(script: &[u8]) -> Destination<'_> {
+    // Without the last `OP_CHECKMULTISIG`.
+    let mut instructions = Instructions::new(&script[..script.len() - 1]);
+    let required = match instructions.next() {
+        Some(Ok(Instruction::Op(op @ OP_1..=OP_16))) => op - OP_1 + 1,
+        _ => return Destination::NonStandard(script),
+    };
+    let mut keys = Vec::new();
+    while let Some(Ok(instruction)) = instructions.next() {
+        match instruction {
+            Instruction::PushBytes(key) if is_public_key(key) => keys.push(key),
+            Instruction::Op(op @ OP_1..=OP_16) => {
+                let is_standard = usize::from(op - OP_1 + 1) == keys.len()
+                    && keys.len() <= MAX_MULTISIG_KEYS
+                    && usize::from(required) <= keys.len()
+                    && instructions.next().is_none();
+                if is_standard {
+                    return Destination::Multisig { required, keys };
+                }
+                break;
+            }
+            _ => break,
+        }
+    }
+    Destination::NonStandard(script)
+}
+
+fn is_public_key(key: &[u8]) -> bool {
+    match key.first() {
+        Some(0x02 | 0x03) => key.len() == 33,
+        Some(0x04 | 0x06 | 0x07) => key.len() == 65,
+        _ => false,
+    }
+}
+
+fn to_array<const N: usize>(slice: &[u8]) -> &[u8; N] {
+    slice.try_into().expect("check length of the slice")
+}
@@ -0,0 +1,97 @@
+//! Verify Bitcoin transactions against multiple SPV instances.
+//!
+//! A single SPV instance relies on its bootstrap checkpoint and its
+//! operators, a quorum requires that a transaction is verified against the
+//! clients of at least `k` of `n` SPV instances.
+
+use alloc::vec::Vec;
+
+use ckb_std::debug;
+
+use crate::{
+    error::{InternalError, Result},
+    load_client_cell_dep,
+    transaction::Transaction,
+    verify_transaction,
+};
+
+/// A `k` of `n` quorum of SPV instances.
+pub struct Quorum<'a> {
+    threshold: usize,
+    spv_type_hashes: Vec<&'a [u8; 32]>,
+}
+
+impl<'a> Quorum<'a> {
+    /// Creates a quorum which requires `threshold` of the SPV instances.
+    ///
+    /// The type script hashes of the SPV instances should be unique, and the
+    /// threshold should be in `1..=n`.
+    pub fn new(threshold: usize, spv_type_hashes: Vec<&'a [u8; 32]>) -> Result<Self> {
+        if threshold == 0 || threshold > spv_type_hashes.len() {
+            return Err(InternalError::QuorumConfigMalformed.into());
+        }
+        for (index, hash) in spv_type_hashes.iter().enumerate() {
+            if spv_type_hashes[..index].contains(hash) {
+                return Err(InternalError::QuorumConfigMalformed.into());
+            }
+        }
+        Ok(Self {
+            threshold,
+            spv_type_hashes,
+        })
+    }
+
+    /// Parses a quorum from the concatenated type script hashes.
+    pub fn from_slice(threshold: usize, spv_type_hashes: &'a [u8]) -> Result<Self> {
+        if spv_type_hashes.is_empty() || spv_type_hashes.len() % 32 != 0 {
+            return Err(InternalError::QuorumConfigMalformed.into());
+        }
+        let hashes = spv_type_hashes
+            .chunks_exact(32)
+            .map(|chunk| chunk.try_into().expect("check length of the chunk"))
+            .collect();
+        Self::new(threshold, hashes)
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    pub fn spv_type_hashes(&self) -> &[&'a [u8; 32]] {
+        &self.spv_type_hashes
+    }
+
+    /// Verifies that a transaction is in the chains of the clients of at least
+    /// `threshold` SPV instances, and it has enough confirmations.
+    ///
+    /// The `tx_proofs` should be in the same order as the SPV instances, since
+    /// each SPV instance has its own MMR.
+    /// An empty proof means that the SPV instance is skipped, but if a proof is
+    /// provided, it should be valid.
+    pub fn verify_transaction(
+        &self,
+        tx: &Transaction,
+        tx_proofs: &[&[u8]],
+        confirmations: u32,
+    ) -> Result<()> {
+        if tx_proofs.len() != self.spv_type_hashes.len() {
+            return Err(InternalError::QuorumProofsCountMismatch.into());
+        }
+        let mut verified = 0;
+        for (spv_type_hash, tx_proof) in self.spv_type_hashes.iter().zip(tx_proofs.iter()) {
+            if tx_proof.is_empty() {
+                debug!("skip the SPV instance {spv_type_hash:02x?}");
+                continue;
+            }
+            let (_index, client) = load_client_cell_dep(spv_type_hash)?;
+            debug!("verify against the SPV instance {spv_type_hash:02x?} (index={_index})");
+            verify_transaction(&client, tx, tx_proof, confirmations)?;
+            verified += 1;
+        }
+        debug!("verified: {verified}, threshold: {}", self.threshold);
+        if verified < self.threshold {
+            return Err(InternalError::QuorumNotReached.into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+//! Check keys against the registry for proven Bitcoin transactions.
+//!
+//! A registry is a sorted linked list of cells, each registry cell records
+//! a key and the next key. A key, e.g. a txid, could be inserted into the
+//! registry only once, so it could be used to avoid replaying proofs of
+//! Bitcoin transactions.
+//!
+//! Each registry is dedicated to one consumer, the type script hash of the
+//! consumer is in the args of the registry type script, and keys could be
+//! inserted only when the consumer is in the same transaction.
+
+use alloc::vec::Vec;
+
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{
+        packed::{Byte, Script},
+        prelude::*,
+    },
+    debug, high_level as hl,
+};
+
+use crate::error::{InternalError, Result};
+
+/// The size of a key in the registry.
+pub const KEY_SIZE: usize = 32;
+
+/// The size of the data of a registry cell.
+pub const REGISTRY_CELL_DATA_SIZE: usize = KEY_SIZE * 2;
+
+/// The minimum key, which is the key of the head cell.
+pub const MIN_KEY: [u8; KEY_SIZE] = [0x00; KEY_SIZE];
+
+/// The maximum key, which is the next key of the tail cell.
+pub const MAX_KEY: [u8; KEY_SIZE] = [0xff; KEY_SIZE];
+
+/// The size of the args of the registry type script: the type id, then the
+/// type script hash of the consumer.
+pub const REGISTRY_ARGS_SIZE: usize = 32 + 32;
+
+/// The data of a registry cell.
+///
+/// It's the concatenation of the key and the next key, and the key should
+/// be less than the next key.
+#[derive(Clone, PartialEq, Eq)]
+pub struct RegistryCell {
+    pub key: [u8; KEY_SIZE],
+    pub next: [u8; KEY_SIZE],
+}
+
+impl RegistryCell {
+    /// The only cell when a registry is created.
+    pub fn head() -> Self {
+        Self {
+            key: MIN_KEY,
+            next: MAX_KEY,
+        }
+    }
+
+    pub fn from_slice(data: &[u8]) -> Result<Self> {
+        if data.len() != REGISTRY_CELL_DATA_SIZE {
+            return Err(InternalError::RegistryCellMalformed.into());
+        }
+        let mut key = [0u8; KEY_SIZE];
+        key.copy_from_slice(&data[..KEY_SIZE]);
+        let mut next = [0u8; KEY_SIZE];
+        next.copy_from_slice(&data[KEY_SIZE..]);
+        if key >= next {
+            return Err(InternalError::RegistryCellMalformed.into());
+        }
+        Ok(Self { key, next })
+    }
+}
+
+/// Builds the registry type script which is dedicated to current script.
+///
+/// A consumer couldn't save the type script hash of its registry in its own
+/// args, since the args of the registry include the type script hash of the
+/// consumer, so it saves the code hash, the hash type and the type id of the
+/// registry instead.
+pub fn registry_type_script(
+    code_hash: &[u8; 32],
+    hash_type: u8,
+    type_id: &[u8; 32],
+) -> Result<Script> {
+    let consumer_type_hash = hl::load_script_hash()?;
+    let mut args = Vec::with_capacity(REGISTRY_ARGS_SIZE);
+    args.extend_from_slice(type_id);
+    args.extend_from_slice(&consumer_type_hash);
+    let script = Script::new_builder()
+        .code_hash(code_hash.pack())
+        .hash_type(Byte::new(hash_type))
+        .args(args.pack())
+        .build();
+    Ok(script)
+}
+
+/// Checks that the key is the only one key which is inserted into the
+/// registry, whose type script is `registry_type_script`, by current
+/// transaction.
+///
+/// Since the registry type script guarantees each key could be inserted only
+/// once, if the key is inserted by current transaction, it's never used
+/// before.
+///
+/// No other keys are allowed to be inserted, otherwise, keys which are not
+/// verified by the consumer could be inserted along with it, to block the
+/// later transactions which use them.
+pub fn check_key_inserted(registry_type_script: &Script, key: &[u8; KEY_SIZE]) -> Result<()> {
+    let inputs = load_keys(registry_type_script, Source::Input)?;
+    if inputs.contains(key) {
+        debug!("key {key:02x?} is in the inputs, it's used before");
+        return Err(InternalError::RegistryKeyIsUsed.into());
+    }
+    let inserted = load_inserted_keys(registry_type_script, &inputs)?;
+    if inserted != [*key] {
+        debug!("key {key:02x?} is not the only inserted key");
+        return Err(InternalError::RegistryKeyIsNotInserted.into());
+    }
+    Ok(())
+}
+
+/// Checks that no key is inserted into the registry, whose type script is
+/// `registry_type_script`, by current transaction.
+///
+/// The registry allows inserting keys when the consumer is in the same
+/// transaction, so the consumer should call it in all other operations.
+pub fn check_no_key_inserted(registry_type_script: &Script) -> Result<()> {
+    let inputs = load_keys(registry_type_script, Source::Input)?;
+    if !load_inserted_keys(registry_type_script, &inputs)?.is_empty() {
+        debug!("keys are inserted without a proof");
+        return Err(InternalError::RegistryKeyIsInsertedUnexpectedly.into());
+    }
+    Ok(())
+}
+
+fn load_inserted_keys(
+    registry_type_script: &Script,
+    inputs: &[[u8; KEY_SIZE]],
+) -> Result<Vec<[u8; KEY_SIZE]>> {
+    let inserted = load_keys(registry_type_script, Source::Output)?
+        .into_iter()
+        .filter(|key| !inputs.contains(key))
+        .collect();
+    Ok(inserted)
+}
+
+fn load_keys(registry_type_script: &Script, source: Source) -> Result<Vec<[u8; KEY_SIZE]>> {
+    let mut keys = Vec::new();
+    for (index, script_opt) in hl::QueryIter::new(hl::load_cell_type, source).enumerate() {
+        if script_opt.as_ref().map(|script| script.as_slice())
+            != Some(registry_type_script.as_slice())
+        {
+            continue;
+        }
+        let data = hl::load_cell_data(index, source)?;
+        let cell = RegistryCell::from_slice(&data)?;
+        keys.push(cell.key);
+    }
+    Ok(keys)
+}
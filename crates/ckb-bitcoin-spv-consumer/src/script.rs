@@ -0,0 +1,108 @@
+//! A minimal parser for Bitcoin scripts.
+//!
+//! Only the push operations are decoded, all other opcodes are returned as
+//! they are.
+
+use alloc::vec::Vec;
+
+use crate::{
+    error::{InternalError, Result},
+    transaction::Cursor,
+};
+
+pub const OP_0: u8 = 0x00;
+pub const OP_PUSHDATA1: u8 = 0x4c;
+pub const OP_PUSHDATA2: u8 = 0x4d;
+pub const OP_PUSHDATA4: u8 = 0x4e;
+pub const OP_1NEGATE: u8 = 0x4f;
+pub const OP_1: u8 = 0x51;
+pub const OP_16: u8 = 0x60;
+pub const OP_RETURN: u8 = 0x6a;
+
+/// An instruction of a Bitcoin script.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Instruction<'r> {
+    /// Pushes data, includes `OP_0` which pushes an empty data.
+    PushBytes(&'r [u8]),
+    /// Any other opcode.
+    Op(u8),
+}
+
+/// An iterator over the instructions of a Bitcoin script.
+pub struct Instructions<'r> {
+    cursor: Cursor<'r>,
+    is_failed: bool,
+}
+
+impl<'r> Instructions<'r> {
+    pub fn new(script: &'r [u8]) -> Self {
+        Self {
+            cursor: Cursor::new(script),
+            is_failed: false,
+        }
+    }
+
+    fn read_instruction(&mut self) -> Result<Instruction<'r>> {
+        let opcode = self.cursor.read_u8()?;
+        let length = match opcode {
+            OP_0 => 0,
+            0x01..=0x4b => usize::from(opcode),
+            OP_PUSHDATA1 => usize::from(self.cursor.read_u8()?),
+            OP_PUSHDATA2 => usize::from(u16::from_le_bytes(self.cursor.read_array()?)),
+            OP_PUSHDATA4 => self.cursor.read_u32()? as usize,
+            _ => return Ok(Instruction::Op(opcode)),
+        };
+        let data = self.cursor.read_bytes(length)?;
+        Ok(Instruction::PushBytes(data))
+    }
+}
+
+impl<'r> Iterator for Instructions<'r> {
+    type Item = Result<Instruction<'r>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_failed || self.cursor.is_finished() {
+            return None;
+        }
+        let result = self.read_instruction().map_err(|_| {
+            self.is_failed = true;
+            InternalError::BitcoinScriptTruncated.into()
+        });
+        Some(result)
+    }
+}
+
+/// Extracts the data of a push-only script, i.e. the concatenation of all
+/// pushed data.
+///
+/// Same as Bitcoin Core, the small integers are push operations too:
+/// `OP_1NEGATE` pushes `0x81`, and `OP_1` ~ `OP_16` push `0x01` ~ `0x10`.
+/// But `OP_RESERVED` is rejected, since it pushes nothing.
+///
+/// Returns `None` if the script has any other opcode, or it's truncated.
+pub fn push_only_data(script: &[u8]) -> Option<Vec<u8>> {
+    let mut data = Vec::with_capacity(script.len());
+    for instruction in Instructions::new(script) {
+        match instruction.ok()? {
+            Instruction::PushBytes(bytes) => data.extend_from_slice(bytes),
+            Instruction::Op(OP_1NEGATE) => data.push(0x81),
+            Instruction::Op(op @ OP_1..=OP_16) => data.push(op - OP_1 + 1),
+            Instruction::Op(_) => return None,
+        }
+    }
+    Some(data)
+}
+
+/// Extracts the payload of a data carrier output, which script pubkey is
+/// `OP_RETURN` then push operations only.
+///
+/// The payload is the data of the script after `OP_RETURN`, see
+/// [`push_only_data`].
+///
+/// Returns `None` if the script is not a data carrier output.
+pub fn op_return_payload(script: &[u8]) -> Option<Vec<u8>> {
+    match script.split_first()? {
+        (&OP_RETURN, rest) => push_only_data(rest),
+        _ => None,
+    }
+}
@@ -0,0 +1,100 @@
+//! Prove that a Bitcoin transaction spends a specific outpoint.
+//!
+//! A spend proof is serialized as:
+//! - The raw transaction, as bytes with a `CompactSize` length prefix.
+//! - The `TransactionProof` of the transaction, as bytes with a
+//!   `CompactSize` length prefix.
+//! - The index of the input which spends the outpoint, as a 4-bytes
+//!   little-endian unsigned integer.
+
+use alloc::vec::Vec;
+
+use ckb_bitcoin_spv_verifier::types::packed;
+use ckb_std::debug;
+
+use crate::{
+    error::{InternalError, Result},
+    transaction::{write_compact_size, Cursor, OutPoint, Transaction},
+    verify_transaction,
+};
+
+/// A proof that a transaction spends an outpoint.
+pub struct SpendProof<'r> {
+    pub raw_tx: &'r [u8],
+    pub tx_proof: &'r [u8],
+    pub input_index: u32,
+}
+
+impl<'r> SpendProof<'r> {
+    /// Parses a spend proof, all bytes should be consumed.
+    pub fn parse(raw: &'r [u8]) -> Result<Self> {
+        Self::read(raw).map_err(|_| InternalError::SpendProofMalformed.into())
+    }
+
+    fn read(raw: &'r [u8]) -> Result<Self> {
+        let mut cursor = Cursor::new(raw);
+        let raw_tx = cursor.read_var_bytes()?;
+        let tx_proof = cursor.read_var_bytes()?;
+        let input_index = cursor.read_u32()?;
+        if !cursor.is_finished() {
+            return Err(InternalError::BitcoinDataTrailingBytes.into());
+        }
+        Ok(Self {
+            raw_tx,
+            tx_proof,
+            input_index,
+        })
+    }
+
+    /// Serializes the spend proof.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut raw = Vec::new();
+        write_compact_size(&mut raw, self.raw_tx.len());
+        raw.extend_from_slice(self.raw_tx);
+        write_compact_size(&mut raw, self.tx_proof.len());
+        raw.extend_from_slice(self.tx_proof);
+        raw.extend_from_slice(&self.input_index.to_le_bytes());
+        raw
+    }
+}
+
+/// Finds the input which spends the outpoint, returns its index.
+pub fn find_spending_input(tx: &Transaction, outpoint: &OutPoint) -> Option<usize> {
+    tx.inputs()
+        .iter()
+        .position(|input| &input.previous_output == outpoint)
+}
+
+/// Checks that the input at `input_index` spends the outpoint.
+pub fn check_spending_input(tx: &Transaction, input_index: u32, outpoint: &OutPoint) -> Result<()> {
+    let is_spent = tx
+        .inputs()
+        .get(input_index as usize)
+        .map(|input| &input.previous_output == outpoint)
+        .unwrap_or(false);
+    if !is_spent {
+        return Err(InternalError::OutPointNotSpent.into());
+    }
+    Ok(())
+}
+
+/// Verifies that a transaction is in the chain of an SPV client, with enough
+/// confirmations, and it spends the outpoint.
+///
+/// Returns the parsed transaction, so the caller could check other parts of
+/// it, such as the outputs.
+pub fn verify_outpoint_spent<'r>(
+    client: &packed::SpvClient,
+    proof: &SpendProof<'r>,
+    outpoint: &OutPoint,
+    confirmations: u32,
+) -> Result<Transaction<'r>> {
+    let tx = Transaction::parse(proof.raw_tx)?;
+    check_spending_input(&tx, proof.input_index, outpoint)?;
+    debug!(
+        "outpoint {outpoint:?} is spent by input {}",
+        proof.input_index
+    );
+    verify_transaction(client, &tx, proof.tx_proof, confirmations)?;
+    Ok(tx)
+}
@@ -0,0 +1,150 @@
+use alloc::{vec, vec::Vec};
+
+use bitcoin::{
+    absolute::LockTime, block, consensus::serialize, hashes::Hash as _, transaction::Version,
+    Amount, Block, BlockHash, CompactTarget, MerkleBlock, OutPoint, ScriptBuf, Sequence,
+    Transaction as BtcTransaction, TxIn, TxMerkleNode, TxOut, Txid, Witness,
+};
+use ckb_bitcoin_spv_verifier::types::packed;
+
+use crate::{
+    batch::{BatchProof, BlockProof, VerifiedTxid},
+    error::{Error, InternalError, Result},
+};
+
+const HEIGHT: u32 = 2016 * 400;
+
+fn build_block(txs_count: u8) -> Block {
+    let txdata = (0..txs_count)
+        .map(|n| BtcTransaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Txid::from_byte_array([n; 32]), 0),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::default(),
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(u64::from(n) * 100_000),
+                script_pubkey: ScriptBuf::from_bytes(vec![n; 22]),
+            }],
+        })
+        .collect();
+    let header = block::Header {
+        version: block::Version::ONE,
+        prev_blockhash: BlockHash::all_zeros(),
+        merkle_root: TxMerkleNode::all_zeros(),
+        time: 1_700_000_000,
+        bits: CompactTarget::from_consensus(0x207f_ffff),
+        nonce: 0,
+    };
+    let mut block = Block { header, txdata };
+    block.header.merkle_root = block.compute_merkle_root().unwrap();
+    block
+}
+
+fn build_merkle_block(block: &Block, indexes: &[usize]) -> Vec<u8> {
+    let txids = indexes
+        .iter()
+        .map(|index| block.txdata[*index].txid())
+        .collect::<Vec<_>>();
+    let merkle_block = MerkleBlock::from_block_with_predicate(block, |txid| txids.contains(txid));
+    serialize(&merkle_block)
+}
+
+fn check_failure<T>(result: Result<T>, expected: InternalError) {
+    match result {
+        Ok(_) => panic!("should be failed"),
+        Err(Error::Internal(actual)) => assert_eq!(actual as i8, expected as i8),
+        Err(_) => panic!("should be an internal error"),
+    }
+}
+
+#[test]
+fn extract_transactions_from_block_proof() {
+    let block = build_block(11);
+    let indexes = [1, 4, 5, 10];
+    let merkle_block = build_merkle_block(&block, &indexes);
+    let proof = BlockProof {
+        height: HEIGHT,
+        merkle_block: &merkle_block,
+    };
+    let (header, txids) = proof.extract().ok().unwrap();
+    assert_eq!(header, block.header);
+    let expected = indexes
+        .iter()
+        .map(|index| VerifiedTxid {
+            height: HEIGHT,
+            tx_index: *index as u32,
+            txid: block.txdata[*index].txid().to_byte_array(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(txids, expected);
+}
+
+#[test]
+fn failed_to_extract_transactions_from_block_proof() {
+    let block = build_block(7);
+
+    // The merkle root in the header is not matched.
+    let mut other_block = block.clone();
+    other_block.header.merkle_root = TxMerkleNode::all_zeros();
+    let merkle_block = build_merkle_block(&other_block, &[2]);
+    let proof = BlockProof {
+        height: HEIGHT,
+        merkle_block: &merkle_block,
+    };
+    check_failure(proof.extract(), InternalError::BatchProofMalformed);
+
+    // No transactions are proven.
+    let merkle_block = build_merkle_block(&block, &[]);
+    let proof = BlockProof {
+        height: HEIGHT,
+        merkle_block: &merkle_block,
+    };
+    check_failure(proof.extract(), InternalError::BatchProofMalformed);
+
+    // The merkle block is truncated.
+    let merkle_block = build_merkle_block(&block, &[2]);
+    let proof = BlockProof {
+        height: HEIGHT,
+        merkle_block: &merkle_block[..merkle_block.len() - 1],
+    };
+    check_failure(proof.extract(), InternalError::BatchProofMalformed);
+}
+
+#[test]
+fn encode_and_parse_batch_proof() {
+    let item_size = packed::HeaderDigest::TOTAL_SIZE;
+    let items = [vec![0x11; item_size], vec![0x22; item_size]];
+    let merkle_blocks = [
+        build_merkle_block(&build_block(3), &[0, 2]),
+        build_merkle_block(&build_block(5), &[4]),
+    ];
+    let proof = BatchProof {
+        header_proof: items.iter().map(|item| &item[..]).collect(),
+        blocks: merkle_blocks
+            .iter()
+            .enumerate()
+            .map(|(i, merkle_block)| BlockProof {
+                height: HEIGHT + i as u32 * 6,
+                merkle_block,
+            })
+            .collect(),
+    };
+    let raw = proof.encode();
+    let parsed = BatchProof::parse(&raw).ok().unwrap();
+    assert_eq!(parsed.header_proof, proof.header_proof);
+    assert_eq!(parsed.blocks.len(), proof.blocks.len());
+    for (actual, expected) in parsed.blocks.iter().zip(proof.blocks.iter()) {
+        assert_eq!(actual.height, expected.height);
+        assert_eq!(actual.merkle_block, expected.merkle_block);
+    }
+
+    let with_trailing_bytes = [&raw[..], &[0x00]].concat();
+    let malformed: Vec<&[u8]> = vec![&[], &raw[..raw.len() - 1], &with_trailing_bytes];
+    for raw in malformed {
+        check_failure(BatchProof::parse(raw), InternalError::BatchProofMalformed);
+    }
+}
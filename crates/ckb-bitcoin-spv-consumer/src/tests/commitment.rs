@@ -0,0 +1,97 @@
+use alloc::{vec, vec::Vec};
+
+use bitcoin::{
+    absolute::LockTime, consensus::serialize, transaction::Version, Amount, OutPoint, ScriptBuf,
+    Sequence, Transaction as BtcTransaction, TxIn, TxOut, Witness,
+};
+use ckb_bitcoin_spv_verifier::types::packed;
+
+use crate::{
+    commitment::{find_op_return, verify_op_return_commitment, Payload},
+    error::{Error, InternalError},
+    transaction::Transaction,
+};
+
+// Outputs: a payment, a commitment which is split into 2 pushes, then a
+// commitment of 32 bytes.
+fn build_transaction() -> Vec<u8> {
+    let mut split = vec![0x6a, 0x02, 0xaa, 0xbb, 0x02];
+    split.extend_from_slice(&[0xcc, 0xdd]);
+    let mut single = vec![0x6a, 0x20];
+    single.extend_from_slice(&[0x11; 32]);
+    let tx = BtcTransaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::default(),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::default(),
+        }],
+        output: [vec![0x51], split, single]
+            .into_iter()
+            .map(|script| TxOut {
+                value: Amount::ZERO,
+                script_pubkey: ScriptBuf::from_bytes(script),
+            })
+            .collect(),
+    };
+    serialize(&tx)
+}
+
+#[test]
+fn match_payloads() {
+    assert!(Payload::Exact(&[1, 2]).is_matched(&[1, 2]));
+    assert!(!Payload::Exact(&[1, 2]).is_matched(&[1, 2, 3]));
+    assert!(!Payload::Exact(&[1, 2]).is_matched(&[1]));
+    assert!(Payload::Prefix(&[1, 2]).is_matched(&[1, 2]));
+    assert!(Payload::Prefix(&[1, 2]).is_matched(&[1, 2, 3]));
+    assert!(!Payload::Prefix(&[1, 2]).is_matched(&[1]));
+    assert!(!Payload::Prefix(&[1, 2]).is_matched(&[2, 1, 2]));
+}
+
+#[test]
+fn find_exact_payload() {
+    let raw = build_transaction();
+    let tx = Transaction::parse(&raw).unwrap_or_else(|_| panic!("failed to parse"));
+    let (index, payload) = find_op_return(&tx, Payload::Exact(&[0x11; 32])).unwrap();
+    assert_eq!(index, 2);
+    assert_eq!(payload, [0x11; 32]);
+    // The concatenation of all pushed data.
+    let (index, payload) = find_op_return(&tx, Payload::Exact(&[0xaa, 0xbb, 0xcc, 0xdd])).unwrap();
+    assert_eq!(index, 1);
+    assert_eq!(payload, [0xaa, 0xbb, 0xcc, 0xdd]);
+}
+
+#[test]
+fn find_prefix_payload() {
+    let raw = build_transaction();
+    let tx = Transaction::parse(&raw).unwrap_or_else(|_| panic!("failed to parse"));
+    // The full payload is returned.
+    let (index, payload) = find_op_return(&tx, Payload::Prefix(&[0xaa, 0xbb, 0xcc])).unwrap();
+    assert_eq!(index, 1);
+    assert_eq!(payload, [0xaa, 0xbb, 0xcc, 0xdd]);
+    // The first matched output is returned.
+    let (index, _) = find_op_return(&tx, Payload::Prefix(&[])).unwrap();
+    assert_eq!(index, 1);
+}
+
+#[test]
+fn no_payload_matched() {
+    let raw = build_transaction();
+    let tx = Transaction::parse(&raw).unwrap_or_else(|_| panic!("failed to parse"));
+    assert!(find_op_return(&tx, Payload::Exact(&[0x11; 31])).is_none());
+    assert!(find_op_return(&tx, Payload::Exact(&[0xcc, 0xdd])).is_none());
+    assert!(find_op_return(&tx, Payload::Prefix(&[0x11; 33])).is_none());
+    // The payment output is not a data carrier output.
+    assert!(find_op_return(&tx, Payload::Exact(&[])).is_none());
+    // The commitment is checked before the proof.
+    let client = packed::SpvClient::default();
+    match verify_op_return_commitment(&client, &tx, &[], 0, Payload::Exact(&[0x22; 32])) {
+        Ok(_) => panic!("should be failed to find the commitment"),
+        Err(Error::Internal(actual)) => {
+            assert_eq!(actual as i8, InternalError::CommitmentNotFound as i8)
+        }
+        Err(_) => panic!("should be an internal error"),
+    }
+}
@@ -0,0 +1,112 @@
+use alloc::{vec, vec::Vec};
+
+use bitcoin::{
+    absolute::LockTime, consensus::serialize, hashes::Hash as _, transaction::Version, Amount,
+    OutPoint, ScriptBuf, Sequence, Transaction as BtcTransaction, TxIn, TxOut, Witness,
+};
+
+use crate::{
+    delegate::{VerifiedTransaction, VerifierArgs},
+    error::{Error, InternalError},
+    transaction::Transaction,
+};
+
+fn build_transaction() -> BtcTransaction {
+    BtcTransaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::default(),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::from_slice(&[vec![1u8; 72]]),
+        }],
+        output: vec![
+            TxOut {
+                value: Amount::from_sat(100_000),
+                script_pubkey: ScriptBuf::from_bytes(vec![0x51; 22]),
+            },
+            TxOut {
+                value: Amount::ZERO,
+                script_pubkey: ScriptBuf::from_bytes(vec![0x6a; 34]),
+            },
+        ],
+    }
+}
+
+fn check_args_failure(argv: &[&[u8]]) {
+    match VerifierArgs::decode(argv) {
+        Ok(_) => panic!("should be failed to decode {argv:?}"),
+        Err(Error::Internal(actual)) => {
+            assert_eq!(actual as i8, InternalError::VerifierArgsMalformed as i8)
+        }
+        Err(_) => panic!("should be an internal error"),
+    }
+}
+
+#[test]
+fn encode_and_decode_args() {
+    let expected = VerifierArgs {
+        cell_dep_index: 12,
+        spv_type_hash: [0xab; 32],
+        confirmations: 6,
+        raw_tx: serialize(&build_transaction()),
+        tx_proof: vec![0x00, 0x01, 0xfe, 0xff],
+    };
+    let encoded = expected.encode();
+    assert_eq!(
+        encoded[0].as_bytes(),
+        [12, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01]
+    );
+    assert_eq!(encoded[1].as_bytes(), [0xab; 32]);
+    assert_eq!(
+        encoded[2].as_bytes(),
+        [6, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01]
+    );
+    assert_eq!(encoded[4].as_bytes(), [0x01, 0x01, 0x01, 0x02, 0xfe, 0xff]);
+    let argv = encoded.iter().map(|arg| arg.as_bytes()).collect::<Vec<_>>();
+    let actual = VerifierArgs::decode(&argv).unwrap_or_else(|_| panic!("failed to decode"));
+    assert_eq!(actual.cell_dep_index, expected.cell_dep_index);
+    assert_eq!(actual.spv_type_hash, expected.spv_type_hash);
+    assert_eq!(actual.confirmations, expected.confirmations);
+    assert_eq!(actual.raw_tx, expected.raw_tx);
+    assert_eq!(actual.tx_proof, expected.tx_proof);
+}
+
+#[test]
+fn failed_to_decode_malformed_args() {
+    let index = [1, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01];
+    let hash = [0xab; 32];
+    check_args_failure(&[&index, &hash, &index, b"tx"]);
+    check_args_failure(&[b"", &hash, &index, b"tx", b"proof"]);
+    check_args_failure(&[&index[..6], &hash, &index, b"tx", b"proof"]);
+    check_args_failure(&[&index, &hash[1..], &index, b"tx", b"proof"]);
+    check_args_failure(&[&index, &[0xab; 33], &index, b"tx", b"proof"]);
+    check_args_failure(&[&index, &hash, &[1, 2, 3, 4, 5], b"tx", b"proof"]);
+    // Nul bytes should be escaped.
+    check_args_failure(&[&index, &hash, &index, b"t\0x", b"proof"]);
+    check_args_failure(&[&[1, 0, 0, 0], &hash, &index, b"tx", b"proof"]);
+    // Only `0x01` and `0x02` could be escaped.
+    check_args_failure(&[&index, &hash, &index, &[0x01, 0x03], b"proof"]);
+    // An escape byte without the escaped byte.
+    check_args_failure(&[&index, &hash, &index, b"tx", &[0x02, 0x01]]);
+}
+
+#[test]
+fn encode_and_decode_verified_transaction() {
+    let expected = build_transaction();
+    let raw = serialize(&expected);
+    let tx = Transaction::parse(&raw).unwrap_or_else(|_| panic!("failed to parse"));
+    let content = VerifiedTransaction::encode(&tx);
+    let actual =
+        VerifiedTransaction::decode(&content).unwrap_or_else(|_| panic!("failed to decode"));
+    assert_eq!(actual.txid, expected.txid().to_byte_array());
+    assert_eq!(actual.outputs.len(), expected.output.len());
+    for (actual, expected) in actual.outputs.iter().zip(expected.output.iter()) {
+        assert_eq!(actual.value, expected.value.to_sat());
+        assert_eq!(actual.script_pubkey, expected.script_pubkey.as_bytes());
+    }
+    // A truncated result should be rejected.
+    assert!(VerifiedTransaction::decode(&content[..content.len() - 1]).is_err());
+    assert!(VerifiedTransaction::decode(&content[..31]).is_err());
+}
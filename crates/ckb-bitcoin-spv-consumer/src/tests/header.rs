@@ -0,0 +1,82 @@
+use alloc::{vec, vec::Vec};
+
+use bitcoin::{
+    block, consensus::serialize, hashes::Hash as _, BlockHash, CompactTarget, TxMerkleNode,
+};
+use ckb_bitcoin_spv_verifier::types::packed;
+
+use crate::{
+    error::{Error, InternalError, Result},
+    header::{HeaderFields, HeaderProof},
+};
+
+const HEIGHT: u32 = 2016 * 400;
+
+fn build_header() -> block::Header {
+    block::Header {
+        version: block::Version::from_consensus(0x2000_0004),
+        prev_blockhash: BlockHash::from_byte_array([0x11; 32]),
+        merkle_root: TxMerkleNode::from_byte_array([0x22; 32]),
+        time: 1_700_000_000,
+        bits: CompactTarget::from_consensus(0x1703_4219),
+        nonce: 0x1234_5678,
+    }
+}
+
+fn check_failure<T>(result: Result<T>, expected: InternalError) {
+    match result {
+        Ok(_) => panic!("should be failed"),
+        Err(Error::Internal(actual)) => assert_eq!(actual as i8, expected as i8),
+        Err(_) => panic!("should be an internal error"),
+    }
+}
+
+#[test]
+fn parse_header_fields() {
+    let header = build_header();
+    let raw: [u8; 80] = serialize(&header).try_into().unwrap();
+    let fields = HeaderFields::parse(HEIGHT, &raw);
+    let expected = HeaderFields {
+        height: HEIGHT,
+        block_hash: header.block_hash().to_byte_array(),
+        version: header.version.to_consensus(),
+        prev_blockhash: header.prev_blockhash.to_byte_array(),
+        merkle_root: header.merkle_root.to_byte_array(),
+        time: header.time,
+        bits: header.bits.to_consensus(),
+        nonce: header.nonce,
+    };
+    assert_eq!(fields, expected);
+}
+
+#[test]
+fn encode_and_parse_header_proof() {
+    let raw_header: [u8; 80] = serialize(&build_header()).try_into().unwrap();
+    let item_size = packed::HeaderDigest::TOTAL_SIZE;
+    let items = [vec![0x11; item_size], vec![0x22; item_size]];
+    let proof = HeaderProof {
+        height: HEIGHT,
+        header: &raw_header,
+        header_proof: items.iter().map(|item| &item[..]).collect(),
+    };
+    let raw = proof.encode();
+    assert_eq!(raw.len(), 4 + 80 + 1 + item_size * 2);
+    let parsed = HeaderProof::parse(&raw).ok().unwrap();
+    assert_eq!(parsed.height, HEIGHT);
+    assert_eq!(parsed.header, &raw_header);
+    assert_eq!(parsed.header_proof, proof.header_proof);
+
+    let with_trailing_bytes = [&raw[..], &[0x00]].concat();
+    let malformed: Vec<&[u8]> = vec![
+        &[],
+        &raw[..4 + 79],
+        &raw[..raw.len() - 1],
+        &with_trailing_bytes,
+    ];
+    for raw in malformed {
+        check_failure(
+            HeaderProof::parse(raw),
+            InternalError::BitcoinHeaderProofMalformed,
+        );
+    }
+}
@@ -0,0 +1,10 @@
+mod batch;
+mod commitment;
+mod delegate;
+mod header;
+mod output;
+mod quorum;
+mod script;
+mod spend;
+mod transaction;
+mod witness;
@@ -0,0 +1,237 @@
+use alloc::{vec, vec::Vec};
+use core::str::FromStr as _;
+
+use bitcoin::{
+    absolute::LockTime,
+    blockdata::opcodes::all::*,
+    consensus::serialize,
+    hashes::{hex::FromHex as _, Hash as _},
+    key::TweakedPublicKey,
+    script::{Builder, PushBytesBuf},
+    transaction::Version,
+    Address, Amount, PubkeyHash, PublicKey, ScriptBuf, ScriptHash, Transaction as BtcTransaction,
+    TxIn, TxOut as BtcTxOut, WPubkeyHash, WScriptHash, WitnessProgram, WitnessVersion,
+    XOnlyPublicKey,
+};
+
+use crate::{
+    error::{Error, InternalError},
+    output::{classify, Destination},
+    transaction::Transaction,
+};
+
+// The generator point of secp256k1.
+const COMPRESSED_KEY: &str = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+const UNCOMPRESSED_KEY: &str = "0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\
+     483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+
+fn hex(s: &str) -> Vec<u8> {
+    Vec::from_hex(s).expect("hex")
+}
+
+fn address_script(address: &str) -> ScriptBuf {
+    Address::from_str(address)
+        .expect("address")
+        .assume_checked()
+        .script_pubkey()
+}
+
+#[test]
+fn classify_mainnet_addresses() {
+    let script = address_script("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa");
+    let expected = hex("62e907b15cbf27d5425399ebf6f0fb50ebb88f18");
+    assert_eq!(
+        classify(script.as_bytes()),
+        Destination::P2pkh(expected[..].try_into().unwrap())
+    );
+
+    let script = address_script("3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy");
+    let expected = hex("b472a266d0bd89c13706a4132ccfb16f7c3b9fcb");
+    assert_eq!(
+        classify(script.as_bytes()),
+        Destination::P2sh(expected[..].try_into().unwrap())
+    );
+
+    let script = address_script("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4");
+    let expected = hex("751e76e8199196d454941c45d1b3a323f1433bd6");
+    assert_eq!(
+        classify(script.as_bytes()),
+        Destination::P2wpkh(expected[..].try_into().unwrap())
+    );
+
+    let script = address_script("bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3");
+    let expected = hex("1863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262");
+    assert_eq!(
+        classify(script.as_bytes()),
+        Destination::P2wsh(expected[..].try_into().unwrap())
+    );
+
+    let script = address_script("bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr");
+    let expected = hex("a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c");
+    assert_eq!(
+        classify(script.as_bytes()),
+        Destination::P2tr(expected[..].try_into().unwrap())
+    );
+}
+
+#[test]
+fn classify_standard_templates() {
+    let compressed = PublicKey::from_str(COMPRESSED_KEY).unwrap();
+    let uncompressed = PublicKey::from_str(UNCOMPRESSED_KEY).unwrap();
+    for key in [compressed, uncompressed] {
+        let script = ScriptBuf::new_p2pk(&key);
+        assert_eq!(
+            classify(script.as_bytes()),
+            Destination::P2pk(&key.to_bytes())
+        );
+    }
+
+    let hash = [0x11; 20];
+    let script = ScriptBuf::new_p2pkh(&PubkeyHash::from_byte_array(hash));
+    assert_eq!(classify(script.as_bytes()), Destination::P2pkh(&hash));
+    let script = ScriptBuf::new_p2sh(&ScriptHash::from_byte_array(hash));
+    assert_eq!(classify(script.as_bytes()), Destination::P2sh(&hash));
+    let script = ScriptBuf::new_p2wpkh(&WPubkeyHash::from_byte_array(hash));
+    assert_eq!(classify(script.as_bytes()), Destination::P2wpkh(&hash));
+
+    let hash = [0x22; 32];
+    let script = ScriptBuf::new_p2wsh(&WScriptHash::from_byte_array(hash));
+    assert_eq!(classify(script.as_bytes()), Destination::P2wsh(&hash));
+
+    let key = XOnlyPublicKey::from_slice(&compressed.to_bytes()[1..]).unwrap();
+    let script = ScriptBuf::new_p2tr_tweaked(TweakedPublicKey::dangerous_assume_tweaked(key));
+    assert_eq!(
+        classify(script.as_bytes()),
+        Destination::P2tr(&key.serialize())
+    );
+
+    for (version, program) in [
+        (WitnessVersion::V1, vec![0x4e, 0x73]),
+        (WitnessVersion::V16, vec![0x33; 40]),
+    ] {
+        let program = WitnessProgram::new(version, program.clone()).unwrap();
+        let script = ScriptBuf::new_witness_program(&program);
+        assert_eq!(
+            classify(script.as_bytes()),
+            Destination::WitnessUnknown {
+                version: version.to_num(),
+                program: program.program().as_bytes(),
+            }
+        );
+    }
+
+    let data = PushBytesBuf::try_from(vec![0x5a; 80]).unwrap();
+    let script = ScriptBuf::new_op_return(&data);
+    assert_eq!(
+        classify(script.as_bytes()),
+        Destination::NullData(script.as_bytes())
+    );
+    // Small integers are push operations too.
+    let script = Builder::new()
+        .push_opcode(OP_RETURN)
+        .push_opcode(OP_PUSHNUM_1)
+        .push_opcode(OP_PUSHNUM_NEG1)
+        .push_slice(data)
+        .into_script();
+    assert_eq!(
+        classify(script.as_bytes()),
+        Destination::NullData(script.as_bytes())
+    );
+}
+
+#[test]
+fn classify_multisig() {
+    let compressed = PublicKey::from_str(COMPRESSED_KEY).unwrap();
+    let uncompressed = PublicKey::from_str(UNCOMPRESSED_KEY).unwrap();
+    let build = |required: i64, keys: &[PublicKey], count: i64| {
+        keys.iter()
+            .fold(Builder::new().push_int(required), |builder, key| {
+                builder.push_key(key)
+            })
+            .push_int(count)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script()
+    };
+
+    let keys = [compressed, uncompressed, compressed];
+    let script = build(2, &keys, 3);
+    let expected_keys = keys.iter().map(|key| key.to_bytes()).collect::<Vec<_>>();
+    assert_eq!(
+        classify(script.as_bytes()),
+        Destination::Multisig {
+            required: 2,
+            keys: expected_keys.iter().map(|key| &key[..]).collect(),
+        }
+    );
+
+    for script in [
+        // More required than keys.
+        build(3, &keys[..2], 2),
+        // Mismatched keys count.
+        build(1, &keys[..2], 3),
+        // Too many keys for a bare multisig.
+        build(1, &[compressed; 4], 4),
+    ] {
+        assert_eq!(
+            classify(script.as_bytes()),
+            Destination::NonStandard(script.as_bytes())
+        );
+    }
+}
+
+#[test]
+fn classify_non_standard_scripts() {
+    let scripts = [
+        // An empty script.
+        vec![],
+        // A witness version 0 program with an unknown length.
+        hex("0015000000000000000000000000000000000000000000"),
+        // A P2PKH script with a truncated hash.
+        hex("76a91362e907b15cbf27d5425399ebf6f0fb50ebb88f88ac"),
+        // A data carrier with non-push opcodes.
+        vec![OP_RETURN.to_u8(), OP_CHECKSIG.to_u8()],
+        // A data carrier with `OP_RESERVED`, which pushes nothing.
+        vec![OP_RETURN.to_u8(), OP_RESERVED.to_u8()],
+        // A data carrier with a truncated push.
+        vec![OP_RETURN.to_u8(), 0x20, 0x00],
+        // A P2PK script with an invalid key.
+        [&[0x21, 0x05][..], &[0x00; 32], &[OP_CHECKSIG.to_u8()]].concat(),
+    ];
+    for script in &scripts {
+        assert_eq!(classify(script), Destination::NonStandard(script));
+    }
+}
+
+#[test]
+fn classify_tx_outputs() {
+    let btc_tx = BtcTransaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn::default()],
+        output: vec![
+            BtcTxOut {
+                value: Amount::from_sat(100_000),
+                script_pubkey: address_script("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"),
+            },
+            BtcTxOut {
+                value: Amount::ZERO,
+                script_pubkey: ScriptBuf::new_op_return([0x5a; 32]),
+            },
+        ],
+    };
+    let raw = serialize(&btc_tx);
+    let tx = Transaction::parse(&raw).ok().unwrap();
+
+    let classified = tx.classify_output(0).ok().unwrap();
+    assert_eq!(classified.value, 100_000);
+    assert!(matches!(classified.destination, Destination::P2wpkh(_)));
+    let classified = tx.classify_output(1).ok().unwrap();
+    assert_eq!(classified.value, 0);
+    assert!(matches!(classified.destination, Destination::NullData(_)));
+
+    let result = tx.classify_output(2);
+    assert!(matches!(
+        result,
+        Err(Error::Internal(InternalError::BitcoinTxOutputNotFound))
+    ));
+}
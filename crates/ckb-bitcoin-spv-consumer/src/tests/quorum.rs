@@ -0,0 +1,43 @@
+use alloc::vec;
+
+use crate::{
+    error::{Error, InternalError},
+    quorum::Quorum,
+};
+
+fn check_config_failure(threshold: usize, spv_type_hashes: &[u8]) {
+    match Quorum::from_slice(threshold, spv_type_hashes) {
+        Ok(_) => panic!("should be failed to create a quorum ({threshold})"),
+        Err(Error::Internal(actual)) => {
+            assert_eq!(actual as i8, InternalError::QuorumConfigMalformed as i8)
+        }
+        Err(_) => panic!("should be an internal error"),
+    }
+}
+
+#[test]
+fn create_quorum() {
+    let mut hashes = vec![1u8; 32];
+    hashes.extend_from_slice(&[2u8; 32]);
+    hashes.extend_from_slice(&[3u8; 32]);
+    for threshold in 1..=3 {
+        let quorum = Quorum::from_slice(threshold, &hashes)
+            .unwrap_or_else(|_| panic!("failed to create a quorum ({threshold})"));
+        assert_eq!(quorum.threshold(), threshold);
+        assert_eq!(quorum.spv_type_hashes().len(), 3);
+        assert_eq!(quorum.spv_type_hashes()[1], &[2u8; 32]);
+    }
+}
+
+#[test]
+fn failed_to_create_malformed_quorum() {
+    let mut hashes = vec![1u8; 32];
+    hashes.extend_from_slice(&[2u8; 32]);
+    check_config_failure(0, &hashes);
+    check_config_failure(3, &hashes);
+    check_config_failure(1, &[]);
+    check_config_failure(1, &hashes[..63]);
+    // Duplicated SPV instances.
+    hashes.extend_from_slice(&[1u8; 32]);
+    check_config_failure(2, &hashes);
+}
@@ -0,0 +1,122 @@
+use alloc::{vec, vec::Vec};
+
+use bitcoin::{opcodes::all::*, script::Builder, script::PushBytesBuf, ScriptBuf};
+
+use crate::script::{op_return_payload, push_only_data, Instruction, Instructions};
+
+fn push_bytes(data: &[u8]) -> PushBytesBuf {
+    PushBytesBuf::try_from(data.to_vec()).expect("push bytes")
+}
+
+#[test]
+fn parse_push_operations() {
+    for len in [0usize, 1, 0x4b, 0x4c, 0xff, 0x100, 0xffff, 0x10000] {
+        let data = vec![0x5a; len];
+        let script = Builder::new()
+            .push_slice(push_bytes(&data))
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        let instructions = Instructions::new(script.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_or_else(|_| panic!("failed to parse a push of {len} bytes"));
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[0], Instruction::PushBytes(&data));
+        assert_eq!(instructions[1], Instruction::Op(OP_CHECKSIG.to_u8()));
+    }
+}
+
+#[test]
+fn parse_non_minimal_push_operations() {
+    let data = [0x5a; 3];
+    // OP_PUSHDATA1, OP_PUSHDATA2 and OP_PUSHDATA4 for 3 bytes.
+    let scripts: [&[u8]; 3] = [
+        &[0x4c, 0x03, 0x5a, 0x5a, 0x5a],
+        &[0x4d, 0x03, 0x00, 0x5a, 0x5a, 0x5a],
+        &[0x4e, 0x03, 0x00, 0x00, 0x00, 0x5a, 0x5a, 0x5a],
+    ];
+    for script in scripts {
+        let mut instructions = Instructions::new(script);
+        assert!(matches!(
+            instructions.next(),
+            Some(Ok(Instruction::PushBytes(actual))) if actual == data
+        ));
+        assert!(instructions.next().is_none());
+    }
+}
+
+#[test]
+fn failed_to_parse_truncated_push_operations() {
+    let scripts: [&[u8]; 4] = [
+        &[0x03, 0x5a, 0x5a],
+        &[0x4c],
+        &[0x4d, 0x03],
+        &[0x4e, 0x03, 0x00, 0x00, 0x00, 0x5a],
+    ];
+    for script in scripts {
+        let mut instructions = Instructions::new(script);
+        assert!(matches!(instructions.next(), Some(Err(_))));
+        assert!(instructions.next().is_none());
+    }
+}
+
+#[test]
+fn extract_op_return_payload() {
+    let payload = [0x5a; 100];
+    let script = Builder::new()
+        .push_opcode(OP_RETURN)
+        .push_slice(push_bytes(&payload[..20]))
+        .push_slice(push_bytes(&payload[20..]))
+        .into_script();
+    assert_eq!(
+        op_return_payload(script.as_bytes()).as_deref(),
+        Some(&payload[..])
+    );
+
+    let empty = ScriptBuf::new_op_return(push_bytes(&[]));
+    assert_eq!(
+        op_return_payload(empty.as_bytes()).as_deref(),
+        Some(&[][..])
+    );
+    assert_eq!(
+        op_return_payload(&[OP_RETURN.to_u8()]).as_deref(),
+        Some(&[][..])
+    );
+
+    // Not a data carrier output.
+    let not_op_return = Builder::new()
+        .push_slice(push_bytes(&payload[..20]))
+        .into_script();
+    assert!(op_return_payload(not_op_return.as_bytes()).is_none());
+    let with_other_opcodes = Builder::new()
+        .push_opcode(OP_RETURN)
+        .push_slice(push_bytes(&payload[..20]))
+        .push_opcode(OP_CHECKSIG)
+        .into_script();
+    assert!(op_return_payload(with_other_opcodes.as_bytes()).is_none());
+    let truncated = [OP_RETURN.to_u8(), 0x20, 0x5a];
+    assert!(op_return_payload(&truncated).is_none());
+    assert!(op_return_payload(&[]).is_none());
+}
+
+#[test]
+fn extract_small_integers_as_data() {
+    let script = Builder::new()
+        .push_opcode(OP_RETURN)
+        .push_opcode(OP_PUSHNUM_1)
+        .push_opcode(OP_PUSHNUM_16)
+        .push_opcode(OP_PUSHNUM_NEG1)
+        .push_slice(push_bytes(&[0x5a]))
+        .into_script();
+    assert_eq!(
+        op_return_payload(script.as_bytes()).as_deref(),
+        Some(&[0x01, 0x10, 0x81, 0x5a][..])
+    );
+    assert_eq!(
+        push_only_data(&script.as_bytes()[1..]).as_deref(),
+        Some(&[0x01, 0x10, 0x81, 0x5a][..])
+    );
+
+    let reserved = [OP_RETURN.to_u8(), OP_RESERVED.to_u8()];
+    assert!(op_return_payload(&reserved).is_none());
+    assert!(push_only_data(&reserved[1..]).is_none());
+}
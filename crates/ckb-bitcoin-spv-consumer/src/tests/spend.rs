@@ -0,0 +1,108 @@
+use alloc::{vec, vec::Vec};
+
+use bitcoin::{
+    absolute::LockTime, consensus::serialize, hashes::Hash as _, transaction::Version, Amount,
+    OutPoint as BtcOutPoint, ScriptBuf, Sequence, Transaction as BtcTransaction, TxIn, TxOut, Txid,
+    Witness,
+};
+
+use crate::{
+    error::{Error, InternalError, Result},
+    spend::{self, SpendProof},
+    transaction::{OutPoint, Transaction},
+};
+
+fn build_transaction() -> BtcTransaction {
+    let input = |n: u8| TxIn {
+        previous_output: BtcOutPoint::new(Txid::from_byte_array([n; 32]), u32::from(n)),
+        script_sig: ScriptBuf::new(),
+        sequence: Sequence::MAX,
+        witness: Witness::from_slice(&[vec![n; 72], vec![n; 33]]),
+    };
+    BtcTransaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![input(1), input(2), input(3)],
+        output: vec![TxOut {
+            value: Amount::from_sat(100_000),
+            script_pubkey: ScriptBuf::from_bytes(vec![0x51; 22]),
+        }],
+    }
+}
+
+fn outpoint(n: u8) -> OutPoint {
+    OutPoint {
+        txid: [n; 32],
+        vout: u32::from(n),
+    }
+}
+
+fn check_failure<T>(result: Result<T>, expected: InternalError) {
+    match result {
+        Ok(_) => panic!("should be failed"),
+        Err(Error::Internal(actual)) => assert_eq!(actual as i8, expected as i8),
+        Err(_) => panic!("should be an internal error"),
+    }
+}
+
+#[test]
+fn find_and_check_spending_inputs() {
+    let raw = serialize(&build_transaction());
+    let tx = Transaction::parse(&raw).ok().unwrap();
+    for n in 1..=3 {
+        let index = spend::find_spending_input(&tx, &outpoint(n));
+        assert_eq!(index, Some(usize::from(n) - 1));
+        assert!(spend::check_spending_input(&tx, u32::from(n) - 1, &outpoint(n)).is_ok());
+    }
+    assert_eq!(spend::find_spending_input(&tx, &outpoint(4)), None);
+}
+
+#[test]
+fn failed_to_check_spending_inputs() {
+    let raw = serialize(&build_transaction());
+    let tx = Transaction::parse(&raw).ok().unwrap();
+    // The outpoint is spent by another input.
+    check_failure(
+        spend::check_spending_input(&tx, 0, &outpoint(2)),
+        InternalError::OutPointNotSpent,
+    );
+    // The outpoint is not spent.
+    check_failure(
+        spend::check_spending_input(&tx, 0, &outpoint(4)),
+        InternalError::OutPointNotSpent,
+    );
+    // Same txid, but a different output.
+    let mut other = outpoint(1);
+    other.vout = 0;
+    check_failure(
+        spend::check_spending_input(&tx, 0, &other),
+        InternalError::OutPointNotSpent,
+    );
+    // The input is out of bound.
+    check_failure(
+        spend::check_spending_input(&tx, 3, &outpoint(3)),
+        InternalError::OutPointNotSpent,
+    );
+}
+
+#[test]
+fn encode_and_parse_spend_proof() {
+    let raw_tx = serialize(&build_transaction());
+    let tx_proof = vec![0x5a; 0x100];
+    let proof = SpendProof {
+        raw_tx: &raw_tx,
+        tx_proof: &tx_proof,
+        input_index: 2,
+    };
+    let raw = proof.encode();
+    let parsed = SpendProof::parse(&raw).ok().unwrap();
+    assert_eq!(parsed.raw_tx, &raw_tx[..]);
+    assert_eq!(parsed.tx_proof, &tx_proof[..]);
+    assert_eq!(parsed.input_index, 2);
+
+    let with_trailing_bytes = [&raw[..], &[0x00]].concat();
+    let malformed: Vec<&[u8]> = vec![&[], &raw[..raw.len() - 1], &with_trailing_bytes];
+    for raw in malformed {
+        check_failure(SpendProof::parse(raw), InternalError::SpendProofMalformed);
+    }
+}
@@ -0,0 +1,152 @@
+use alloc::{vec, vec::Vec};
+
+use bitcoin::{
+    absolute::LockTime, consensus::serialize, hashes::Hash as _, transaction::Version, Amount,
+    OutPoint, ScriptBuf, Sequence, Transaction as BtcTransaction, TxIn, TxOut, Txid, Witness,
+};
+
+use crate::{
+    error::{Error, InternalError},
+    transaction::Transaction,
+};
+
+fn build_transaction(with_witness: bool) -> BtcTransaction {
+    let input = |n: u8| TxIn {
+        previous_output: OutPoint::new(Txid::from_byte_array([n; 32]), u32::from(n)),
+        script_sig: ScriptBuf::from_bytes(vec![n; usize::from(n)]),
+        sequence: Sequence(0xffff_fff0 + u32::from(n)),
+        witness: if with_witness {
+            Witness::from_slice(&[vec![n; 72], vec![n; 33]])
+        } else {
+            Witness::default()
+        },
+    };
+    let output = |n: u8| TxOut {
+        value: Amount::from_sat(u64::from(n) * 100_000),
+        script_pubkey: ScriptBuf::from_bytes(vec![n; 22]),
+    };
+    BtcTransaction {
+        version: Version::TWO,
+        lock_time: LockTime::from_consensus(840_000),
+        input: vec![input(1), input(2)],
+        output: vec![output(1), output(2), output(3)],
+    }
+}
+
+fn check_transaction(expected: &BtcTransaction) {
+    let raw = serialize(expected);
+    let actual = Transaction::parse(&raw).unwrap_or_else(|_| panic!("failed to parse {raw:02x?}"));
+    assert_eq!(actual.raw(), &raw[..]);
+    assert_eq!(actual.version(), expected.version.0);
+    assert_eq!(actual.lock_time(), expected.lock_time.to_consensus_u32());
+    assert_eq!(actual.inputs().len(), expected.input.len());
+    for (actual, expected) in actual.inputs().iter().zip(expected.input.iter()) {
+        let previous_output = &expected.previous_output;
+        assert_eq!(
+            actual.previous_output.txid,
+            previous_output.txid.to_byte_array()
+        );
+        assert_eq!(actual.previous_output.vout, previous_output.vout);
+        assert_eq!(actual.script_sig, expected.script_sig.as_bytes());
+        assert_eq!(actual.sequence, expected.sequence.0);
+        assert_eq!(actual.witness, expected.witness.to_vec());
+    }
+    assert_eq!(actual.outputs().len(), expected.output.len());
+    for (actual, expected) in actual.outputs().iter().zip(expected.output.iter()) {
+        assert_eq!(actual.value, expected.value.to_sat());
+        assert_eq!(actual.script_pubkey, expected.script_pubkey.as_bytes());
+    }
+    assert_eq!(actual.txid(), expected.txid().to_byte_array());
+    assert_eq!(actual.wtxid(), expected.wtxid().to_byte_array());
+}
+
+fn check_failure(raw: &[u8], expected: InternalError) {
+    match Transaction::parse(raw) {
+        Ok(_) => panic!("should be failed to parse {raw:02x?}"),
+        Err(Error::Internal(actual)) => assert_eq!(actual as i8, expected as i8),
+        Err(_) => panic!("should be an internal error"),
+    }
+}
+
+#[test]
+fn parse_legacy_transaction() {
+    let tx = build_transaction(false);
+    check_transaction(&tx);
+}
+
+#[test]
+fn parse_segwit_transaction() {
+    let tx = build_transaction(true);
+    check_transaction(&tx);
+    // The witness should not affect the transaction hash.
+    let raw = serialize(&tx);
+    let stripped = serialize(&build_transaction(false));
+    assert_ne!(raw, stripped);
+    let txid = Transaction::parse(&raw).ok().unwrap().txid();
+    let stripped_txid = Transaction::parse(&stripped).ok().unwrap().txid();
+    assert_eq!(txid, stripped_txid);
+}
+
+#[test]
+fn parse_large_script() {
+    let mut tx = build_transaction(false);
+    // Requires a 3-bytes `CompactSize` as its length.
+    tx.output[0].script_pubkey = ScriptBuf::from_bytes(vec![0x51; 0x1234]);
+    check_transaction(&tx);
+}
+
+#[test]
+fn failed_to_parse_truncated_data() {
+    let raw = serialize(&build_transaction(true));
+    for len in [0, 3, 4, 5, 6, 40, raw.len() - 1] {
+        check_failure(&raw[..len], InternalError::BitcoinDataUnexpectedEnd);
+    }
+}
+
+#[test]
+fn failed_to_parse_with_trailing_bytes() {
+    let mut raw = serialize(&build_transaction(false));
+    raw.push(0);
+    check_failure(&raw, InternalError::BitcoinDataTrailingBytes);
+}
+
+#[test]
+fn failed_to_parse_unknown_flag() {
+    let mut raw = serialize(&build_transaction(true));
+    raw[5] = 0x02;
+    check_failure(&raw, InternalError::BitcoinTxUnsupportedFlag);
+}
+
+#[test]
+fn failed_to_parse_64_bytes_transaction() {
+    let mut tx = build_transaction(false);
+    tx.input.truncate(1);
+    tx.input[0].script_sig = ScriptBuf::new();
+    tx.output.truncate(1);
+    tx.output[0].script_pubkey = ScriptBuf::from_bytes(vec![0x51; 4]);
+    let raw = serialize(&tx);
+    assert_eq!(raw.len(), 64);
+    check_failure(&raw, InternalError::BitcoinTxSizeIs64);
+
+    // Witness data is not a part of the txid, so it doesn't help.
+    tx.input[0].witness = Witness::from_slice(&[vec![0x01; 72]]);
+    let raw = serialize(&tx);
+    assert!(raw.len() > 64);
+    check_failure(&raw, InternalError::BitcoinTxSizeIs64);
+
+    // One byte more or less is fine.
+    for size in [3, 5] {
+        tx.output[0].script_pubkey = ScriptBuf::from_bytes(vec![0x51; size]);
+        assert!(Transaction::parse(&serialize(&tx)).is_ok());
+    }
+}
+
+#[test]
+fn failed_to_parse_non_canonical_size() {
+    let raw = serialize(&build_transaction(false));
+    // Re-encode the inputs count (`0x02`) as `0xfd 0x02 0x00`.
+    let mut non_canonical: Vec<u8> = raw[..4].to_vec();
+    non_canonical.extend_from_slice(&[0xfd, 0x02, 0x00]);
+    non_canonical.extend_from_slice(&raw[5..]);
+    check_failure(&non_canonical, InternalError::BitcoinDataNonCanonicalSize);
+}
@@ -0,0 +1,362 @@
+use alloc::{vec, vec::Vec};
+
+use bitcoin::{
+    absolute::LockTime,
+    consensus::serialize,
+    hashes::{sha256d, Hash as _},
+    script::PushBytesBuf,
+    transaction::Version,
+    Amount, Block, BlockHash, CompactTarget, OutPoint, ScriptBuf, Sequence,
+    Transaction as BtcTransaction, TxIn, TxMerkleNode, TxOut, Txid, Witness,
+};
+
+use crate::{
+    error::{Error, InternalError, Result},
+    transaction::Transaction,
+    witness::{self, WitnessProof, WITNESS_COMMITMENT_PREFIX},
+};
+
+const RESERVED_VALUE: [u8; 32] = [0x00; 32];
+
+fn build_transaction(n: u8) -> BtcTransaction {
+    BtcTransaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::new(Txid::from_byte_array([n; 32]), 0),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::from_slice(&[vec![n; 72], vec![n; 33]]),
+        }],
+        output: vec![TxOut {
+            value: Amount::from_sat(u64::from(n) * 100_000),
+            script_pubkey: ScriptBuf::from_bytes(vec![n; 22]),
+        }],
+    }
+}
+
+// The witness commitment is a placeholder, it's updated after all
+// transactions of the block are known.
+fn build_coinbase(with_commitment: bool, reserved_value: &[u8]) -> BtcTransaction {
+    let mut output = vec![TxOut {
+        value: Amount::from_sat(50 * 100_000_000),
+        script_pubkey: ScriptBuf::new(),
+    }];
+    if with_commitment {
+        output.push(TxOut {
+            value: Amount::ZERO,
+            script_pubkey: commitment_script(&[0xff; 32]),
+        });
+    }
+    BtcTransaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::null(),
+            script_sig: ScriptBuf::from_bytes(vec![0x03, 0x01, 0x02, 0x03]),
+            sequence: Sequence::MAX,
+            witness: Witness::from_slice(&[reserved_value]),
+        }],
+        output,
+    }
+}
+
+fn commitment_script(commitment: &[u8; 32]) -> ScriptBuf {
+    let data = [&WITNESS_COMMITMENT_PREFIX[2..], &commitment[..]].concat();
+    ScriptBuf::new_op_return(PushBytesBuf::try_from(data).unwrap())
+}
+
+// Builds a block with 4 segwit transactions after the coinbase transaction.
+fn build_block(coinbase: BtcTransaction) -> Block {
+    let header = bitcoin::block::Header {
+        version: bitcoin::block::Version::ONE,
+        prev_blockhash: BlockHash::all_zeros(),
+        merkle_root: TxMerkleNode::all_zeros(),
+        time: 1_700_000_000,
+        bits: CompactTarget::from_consensus(0x207f_ffff),
+        nonce: 0,
+    };
+    let txdata = [coinbase]
+        .into_iter()
+        .chain((1..=4).map(build_transaction))
+        .collect();
+    let mut block = Block { header, txdata };
+    let witness_root = block.witness_root().unwrap();
+    let reserved_value = block.txdata[0].input[0].witness.to_vec().concat();
+    let commitment = Block::compute_witness_commitment(&witness_root, &reserved_value);
+    if let Some(output) = block.txdata[0].output.last_mut() {
+        if output
+            .script_pubkey
+            .as_bytes()
+            .starts_with(&WITNESS_COMMITMENT_PREFIX)
+        {
+            output.script_pubkey = commitment_script(&commitment.to_byte_array());
+        }
+    }
+    block.header.merkle_root = block.compute_merkle_root().unwrap();
+    block
+}
+
+// Builds the merkle branch of the wtxid at `index`, from the bottom to the top.
+fn build_branch(block: &Block, index: usize) -> Vec<[u8; 32]> {
+    let leaves = block
+        .txdata
+        .iter()
+        .enumerate()
+        .map(|(i, tx)| {
+            if i == 0 {
+                [0u8; 32]
+            } else {
+                tx.wtxid().to_byte_array()
+            }
+        })
+        .collect::<Vec<_>>();
+    build_merkle_branch(leaves, index)
+}
+
+// Builds the merkle branch of the coinbase txid, from the bottom to the top.
+fn build_coinbase_branch(block: &Block) -> Vec<[u8; 32]> {
+    let leaves = block
+        .txdata
+        .iter()
+        .map(|tx| tx.txid().to_byte_array())
+        .collect::<Vec<_>>();
+    build_merkle_branch(leaves, 0)
+}
+
+fn build_merkle_branch(mut level: Vec<[u8; 32]>, mut index: usize) -> Vec<[u8; 32]> {
+    let mut branch = Vec::new();
+    while level.len() > 1 {
+        if level.len() % 2 == 1 {
+            level.push(*level.last().unwrap());
+        }
+        branch.push(level[index ^ 1]);
+        level = level
+            .chunks(2)
+            .map(|pair| sha256d::Hash::hash(&pair.concat()).to_byte_array())
+            .collect();
+        index >>= 1;
+    }
+    branch
+}
+
+fn verify(block: &Block, tx: &BtcTransaction, tx_index: u32, branch: &[[u8; 32]]) -> Result<()> {
+    let raw_tx = serialize(tx);
+    let raw_coinbase = serialize(&block.txdata[0]);
+    let tx = Transaction::parse(&raw_tx)?;
+    let coinbase = Transaction::parse(&raw_coinbase)?;
+    let branch = branch.iter().collect::<Vec<_>>();
+    witness::verify_witness_commitment(&tx, tx_index, &coinbase, &branch)
+}
+
+fn check_failure(result: Result<()>, expected: InternalError) {
+    match result {
+        Ok(_) => panic!("should be failed"),
+        Err(Error::Internal(actual)) => assert_eq!(actual as i8, expected as i8),
+        Err(_) => panic!("should be an internal error"),
+    }
+}
+
+#[test]
+fn verify_witness_of_all_transactions() {
+    let block = build_block(build_coinbase(true, &RESERVED_VALUE));
+    assert!(block.check_witness_commitment());
+    for (index, tx) in block.txdata.iter().enumerate().skip(1) {
+        let branch = build_branch(&block, index);
+        assert_eq!(branch.len(), 3);
+        let wtxid = tx.wtxid().to_byte_array();
+        let root = witness::calculate_witness_root(
+            &wtxid,
+            index as u32,
+            &branch.iter().collect::<Vec<_>>(),
+        )
+        .ok()
+        .unwrap();
+        assert_eq!(root, block.witness_root().unwrap().to_byte_array());
+        let result = verify(&block, tx, index as u32, &branch);
+        assert!(
+            result.is_ok(),
+            "failed to verify the {index}-th transaction"
+        );
+    }
+}
+
+#[test]
+fn verify_with_non_zero_reserved_value() {
+    let block = build_block(build_coinbase(true, &[0x11; 32]));
+    assert!(block.check_witness_commitment());
+    let branch = build_branch(&block, 2);
+    assert!(verify(&block, &block.txdata[2], 2, &branch).is_ok());
+}
+
+#[test]
+fn failed_to_verify_tampered_witness() {
+    let block = build_block(build_coinbase(true, &RESERVED_VALUE));
+    let branch = build_branch(&block, 2);
+    let mut tx = block.txdata[2].clone();
+    tx.input[0].witness = Witness::from_slice(&[vec![0x00; 72], vec![0x02; 33]]);
+    // The txid is not changed, only the witness is tampered.
+    assert_eq!(tx.txid(), block.txdata[2].txid());
+    check_failure(
+        verify(&block, &tx, 2, &branch),
+        InternalError::WitnessCommitmentMismatch,
+    );
+}
+
+#[test]
+fn failed_to_verify_with_wrong_position() {
+    let block = build_block(build_coinbase(true, &RESERVED_VALUE));
+    let tx = &block.txdata[1];
+    let branch = build_branch(&block, 1);
+    check_failure(
+        verify(&block, tx, 2, &branch),
+        InternalError::WitnessCommitmentMismatch,
+    );
+    // Out of the range of the tree.
+    check_failure(
+        verify(&block, tx, 9, &branch),
+        InternalError::WitnessProofMalformed,
+    );
+    // The position of the coinbase transaction.
+    check_failure(
+        verify(&block, tx, 0, &branch),
+        InternalError::WitnessProofMalformed,
+    );
+    // A truncated branch.
+    check_failure(
+        verify(&block, tx, 1, &branch[..2]),
+        InternalError::WitnessCommitmentMismatch,
+    );
+}
+
+#[test]
+fn failed_to_verify_with_bad_coinbase() {
+    let block = build_block(build_coinbase(false, &RESERVED_VALUE));
+    let branch = build_branch(&block, 1);
+    check_failure(
+        verify(&block, &block.txdata[1], 1, &branch),
+        InternalError::WitnessCommitmentNotFound,
+    );
+
+    let block = build_block(build_coinbase(true, &[0x00; 31]));
+    let branch = build_branch(&block, 1);
+    check_failure(
+        verify(&block, &block.txdata[1], 1, &branch),
+        InternalError::WitnessProofMalformed,
+    );
+}
+
+#[test]
+fn verify_witness_proofs() {
+    let block = build_block(build_coinbase(true, &RESERVED_VALUE));
+    let merkle_root = block.header.merkle_root.to_byte_array();
+    let tx_count = block.txdata.len() as u32;
+    let coinbase_tx = serialize(&block.txdata[0]);
+    let coinbase_branch = build_coinbase_branch(&block);
+    for (index, tx) in block.txdata.iter().enumerate().skip(1) {
+        let raw_tx = serialize(tx);
+        let tx = Transaction::parse(&raw_tx).ok().unwrap();
+        let branch = build_branch(&block, index);
+        let proof = WitnessProof {
+            coinbase_tx: &coinbase_tx,
+            coinbase_branch: coinbase_branch.iter().collect(),
+            branch: branch.iter().collect(),
+        };
+        let result =
+            witness::verify_witness_proof(&tx, index as u32, tx_count, &merkle_root, &proof);
+        assert!(
+            result.is_ok(),
+            "failed to verify the {index}-th transaction"
+        );
+    }
+}
+
+#[test]
+fn failed_to_verify_witness_proofs() {
+    let block = build_block(build_coinbase(true, &RESERVED_VALUE));
+    let merkle_root = block.header.merkle_root.to_byte_array();
+    let tx_count = block.txdata.len() as u32;
+    let coinbase_tx = serialize(&block.txdata[0]);
+    let coinbase_branch = build_coinbase_branch(&block);
+    let raw_tx = serialize(&block.txdata[1]);
+    let tx = Transaction::parse(&raw_tx).ok().unwrap();
+    let branch = build_branch(&block, 1);
+    let verify = |coinbase_tx: &[u8], coinbase_branch: &[[u8; 32]], branch: &[[u8; 32]]| {
+        let proof = WitnessProof {
+            coinbase_tx,
+            coinbase_branch: coinbase_branch.iter().collect(),
+            branch: branch.iter().collect(),
+        };
+        witness::verify_witness_proof(&tx, 1, tx_count, &merkle_root, &proof)
+    };
+    assert!(verify(&coinbase_tx, &coinbase_branch, &branch).is_ok());
+
+    // The branches should have the same length as the depth of the tree.
+    let mut longer_branch = branch.clone();
+    longer_branch.push([0x00; 32]);
+    for (coinbase_branch, branch) in [
+        (&coinbase_branch[..2], &branch[..]),
+        (&coinbase_branch[..], &branch[..2]),
+        (&coinbase_branch[..], &longer_branch[..]),
+    ] {
+        check_failure(
+            verify(&coinbase_tx, coinbase_branch, branch),
+            InternalError::WitnessProofMalformed,
+        );
+    }
+
+    // A coinbase transaction which is not in the block.
+    let other_block = build_block(build_coinbase(true, &[0x11; 32]));
+    let other_coinbase_tx = serialize(&other_block.txdata[0]);
+    check_failure(
+        verify(&other_coinbase_tx, &coinbase_branch, &branch),
+        InternalError::WitnessProofMalformed,
+    );
+    // Another transaction in the block, as the coinbase transaction.
+    let other_branch = build_merkle_branch(
+        block
+            .txdata
+            .iter()
+            .map(|tx| tx.txid().to_byte_array())
+            .collect(),
+        1,
+    );
+    check_failure(
+        verify(&raw_tx, &other_branch, &branch),
+        InternalError::WitnessProofMalformed,
+    );
+}
+
+#[test]
+fn calculate_merkle_tree_depth() {
+    for (count, depth) in [(1, 0), (2, 1), (3, 2), (4, 2), (5, 3), (8, 3), (9, 4)] {
+        assert_eq!(witness::merkle_tree_depth(count), depth, "count: {count}");
+    }
+}
+
+#[test]
+fn encode_and_parse_witness_proof() {
+    let block = build_block(build_coinbase(true, &RESERVED_VALUE));
+    let coinbase_tx = serialize(&block.txdata[0]);
+    let coinbase_branch = build_coinbase_branch(&block);
+    let branch = build_branch(&block, 3);
+    let proof = WitnessProof {
+        coinbase_tx: &coinbase_tx,
+        coinbase_branch: coinbase_branch.iter().collect(),
+        branch: branch.iter().collect(),
+    };
+    let raw = proof.encode();
+    let parsed = WitnessProof::parse(&raw).ok().unwrap();
+    assert_eq!(parsed.coinbase_tx, &coinbase_tx[..]);
+    assert_eq!(parsed.coinbase_branch, proof.coinbase_branch);
+    assert_eq!(parsed.branch, proof.branch);
+
+    let mut with_trailing_bytes = raw.clone();
+    with_trailing_bytes.push(0x00);
+    for malformed in [&raw[..raw.len() - 1], &with_trailing_bytes[..]] {
+        check_failure(
+            WitnessProof::parse(malformed).map(|_| ()),
+            InternalError::WitnessProofMalformed,
+        );
+    }
+}
@@ -0,0 +1,310 @@
+//! A minimal zero-copy parser for Bitcoin transactions.
+//!
+//! Only the consensus serialization is supported, both the legacy format and
+//! the segwit format (BIP-144).
+
+use alloc::vec::Vec;
+
+use sha2::{Digest as _, Sha256};
+
+use crate::error::{InternalError, Result};
+
+/// The hash of a Bitcoin transaction, in internal byte order.
+///
+/// The byte order is the reverse of what block explorers display.
+pub type Txid = [u8; 32];
+
+/// The hash of a Bitcoin transaction with its witness data, in internal byte
+/// order.
+pub type Wtxid = [u8; 32];
+
+/// A parsed Bitcoin transaction, which borrows the raw bytes.
+pub struct Transaction<'r> {
+    raw: &'r [u8],
+    version: i32,
+    inputs: Vec<TxIn<'r>>,
+    outputs: Vec<TxOut<'r>>,
+    lock_time: u32,
+    // Position of the inputs and the outputs in the raw bytes, which is
+    // the only part to be hashed besides the version and the lock time.
+    body_start: usize,
+    outputs_start: usize,
+    body_end: usize,
+}
+
+/// A reference to an output of a previous transaction.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct OutPoint {
+    pub txid: Txid,
+    pub vout: u32,
+}
+
+/// An input of a Bitcoin transaction.
+pub struct TxIn<'r> {
+    pub previous_output: OutPoint,
+    pub script_sig: &'r [u8],
+    pub sequence: u32,
+    /// The witness items, it's empty if the transaction is not in the segwit
+    /// format.
+    pub witness: Vec<&'r [u8]>,
+}
+
+/// An output of a Bitcoin transaction.
+pub struct TxOut<'r> {
+    /// The amount, in satoshis.
+    pub value: u64,
+    pub script_pubkey: &'r [u8],
+}
+
+pub(crate) struct Cursor<'r> {
+    data: &'r [u8],
+    position: usize,
+}
+
+impl<'r> Cursor<'r> {
+    pub(crate) fn new(data: &'r [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    pub(crate) fn position(&self) -> usize {
+        self.position
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.position == self.data.len()
+    }
+
+    pub(crate) fn peek_u8(&self) -> Result<u8> {
+        self.data
+            .get(self.position)
+            .copied()
+            .ok_or_else(|| InternalError::BitcoinDataUnexpectedEnd.into())
+    }
+
+    pub(crate) fn read_bytes(&mut self, len: usize) -> Result<&'r [u8]> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or(InternalError::BitcoinDataUnexpectedEnd)?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    pub(crate) fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.read_bytes(N)?);
+        Ok(array)
+    }
+
+    pub(crate) fn read_u8(&mut self) -> Result<u8> {
+        let value = self.peek_u8()?;
+        self.position += 1;
+        Ok(value)
+    }
+
+    pub(crate) fn read_u32(&mut self) -> Result<u32> {
+        self.read_array().map(u32::from_le_bytes)
+    }
+
+    pub(crate) fn read_u64(&mut self) -> Result<u64> {
+        self.read_array().map(u64::from_le_bytes)
+    }
+
+    /// Reads a `CompactSize` unsigned integer, only the canonical encoding is accepted.
+    pub(crate) fn read_compact_size(&mut self) -> Result<u64> {
+        let (value, min) = match self.read_u8()? {
+            0xff => (self.read_u64()?, 0x1_0000_0000),
+            0xfe => (u64::from(self.read_u32()?), 0x1_0000),
+            0xfd => (u64::from(u16::from_le_bytes(self.read_array()?)), 0xfd),
+            n => return Ok(u64::from(n)),
+        };
+        if value < min {
+            return Err(InternalError::BitcoinDataNonCanonicalSize.into());
+        }
+        Ok(value)
+    }
+
+    /// Reads a length, which should not be larger than the remaining data.
+    pub(crate) fn read_length(&mut self) -> Result<usize> {
+        let length = self.read_compact_size()?;
+        let remaining = self.data.len() - self.position;
+        if length > remaining as u64 {
+            return Err(InternalError::BitcoinDataUnexpectedEnd.into());
+        }
+        Ok(length as usize)
+    }
+
+    pub(crate) fn read_var_bytes(&mut self) -> Result<&'r [u8]> {
+        let length = self.read_length()?;
+        self.read_bytes(length)
+    }
+}
+
+impl<'r> Transaction<'r> {
+    /// Parses a transaction from its consensus serialization.
+    ///
+    /// All bytes should be consumed.
+    ///
+    /// Transactions whose serialization without witness data is 64 bytes are
+    /// rejected, since such a transaction has the same size as an inner node
+    /// of the merkle tree, and a proof only commits to the txid
+    /// (CVE-2017-12842).
+    pub fn parse(raw: &'r [u8]) -> Result<Self> {
+        let mut cursor = Cursor::new(raw);
+        let version = cursor.read_u32()? as i32;
+        // The segwit marker is an empty inputs list, then the flag follows.
+        let is_segwit = cursor.peek_u8()? == 0x00;
+        if is_segwit {
+            let _marker = cursor.read_u8()?;
+            if cursor.read_u8()? != 0x01 {
+                return Err(InternalError::BitcoinTxUnsupportedFlag.into());
+            }
+        }
+        let body_start = cursor.position();
+        let inputs_count = cursor.read_length()?;
+        if inputs_count == 0 {
+            return Err(InternalError::BitcoinTxNoInputs.into());
+        }
+        let mut inputs = Vec::with_capacity(inputs_count);
+        for _ in 0..inputs_count {
+            let txid = cursor.read_array()?;
+            let vout = cursor.read_u32()?;
+            let script_sig = cursor.read_var_bytes()?;
+            let sequence = cursor.read_u32()?;
+            let input = TxIn {
+                previous_output: OutPoint { txid, vout },
+                script_sig,
+                sequence,
+                witness: Vec::new(),
+            };
+            inputs.push(input);
+        }
+        let outputs_start = cursor.position();
+        let outputs = read_outputs(&mut cursor)?;
+        let body_end = cursor.position();
+        if is_segwit {
+            for input in &mut inputs {
+                let items_count = cursor.read_length()?;
+                let mut witness = Vec::with_capacity(items_count);
+                for _ in 0..items_count {
+                    witness.push(cursor.read_var_bytes()?);
+                }
+                input.witness = witness;
+            }
+        }
+        let lock_time = cursor.read_u32()?;
+        if !cursor.is_finished() {
+            return Err(InternalError::BitcoinDataTrailingBytes.into());
+        }
+        // The version, the inputs, the outputs and the lock time.
+        if 4 + (body_end - body_start) + 4 == 64 {
+            return Err(InternalError::BitcoinTxSizeIs64.into());
+        }
+        let tx = Self {
+            raw,
+            version,
+            inputs,
+            outputs,
+            lock_time,
+            body_start,
+            outputs_start,
+            body_end,
+        };
+        Ok(tx)
+    }
+
+    /// The raw bytes which the transaction was parsed from.
+    pub fn raw(&self) -> &'r [u8] {
+        self.raw
+    }
+
+    pub fn version(&self) -> i32 {
+        self.version
+    }
+
+    pub fn inputs(&self) -> &[TxIn<'r>] {
+        &self.inputs
+    }
+
+    pub fn outputs(&self) -> &[TxOut<'r>] {
+        &self.outputs
+    }
+
+    /// The raw bytes of the outputs, includes the count of the outputs.
+    ///
+    /// It could be parsed by [`parse_outputs`].
+    pub fn raw_outputs(&self) -> &'r [u8] {
+        &self.raw[self.outputs_start..self.body_end]
+    }
+
+    pub fn lock_time(&self) -> u32 {
+        self.lock_time
+    }
+
+    /// Calculates the transaction hash, which excludes the witness data.
+    pub fn txid(&self) -> Txid {
+        let mut hasher = Sha256::new();
+        hasher.update(&self.raw[..4]);
+        hasher.update(&self.raw[self.body_start..self.body_end]);
+        hasher.update(&self.raw[self.raw.len() - 4..]);
+        sha256(&hasher.finalize())
+    }
+
+    /// Calculates the transaction hash, which includes the witness data.
+    ///
+    /// It's the same as the txid if the transaction is not in the segwit
+    /// format.
+    pub fn wtxid(&self) -> Wtxid {
+        sha256(&sha256(self.raw))
+    }
+}
+
+/// Parses the outputs of a transaction, from the same format as they are
+/// serialized in the transaction.
+///
+/// All bytes should be consumed.
+pub fn parse_outputs<'r>(raw: &'r [u8]) -> Result<Vec<TxOut<'r>>> {
+    let mut cursor = Cursor::new(raw);
+    let outputs = read_outputs(&mut cursor)?;
+    if !cursor.is_finished() {
+        return Err(InternalError::BitcoinDataTrailingBytes.into());
+    }
+    Ok(outputs)
+}
+
+fn read_outputs<'r>(cursor: &mut Cursor<'r>) -> Result<Vec<TxOut<'r>>> {
+    let outputs_count = cursor.read_length()?;
+    let mut outputs = Vec::with_capacity(outputs_count);
+    for _ in 0..outputs_count {
+        let value = cursor.read_u64()?;
+        let script_pubkey = cursor.read_var_bytes()?;
+        outputs.push(TxOut {
+            value,
+            script_pubkey,
+        });
+    }
+    Ok(outputs)
+}
+
+/// Writes a `CompactSize` unsigned integer, in the canonical encoding.
+pub(crate) fn write_compact_size(raw: &mut Vec<u8>, size: usize) {
+    let size = size as u64;
+    if size < 0xfd {
+        raw.push(size as u8);
+    } else if size <= 0xffff {
+        raw.push(0xfd);
+        raw.extend_from_slice(&(size as u16).to_le_bytes());
+    } else if size <= 0xffff_ffff {
+        raw.push(0xfe);
+        raw.extend_from_slice(&(size as u32).to_le_bytes());
+    } else {
+        raw.push(0xff);
+        raw.extend_from_slice(&size.to_le_bytes());
+    }
+}
+
+pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
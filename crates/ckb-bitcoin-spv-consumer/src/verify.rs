@@ -0,0 +1,60 @@
+//! Verify Bitcoin transactions against SPV clients.
+
+use bitcoin::{consensus::deserialize, hashes::Hash as _, MerkleBlock};
+use ckb_bitcoin_spv_verifier::types::{
+    core,
+    packed::{self, TransactionProofReader},
+    prelude::*,
+};
+use ckb_std::debug;
+
+use crate::{
+    error::{InternalError, Result},
+    transaction::Transaction,
+    witness::{self, WitnessProof},
+};
+
+/// Verifies that a transaction is in the chain of an SPV client, and it has
+/// enough confirmations.
+///
+/// The `tx_proof` is a packed `TransactionProof`.
+pub fn verify_transaction(
+    client: &packed::SpvClient,
+    tx: &Transaction,
+    tx_proof: &[u8],
+    confirmations: u32,
+) -> Result<()> {
+    let tx_proof = TransactionProofReader::from_slice(tx_proof)
+        .map_err(|_| InternalError::TxProofMalformed)?;
+    let txid = tx.txid();
+    debug!("verify transaction {txid:02x?} with {confirmations} confirmations");
+    client.verify_transaction(core::Hash::from_bytes_ref(&txid), tx_proof, confirmations)?;
+    Ok(())
+}
+
+/// Verifies that a transaction, includes its witness data, is in the chain of
+/// an SPV client, and it has enough confirmations.
+///
+/// The `tx_proof` is a packed `TransactionProof`, and the `witness_proof` is
+/// a [`WitnessProof`], the coinbase transaction in it is proven against the
+/// block header which is proven by the `tx_proof`.
+pub fn verify_segwit_transaction(
+    client: &packed::SpvClient,
+    tx: &Transaction,
+    tx_proof: &[u8],
+    witness_proof: &[u8],
+    confirmations: u32,
+) -> Result<()> {
+    verify_transaction(client, tx, tx_proof, confirmations)?;
+    let tx_proof = TransactionProofReader::from_slice(tx_proof)
+        .map_err(|_| InternalError::TxProofMalformed)?;
+    let tx_index: u32 = tx_proof.tx_index().unpack();
+    // The merkle block is checked when verify the transaction.
+    let merkle_block: MerkleBlock = deserialize(tx_proof.transaction_proof().raw_data())
+        .map_err(|_| InternalError::TxProofMalformed)?;
+    let merkle_root = merkle_block.header.merkle_root.to_byte_array();
+    let tx_count = merkle_block.txn.num_transactions();
+    debug!("verify witness of transaction (index={tx_index}, count={tx_count})");
+    let witness_proof = WitnessProof::parse(witness_proof)?;
+    witness::verify_witness_proof(tx, tx_index, tx_count, &merkle_root, &witness_proof)
+}
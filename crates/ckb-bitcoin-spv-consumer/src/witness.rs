@@ -0,0 +1,218 @@
+//! Verify the witness data of Bitcoin transactions, through the witness
+//! commitment in the coinbase transaction (BIP-141).
+//!
+//! The proof of a transaction only proves its txid, which excludes the
+//! witness data. To trust the witness data, a witness proof is required
+//! additionally, which is serialized as:
+//! - The coinbase transaction of the same block, as bytes with a
+//!   `CompactSize` length prefix.
+//! - The merkle branch of the coinbase txid in the transaction merkle tree:
+//!   the count of hashes as a `CompactSize`, then the hashes, from the bottom
+//!   to the top.
+//! - The merkle branch of the wtxid in the witness merkle tree, in the same
+//!   format.
+//!
+//! The coinbase transaction is proven against the merkle root of the block
+//! header which is already proven for the transaction, so no more MMR proof
+//! is required.
+//!
+//! The position of a wtxid in the witness merkle tree is the same as the
+//! position of its txid in the transaction merkle tree, so both branches
+//! should have the same length as the depth of the transaction merkle tree.
+
+use alloc::vec::Vec;
+
+use ckb_std::debug;
+
+use crate::{
+    error::{InternalError, Result},
+    transaction::{sha256, write_compact_size, Cursor, Transaction, Wtxid},
+};
+
+/// The prefix of the script pubkey of the output which contains the witness
+/// commitment: `OP_RETURN`, a push of 36 bytes, then the header `0xaa21a9ed`.
+pub const WITNESS_COMMITMENT_PREFIX: [u8; 6] = [0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+
+/// A proof for the witness data of a transaction.
+pub struct WitnessProof<'r> {
+    pub coinbase_tx: &'r [u8],
+    /// The merkle branch of the coinbase txid.
+    pub coinbase_branch: Vec<&'r [u8; 32]>,
+    /// The merkle branch of the wtxid.
+    pub branch: Vec<&'r [u8; 32]>,
+}
+
+impl<'r> WitnessProof<'r> {
+    /// Parses a witness proof, all bytes should be consumed.
+    pub fn parse(raw: &'r [u8]) -> Result<Self> {
+        Self::read(raw).map_err(|_| InternalError::WitnessProofMalformed.into())
+    }
+
+    fn read(raw: &'r [u8]) -> Result<Self> {
+        let mut cursor = Cursor::new(raw);
+        let coinbase_tx = cursor.read_var_bytes()?;
+        let coinbase_branch = read_branch(&mut cursor)?;
+        let branch = read_branch(&mut cursor)?;
+        if !cursor.is_finished() {
+            return Err(InternalError::BitcoinDataTrailingBytes.into());
+        }
+        Ok(Self {
+            coinbase_tx,
+            coinbase_branch,
+            branch,
+        })
+    }
+
+    /// Serializes the witness proof.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut raw = Vec::new();
+        write_compact_size(&mut raw, self.coinbase_tx.len());
+        raw.extend_from_slice(self.coinbase_tx);
+        write_branch(&mut raw, &self.coinbase_branch);
+        write_branch(&mut raw, &self.branch);
+        raw
+    }
+}
+
+fn read_branch<'r>(cursor: &mut Cursor<'r>) -> Result<Vec<&'r [u8; 32]>> {
+    let length = cursor.read_length()?;
+    let mut branch = Vec::with_capacity(length);
+    for _ in 0..length {
+        let hash = cursor.read_bytes(32)?;
+        branch.push(hash.try_into().expect("check length of the hash"));
+    }
+    Ok(branch)
+}
+
+fn write_branch(raw: &mut Vec<u8>, branch: &[&[u8; 32]]) {
+    write_compact_size(raw, branch.len());
+    for hash in branch {
+        raw.extend_from_slice(&hash[..]);
+    }
+}
+
+/// Calculates the depth of a merkle tree with `count` leaves.
+pub fn merkle_tree_depth(count: u32) -> usize {
+    // The depth is `ceil(log2(count))`, and a tree with 1 leaf has no branch.
+    (u32::BITS - count.saturating_sub(1).leading_zeros()) as usize
+}
+
+/// Finds the witness commitment in a coinbase transaction.
+///
+/// If there are multiple outputs which match the pattern, the last one is
+/// the witness commitment.
+pub fn find_witness_commitment<'r>(coinbase: &Transaction<'r>) -> Option<&'r [u8; 32]> {
+    coinbase.outputs().iter().rev().find_map(|output| {
+        let script = output.script_pubkey;
+        if script.len() >= 38 && script.starts_with(&WITNESS_COMMITMENT_PREFIX) {
+            Some(
+                script[6..38]
+                    .try_into()
+                    .expect("check length of the commitment"),
+            )
+        } else {
+            None
+        }
+    })
+}
+
+/// Calculates the root of a merkle tree, from a leaf at `index` and its
+/// merkle branch.
+pub fn calculate_merkle_root(
+    leaf: &[u8; 32],
+    index: u32,
+    branch: &[&[u8; 32]],
+) -> Result<[u8; 32]> {
+    // The index should be in the range of the tree which height is the
+    // length of the branch.
+    if branch.len() < 32 && (index >> branch.len()) != 0 {
+        return Err(InternalError::WitnessProofMalformed.into());
+    }
+    let mut data = [0u8; 64];
+    let root = branch
+        .iter()
+        .enumerate()
+        .fold(*leaf, |node, (height, sibling)| {
+            if (index >> height) & 1 == 0 {
+                data[..32].copy_from_slice(&node);
+                data[32..].copy_from_slice(&sibling[..]);
+            } else {
+                data[..32].copy_from_slice(&sibling[..]);
+                data[32..].copy_from_slice(&node);
+            }
+            sha256(&sha256(&data))
+        });
+    Ok(root)
+}
+
+/// Calculates the root of the witness merkle tree, from a wtxid at `index`
+/// and its merkle branch.
+pub fn calculate_witness_root(wtxid: &Wtxid, index: u32, branch: &[&[u8; 32]]) -> Result<[u8; 32]> {
+    calculate_merkle_root(wtxid, index, branch)
+}
+
+/// Verifies the witness data of a transaction by a witness proof.
+///
+/// The transaction should be proven to be at `tx_index` of a block, which
+/// has `tx_count` transactions and the `merkle_root`, then the coinbase
+/// transaction is proven against the same merkle root.
+pub fn verify_witness_proof(
+    tx: &Transaction,
+    tx_index: u32,
+    tx_count: u32,
+    merkle_root: &[u8; 32],
+    proof: &WitnessProof,
+) -> Result<()> {
+    let depth = merkle_tree_depth(tx_count);
+    if proof.coinbase_branch.len() != depth || proof.branch.len() != depth {
+        debug!(
+            "the depth of the merkle tree is {depth}, but the branches are {} and {}",
+            proof.coinbase_branch.len(),
+            proof.branch.len()
+        );
+        return Err(InternalError::WitnessProofMalformed.into());
+    }
+    let coinbase = Transaction::parse(proof.coinbase_tx)?;
+    let coinbase_root = calculate_merkle_root(&coinbase.txid(), 0, &proof.coinbase_branch)?;
+    if &coinbase_root != merkle_root {
+        debug!("the coinbase transaction is not in the block");
+        return Err(InternalError::WitnessProofMalformed.into());
+    }
+    verify_witness_commitment(tx, tx_index, &coinbase, &proof.branch)
+}
+
+/// Verifies the witness data of a transaction against the witness commitment
+/// in the coinbase transaction.
+///
+/// The `tx_index` is the position of the transaction in the block; both the
+/// transaction and the coinbase transaction should be proven to be in the
+/// same block, which is not checked here, see [`verify_witness_proof`].
+pub fn verify_witness_commitment(
+    tx: &Transaction,
+    tx_index: u32,
+    coinbase: &Transaction,
+    branch: &[&[u8; 32]],
+) -> Result<()> {
+    // The wtxid of the coinbase transaction is always zero in the tree.
+    if tx_index == 0 {
+        return Err(InternalError::WitnessProofMalformed.into());
+    }
+    let reserved_value = match coinbase.inputs() {
+        [input] => match &input.witness[..] {
+            [reserved_value] if reserved_value.len() == 32 => *reserved_value,
+            _ => return Err(InternalError::WitnessProofMalformed.into()),
+        },
+        _ => return Err(InternalError::WitnessProofMalformed.into()),
+    };
+    let expected =
+        find_witness_commitment(coinbase).ok_or(InternalError::WitnessCommitmentNotFound)?;
+    let witness_root = calculate_witness_root(&tx.wtxid(), tx_index, branch)?;
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(&witness_root);
+    data[32..].copy_from_slice(reserved_value);
+    let actual = sha256(&sha256(&data));
+    if &actual != expected {
+        return Err(InternalError::WitnessCommitmentMismatch.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use ckb_bitcoin_spv_tx_builder::cost::{
+    occupied_capacity, reorg_cost, update_cost, CostParams, CyclesModel, TxCost,
+};
+use ckb_bitcoin_spv_verifier::types::packed;
+use ckb_types::{bytes::Bytes, core::ScriptHashType, packed::Script, prelude::*};
+
+const SHANNONS_PER_CKB: u64 = 100_000_000;
+
+/// The arguments to estimate the cost of an SPV instance.
+pub(crate) struct CostArgs {
+    pub(crate) lock_args: Vec<u8>,
+    pub(crate) type_args: Vec<u8>,
+    pub(crate) headers_count: u32,
+    pub(crate) mmr_leaves: u32,
+    pub(crate) fee_rate: u64,
+    pub(crate) cycles: CyclesModel,
+}
+
+/// The args of the SPV type script which only sets the clients count, it
+/// has the same size as the real one.
+pub(crate) fn type_args(clients_count: u8) -> Vec<u8> {
+    packed::SpvTypeArgs::new_builder()
+        .clients_count(clients_count.into())
+        .build()
+        .as_slice()
+        .to_vec()
+}
+
+/// Calculates the occupied capacities of SPV cells, and estimates the cost
+/// of an update and each size of reorgs, then returns the report.
+///
+/// Only the sizes of scripts matter, so the code hashes are dummy.
+pub(crate) fn report(args: &CostArgs) -> Result<String, String> {
+    let script = |args: &[u8]| {
+        Script::new_builder()
+            .hash_type(ScriptHashType::Type.into())
+            .args(Bytes::from(args.to_vec()).pack())
+            .build()
+    };
+    let params = CostParams {
+        lock_script: script(&args.lock_args),
+        type_script: script(&args.type_args),
+        mmr_leaves: args.mmr_leaves,
+        fee_rate: args.fee_rate,
+        cycles: args.cycles,
+    };
+    let capacity = occupied_capacity(&params.lock_script, &params.type_script)
+        .map_err(|err| err.to_string())?;
+
+    let mut lines = vec![
+        format!("info cell: {}", ckb(capacity.info)),
+        format!("client cell: {}", ckb(capacity.client)),
+        format!(
+            "total ({} clients): {}",
+            capacity.clients_count,
+            ckb(capacity.total())
+        ),
+    ];
+    let headers_count = args.headers_count;
+    let cost = update_cost(&params, headers_count).map_err(|err| err.to_string())?;
+    lines.push(format!(
+        "update ({headers_count} headers): {}",
+        tx_cost(&cost)
+    ));
+    for replaced in 2..capacity.clients_count {
+        let cost = reorg_cost(&params, replaced, headers_count).map_err(|err| err.to_string())?;
+        lines.push(format!(
+            "reorg ({replaced} clients, {headers_count} headers): {}",
+            tx_cost(&cost)
+        ));
+    }
+    Ok(lines.join("\n"))
+}
+
+fn ckb(shannons: u64) -> String {
+    // Occupied capacities are always in whole CKBytes.
+    format!("{} CKB", shannons / SHANNONS_PER_CKB)
+}
+
+fn tx_cost(cost: &TxCost) -> String {
+    format!(
+        "size {} bytes, cycles {}, fee {} shannons",
+        cost.size, cost.cycles, cost.fee
+    )
+}
@@ -0,0 +1,113 @@
+use ckb_bitcoin_spv_verifier::types::{
+    core::{BitcoinChainType, SpvTypeArgs},
+    packed::{
+        SpvBootstrapReader, SpvClientReader, SpvInfoReader, SpvTypeArgsReader, SpvUpdateReader,
+    },
+    prelude::*,
+};
+
+use crate::Kind;
+
+/// The decoded data, and the problems of its values.
+pub(crate) struct Decoded {
+    pub(crate) text: String,
+    pub(crate) problems: Vec<String>,
+}
+
+pub(crate) fn decode(kind: Kind, data: &[u8]) -> Result<Decoded, String> {
+    match kind {
+        Kind::Info => decode_info(data),
+        Kind::Client => decode_client(data),
+        Kind::TypeArgs => decode_type_args(data),
+        Kind::Bootstrap => decode_bootstrap(data),
+        Kind::Update => decode_update(data),
+    }
+}
+
+fn decode_info(data: &[u8]) -> Result<Decoded, String> {
+    let info = SpvInfoReader::from_slice(data).map_err(|err| format!("not an SpvInfo: {err}"))?;
+    Ok(Decoded {
+        text: info.to_string(),
+        problems: Vec::new(),
+    })
+}
+
+fn decode_client(data: &[u8]) -> Result<Decoded, String> {
+    let client =
+        SpvClientReader::from_slice(data).map_err(|err| format!("not an SpvClient: {err}"))?;
+    let mut problems = Vec::new();
+    let headers_mmr_root = client.headers_mmr_root();
+    let min_height: u32 = headers_mmr_root.min_height().unpack();
+    let max_height: u32 = headers_mmr_root.max_height().unpack();
+    if min_height > max_height {
+        problems.push(format!(
+            "the min height ({min_height}) is greater than the max height ({max_height})"
+        ));
+    }
+    Ok(Decoded {
+        text: client.to_string(),
+        problems,
+    })
+}
+
+fn decode_type_args(data: &[u8]) -> Result<Decoded, String> {
+    let packed_args =
+        SpvTypeArgsReader::from_slice(data).map_err(|err| format!("not an SpvTypeArgs: {err}"))?;
+    let args: SpvTypeArgs = packed_args.unpack();
+    let mut problems = Vec::new();
+    if args.clients_count < 3 {
+        problems.push(format!(
+            "the clients count ({}) should be at least 3",
+            args.clients_count
+        ));
+    }
+    let chain_type = chain_type_name(args.flags).unwrap_or_else(|| {
+        problems.push(format!("unknown chain type in flags ({:08b})", args.flags));
+        "unknown"
+    });
+    let text = format!(
+        "{packed_args}\nclients count: {}\nflags: {:08b}\nchain type: {chain_type}",
+        args.clients_count, args.flags
+    );
+    Ok(Decoded { text, problems })
+}
+
+fn decode_bootstrap(data: &[u8]) -> Result<Decoded, String> {
+    let bootstrap = SpvBootstrapReader::from_slice(data)
+        .map_err(|err| format!("not an SpvBootstrap: {err}"))?
+        .to_entity();
+    let mut problems = Vec::new();
+    if bootstrap.initialize_spv_client().is_err() {
+        problems.push("failed to initialize an SPV client from the bootstrap".to_owned());
+    }
+    Ok(Decoded {
+        text: bootstrap.to_string(),
+        problems,
+    })
+}
+
+fn decode_update(data: &[u8]) -> Result<Decoded, String> {
+    let update =
+        SpvUpdateReader::from_slice(data).map_err(|err| format!("not an SpvUpdate: {err}"))?;
+    let mut problems = Vec::new();
+    let headers_count = update.headers().len();
+    if headers_count == 0 {
+        problems.push("no headers in the update".to_owned());
+    }
+    let text = format!("{update}\nheaders count: {headers_count}");
+    Ok(Decoded { text, problems })
+}
+
+/// The chain type is in the highest two bits of the flags.
+pub(crate) fn chain_type_name(flags: u8) -> Option<&'static str> {
+    let chain_type: BitcoinChainType = flags.into();
+    if chain_type == BitcoinChainType::Mainnet {
+        Some("mainnet")
+    } else if chain_type == BitcoinChainType::Testnet {
+        Some("testnet")
+    } else if chain_type == BitcoinChainType::Signet {
+        Some("signet")
+    } else {
+        None
+    }
+}
@@ -0,0 +1,23 @@
+use std::{fs, path::Path, str};
+
+/// Loads the data from a hex string, or from a file.
+///
+/// A file could contain a hex string, or the raw bytes.
+pub(crate) fn load(input: &str) -> Result<Vec<u8>, String> {
+    let path = Path::new(input);
+    if path.is_file() {
+        let content =
+            fs::read(path).map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+        let hex_opt = str::from_utf8(&content)
+            .ok()
+            .and_then(|text| decode_hex(text.trim()).ok());
+        Ok(hex_opt.unwrap_or(content))
+    } else {
+        decode_hex(input)
+    }
+}
+
+pub(crate) fn decode_hex(input: &str) -> Result<Vec<u8>, String> {
+    let hex_str = input.strip_prefix("0x").unwrap_or(input);
+    hex::decode(hex_str).map_err(|err| format!("\"{input}\" is neither a file nor a hex: {err}"))
+}
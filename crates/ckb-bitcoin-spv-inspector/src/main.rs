@@ -0,0 +1,175 @@
+//! A command-line tool to inspect and decode cells of Bitcoin SPV instances.
+//!
+//! All data could be provided as hex strings, with or without the `0x`
+//! prefix, or as paths of files, which contain the hex strings or the raw
+//! bytes.
+
+use std::process::ExitCode;
+
+use ckb_bitcoin_spv_tx_builder::cost::CyclesModel;
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+
+mod cost;
+mod decode;
+mod input;
+mod ring;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Decode an SPV data, then check its values.
+    Decode {
+        /// The kind of the data.
+        #[arg(value_enum)]
+        kind: Kind,
+        /// The data, a hex string or a path of a file.
+        input: String,
+    },
+    /// Check that the SPV info cell and the SPV client cells form a
+    /// consistent ring.
+    CheckRing {
+        /// The data of the SPV info cell.
+        #[arg(long)]
+        info: String,
+        /// The data of an SPV client cell, all client cells should be
+        /// provided.
+        #[arg(long = "client", required = true)]
+        clients: Vec<String>,
+        /// The args of the SPV type script.
+        #[arg(long)]
+        type_args: Option<String>,
+    },
+    /// Calculate the occupied capacities of SPV cells, and estimate the
+    /// cost of transactions.
+    ///
+    /// The cycles are estimated by a linear model, its defaults are
+    /// calibrated with the cycles of the contracts in this repository.
+    #[command(group(ArgGroup::new("clients").required(true).args(["type_args", "clients_count"])))]
+    Cost {
+        /// The args of the lock script of SPV cells.
+        #[arg(long, default_value = "")]
+        lock_args: String,
+        /// The args of the SPV type script.
+        #[arg(long)]
+        type_args: Option<String>,
+        /// The count of SPV clients, if the SPV type script is not created
+        /// yet.
+        #[arg(long)]
+        clients_count: Option<u8>,
+        /// The count of headers in each transaction.
+        #[arg(long, default_value_t = 1)]
+        headers: u32,
+        /// The count of headers in the MMR of SPV clients.
+        #[arg(long, default_value_t = 100_000)]
+        mmr_leaves: u32,
+        /// The fee rate, in shannons per KB.
+        #[arg(long, default_value_t = 1000)]
+        fee_rate: u64,
+        /// The base cycles of a transaction.
+        #[arg(long)]
+        cycles_base: Option<u64>,
+        /// The cycles to verify each header.
+        #[arg(long)]
+        cycles_per_header: Option<u64>,
+        /// The cycles to write each SPV client.
+        #[arg(long)]
+        cycles_per_client: Option<u64>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub(crate) enum Kind {
+    /// `SpvInfo`, the data of the SPV info cell.
+    Info,
+    /// `SpvClient`, the data of an SPV client cell.
+    Client,
+    /// `SpvTypeArgs`, the args of the SPV type script.
+    TypeArgs,
+    /// `SpvBootstrap`, the witness to create an SPV instance.
+    Bootstrap,
+    /// `SpvUpdate`, the witness to update an SPV instance.
+    Update,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Decode { kind, input } => input::load(&input)
+            .and_then(|data| decode::decode(kind, &data))
+            .map(|decoded| {
+                println!("{}", decoded.text);
+                decoded.problems
+            }),
+        Command::CheckRing {
+            info,
+            clients,
+            type_args,
+        } => input::load(&info).and_then(|info| {
+            let clients = clients
+                .iter()
+                .map(String::as_str)
+                .map(input::load)
+                .collect::<Result<Vec<_>, _>>()?;
+            let type_args = type_args.as_deref().map(input::load).transpose()?;
+            ring::check_ring(&info, &clients, type_args.as_deref())
+        }),
+        Command::Cost {
+            lock_args,
+            type_args,
+            clients_count,
+            headers,
+            mmr_leaves,
+            fee_rate,
+            cycles_base,
+            cycles_per_header,
+            cycles_per_client,
+        } => input::decode_hex(&lock_args).and_then(|lock_args| {
+            let type_args = match type_args {
+                Some(type_args) => input::load(&type_args)?,
+                None => cost::type_args(clients_count.unwrap_or_default()),
+            };
+            let default_cycles = CyclesModel::default();
+            let args = cost::CostArgs {
+                lock_args,
+                type_args,
+                headers_count: headers,
+                mmr_leaves,
+                fee_rate,
+                cycles: CyclesModel {
+                    base: cycles_base.unwrap_or(default_cycles.base),
+                    per_header: cycles_per_header.unwrap_or(default_cycles.per_header),
+                    per_client: cycles_per_client.unwrap_or(default_cycles.per_client),
+                },
+            };
+            cost::report(&args).map(|report| {
+                println!("{report}");
+                Vec::new()
+            })
+        }),
+    };
+    match result {
+        Ok(problems) if problems.is_empty() => {
+            println!("OK");
+            ExitCode::SUCCESS
+        }
+        Ok(problems) => {
+            for problem in problems {
+                println!("problem: {problem}");
+            }
+            ExitCode::from(1)
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use ckb_bitcoin_spv_model::ring::prev_client_id;
+use ckb_bitcoin_spv_verifier::types::{
+    core::{BitcoinChainType, SpvTypeArgs, U256},
+    packed::{SpvClient, SpvClientReader, SpvInfoReader, SpvTypeArgsReader},
+    prelude::*,
+};
+
+/// Checks that the SPV info cell and the SPV client cells form a consistent
+/// ring, then returns the problems.
+///
+/// If the args of the SPV type script is not provided, the count of the
+/// provided clients is used as the clients count.
+pub(crate) fn check_ring(
+    info: &[u8],
+    clients: &[Vec<u8>],
+    type_args: Option<&[u8]>,
+) -> Result<Vec<String>, String> {
+    let tip_client_id: u8 = SpvInfoReader::from_slice(info)
+        .map_err(|err| format!("the info is not an SpvInfo: {err}"))?
+        .tip_client_id()
+        .into();
+    let clients = clients
+        .iter()
+        .enumerate()
+        .map(|(index, data)| {
+            SpvClientReader::from_slice(data)
+                .map(|client| client.to_entity())
+                .map_err(|err| format!("the {index}-th client is not an SpvClient: {err}"))
+        })
+        .collect::<Result<Vec<SpvClient>, _>>()?;
+    let type_args: Option<SpvTypeArgs> = type_args
+        .map(|data| {
+            SpvTypeArgsReader::from_slice(data)
+                .map(|args| args.unpack())
+                .map_err(|err| format!("the type args is not an SpvTypeArgs: {err}"))
+        })
+        .transpose()?;
+
+    let mut problems = Vec::new();
+
+    let clients_count = if let Some(ref args) = type_args {
+        if usize::from(args.clients_count) != clients.len() {
+            problems.push(format!(
+                "the clients count is {} but {} clients are provided",
+                args.clients_count,
+                clients.len()
+            ));
+        }
+        args.clients_count
+    } else {
+        u8::try_from(clients.len()).map_err(|_| "too many clients".to_owned())?
+    };
+    if clients_count < 3 {
+        problems.push(format!(
+            "the clients count ({clients_count}) should be at least 3"
+        ));
+    }
+    if tip_client_id >= clients_count {
+        problems.push(format!(
+            "the tip client id ({tip_client_id}) is out of the clients count ({clients_count})"
+        ));
+        return Ok(problems);
+    }
+
+    let mut slots: Vec<Option<&SpvClient>> = vec![None; usize::from(clients_count)];
+    for client in &clients {
+        let id: u8 = client.id().into();
+        match slots.get_mut(usize::from(id)) {
+            None => problems.push(format!(
+                "the client id ({id}) is out of the clients count ({clients_count})"
+            )),
+            Some(Some(_)) => problems.push(format!("the client id ({id}) is duplicated")),
+            Some(slot) => *slot = Some(client),
+        }
+    }
+    for (id, slot) in slots.iter().enumerate() {
+        if slot.is_none() {
+            problems.push(format!("the client (id={id}) is not found"));
+        }
+    }
+    let Some(tip_client) = slots[usize::from(tip_client_id)] else {
+        return Ok(problems);
+    };
+
+    // All clients are initialized from the same bootstrap, and the tip
+    // client has the best chain, so walks back from the tip client to check
+    // all the others.
+    let (tip_min_height, tip_max_height, tip_chain_work) = heights_and_chain_work(tip_client);
+    let skip_chain_work = type_args
+        .as_ref()
+        .is_some_and(|args| BitcoinChainType::Testnet == args.flags.into());
+    let mut id = prev_client_id(tip_client_id, clients_count);
+    while id != tip_client_id {
+        if let Some(client) = slots[usize::from(id)] {
+            let (min_height, max_height, chain_work) = heights_and_chain_work(client);
+            if min_height != tip_min_height {
+                problems.push(format!(
+                    "the min height of the client (id={id}) is {min_height}, \
+                    but the tip client's is {tip_min_height}"
+                ));
+            }
+            if max_height > tip_max_height {
+                problems.push(format!(
+                    "the max height of the client (id={id}) is {max_height}, \
+                    higher than the tip client's {tip_max_height}"
+                ));
+            }
+            // Reorgs on the testnet don't require more chain work.
+            if !skip_chain_work && chain_work > tip_chain_work {
+                problems.push(format!(
+                    "the client (id={id}) has more chain work than the tip client"
+                ));
+            }
+        }
+        id = prev_client_id(id, clients_count);
+    }
+
+    Ok(problems)
+}
+
+fn heights_and_chain_work(client: &SpvClient) -> (u32, u32, U256) {
+    let headers_mmr_root = client.headers_mmr_root();
+    (
+        headers_mmr_root.min_height().unpack(),
+        headers_mmr_root.max_height().unpack(),
+        headers_mmr_root.partial_chain_work().unpack(),
+    )
+}
@@ -0,0 +1,45 @@
+use ckb_bitcoin_spv_tx_builder::cost::CyclesModel;
+use ckb_bitcoin_spv_verifier::types::packed;
+
+use crate::cost::{report, type_args, CostArgs};
+
+// The args of the permissionless update lock, with the reward mode.
+const LOCK_ARGS_SIZE: usize = 32 + 20 + 8;
+
+fn args(type_args: Vec<u8>) -> CostArgs {
+    CostArgs {
+        lock_args: vec![5u8; LOCK_ARGS_SIZE],
+        type_args,
+        headers_count: 20,
+        mmr_leaves: 100_000,
+        fee_rate: 1000,
+        cycles: CyclesModel::default(),
+    }
+}
+
+#[test]
+fn report_capacities_and_costs() {
+    let text = report(&args(type_args(5))).unwrap();
+    let lines = text.lines().collect::<Vec<_>>();
+
+    let cell_size = 8 + (32 + 1 + LOCK_ARGS_SIZE) + (32 + 1 + packed::SpvTypeArgs::TOTAL_SIZE);
+    let info = cell_size + packed::SpvInfo::TOTAL_SIZE;
+    let client = cell_size + packed::SpvClient::TOTAL_SIZE;
+    assert_eq!(lines[0], format!("info cell: {info} CKB"));
+    assert_eq!(lines[1], format!("client cell: {client} CKB"));
+    assert_eq!(
+        lines[2],
+        format!("total (5 clients): {} CKB", info + client * 5)
+    );
+    assert!(lines[3].starts_with("update (20 headers): "));
+    // Reorgs replace 2 to 4 clients.
+    assert_eq!(lines.len(), 7);
+    assert!(lines[4].starts_with("reorg (2 clients, 20 headers): "));
+    assert!(lines[6].starts_with("reorg (4 clients, 20 headers): "));
+}
+
+#[test]
+fn report_bad_type_args() {
+    assert!(report(&args(vec![0u8; 3])).is_err());
+    assert!(report(&args(type_args(2))).is_err());
+}
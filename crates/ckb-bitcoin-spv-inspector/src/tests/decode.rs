@@ -0,0 +1,89 @@
+use bitcoin::{blockdata::constants::genesis_block, Network};
+use ckb_bitcoin_spv_verifier::types::{core, packed, prelude::*};
+
+use crate::{
+    decode::{chain_type_name, decode},
+    input::decode_hex,
+    Kind,
+};
+
+fn type_args(clients_count: u8, flags: u8) -> packed::SpvTypeArgs {
+    packed::SpvTypeArgs::new_builder()
+        .type_id(core::Hash::from_bytes_ref(&[1u8; 32]).pack())
+        .clients_count(clients_count.into())
+        .flags(flags.into())
+        .build()
+}
+
+#[test]
+fn load_hex() {
+    assert_eq!(decode_hex("0x0102").unwrap(), vec![1, 2]);
+    assert_eq!(decode_hex("ff").unwrap(), vec![0xff]);
+    assert!(decode_hex("0xzz").is_err());
+}
+
+#[test]
+fn chain_types_in_flags() {
+    // The chain type is in the highest two bits of the flags.
+    let names = (0..4u8)
+        .filter_map(|bits| chain_type_name(bits << 6))
+        .collect::<Vec<_>>();
+    assert_eq!(names.len(), 3);
+    for name in ["mainnet", "testnet", "signet"] {
+        assert!(names.contains(&name));
+    }
+    // The lower bits are not used by the chain type.
+    assert_eq!(chain_type_name(0b0011_1111), chain_type_name(0));
+}
+
+#[test]
+fn decode_type_args() {
+    let args = type_args(5, 0);
+    let decoded = decode(Kind::TypeArgs, args.as_slice()).unwrap();
+    assert!(decoded.problems.is_empty());
+    assert!(decoded.text.contains("clients count: 5"));
+    assert!(decoded.text.contains("chain type: mainnet"));
+
+    let args = type_args(2, 0);
+    let decoded = decode(Kind::TypeArgs, args.as_slice()).unwrap();
+    assert_eq!(decoded.problems.len(), 1);
+
+    assert!(decode(Kind::TypeArgs, &args.as_slice()[1..]).is_err());
+}
+
+#[test]
+fn decode_info_and_client() {
+    let info = packed::SpvInfo::new_builder()
+        .tip_client_id(3u8.into())
+        .build();
+    let decoded = decode(Kind::Info, info.as_slice()).unwrap();
+    assert!(decoded.problems.is_empty());
+    assert!(decode(Kind::Client, info.as_slice()).is_err());
+
+    let headers_mmr_root = packed::HeaderDigest::new_builder()
+        .min_height(10u32.pack())
+        .max_height(9u32.pack())
+        .build();
+    let client = packed::SpvClient::new_builder()
+        .headers_mmr_root(headers_mmr_root)
+        .build();
+    let decoded = decode(Kind::Client, client.as_slice()).unwrap();
+    assert_eq!(decoded.problems.len(), 1);
+    assert!(decode(Kind::Info, client.as_slice()).is_err());
+}
+
+#[test]
+fn decode_bootstrap_and_update() {
+    let header = genesis_block(Network::Bitcoin).header;
+    let bootstrap = packed::SpvBootstrap::new_builder()
+        .height(0u32.pack())
+        .header(header.pack())
+        .build();
+    let decoded = decode(Kind::Bootstrap, bootstrap.as_slice()).unwrap();
+    assert!(decoded.problems.is_empty());
+
+    let update = packed::SpvUpdate::new_builder().build();
+    let decoded = decode(Kind::Update, update.as_slice()).unwrap();
+    assert!(decoded.text.contains("headers count: 0"));
+    assert_eq!(decoded.problems.len(), 1);
+}
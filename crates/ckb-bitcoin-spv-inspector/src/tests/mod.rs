@@ -0,0 +1,3 @@
+mod cost;
+mod decode;
+mod ring;
@@ -0,0 +1,88 @@
+use ckb_bitcoin_spv_verifier::types::{packed, prelude::*};
+
+use crate::ring::check_ring;
+
+const CLIENTS_COUNT: u8 = 5;
+
+fn info(tip_client_id: u8) -> Vec<u8> {
+    packed::SpvInfo::new_builder()
+        .tip_client_id(tip_client_id.into())
+        .build()
+        .as_slice()
+        .to_vec()
+}
+
+fn client(id: u8, max_height: u32) -> Vec<u8> {
+    let headers_mmr_root = packed::HeaderDigest::new_builder()
+        .min_height(100u32.pack())
+        .max_height(max_height.pack())
+        .build();
+    packed::SpvClient::new_builder()
+        .id(id.into())
+        .headers_mmr_root(headers_mmr_root)
+        .build()
+        .as_slice()
+        .to_vec()
+}
+
+fn type_args(clients_count: u8) -> Vec<u8> {
+    packed::SpvTypeArgs::new_builder()
+        .clients_count(clients_count.into())
+        .build()
+        .as_slice()
+        .to_vec()
+}
+
+/// Clients after updates: the tip client has the max height, the next
+/// client has the min height.
+fn clients(tip_client_id: u8) -> Vec<Vec<u8>> {
+    (0..CLIENTS_COUNT)
+        .map(|id| {
+            let distance = (tip_client_id + CLIENTS_COUNT - id) % CLIENTS_COUNT;
+            client(id, 200 - u32::from(distance))
+        })
+        .collect()
+}
+
+#[test]
+fn consistent_ring() {
+    for tip_client_id in 0..CLIENTS_COUNT {
+        let problems = check_ring(&info(tip_client_id), &clients(tip_client_id), None).unwrap();
+        assert!(problems.is_empty(), "{problems:?}");
+        let args = type_args(CLIENTS_COUNT);
+        let problems =
+            check_ring(&info(tip_client_id), &clients(tip_client_id), Some(&args)).unwrap();
+        assert!(problems.is_empty(), "{problems:?}");
+    }
+
+    // After a reorg, the cleared clients have the same data as the tip client.
+    let mut clients = clients(2);
+    clients[3] = client(3, 200);
+    clients[4] = client(4, 200);
+    let problems = check_ring(&info(2), &clients, None).unwrap();
+    assert!(problems.is_empty(), "{problems:?}");
+}
+
+#[test]
+fn inconsistent_ring() {
+    let problems = check_ring(&info(CLIENTS_COUNT), &clients(0), None).unwrap();
+    assert_eq!(problems.len(), 1);
+
+    let args = type_args(CLIENTS_COUNT + 1);
+    let problems = check_ring(&info(0), &clients(0), Some(&args)).unwrap();
+    // The count is mismatched, and the last client is not found.
+    assert_eq!(problems.len(), 2);
+
+    let mut clients = self::clients(1);
+    clients[3] = client(1, 199);
+    let problems = check_ring(&info(1), &clients, None).unwrap();
+    // Client 1 is duplicated, and client 3 is not found.
+    assert_eq!(problems.len(), 2);
+
+    let problems = check_ring(&info(1), &self::clients(2), None).unwrap();
+    // Client 2 is higher than the tip client.
+    assert_eq!(problems.len(), 1);
+
+    assert!(check_ring(&info(0)[1..], &self::clients(0), None).is_err());
+    assert!(check_ring(&info(0), &[info(0)], None).is_err());
+}
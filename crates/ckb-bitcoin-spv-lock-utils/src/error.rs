@@ -0,0 +1,25 @@
+use core::result;
+
+use ckb_std::error::SysError;
+
+pub type Result<T> = result::Result<T, Error>;
+
+/// Errors in current crate.
+///
+/// They don't have error codes, the lock scripts which use this crate map
+/// them into their own errors.
+pub enum Error {
+    Sys(SysError),
+    WitnessMalformed,
+    SignatureMalformed,
+    SignatureIsIncorrect,
+    SpvInfoNotFound,
+    SpvClientNotFound,
+    TipNotAdvanced,
+}
+
+impl From<SysError> for Error {
+    fn from(err: SysError) -> Self {
+        Self::Sys(err)
+    }
+}
@@ -0,0 +1,11 @@
+//! Utilities for lock scripts of the Bitcoin SPV cells.
+//!
+//! The lock scripts which allow anyone to update the SPV cells still have an
+//! owner, this crate helps them to verify the signature of the owner, and to
+//! check the updates of the SPV instance before rewarding relayers.
+
+#![no_std]
+
+pub mod error;
+pub mod reward;
+pub mod signature;
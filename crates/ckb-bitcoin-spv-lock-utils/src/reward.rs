@@ -0,0 +1,88 @@
+//! Check that the SPV instance, which is locked by current lock, is really
+//! updated in current transaction, so the relayer deserves the reward.
+//!
+//! The Bitcoin SPV type script verifies the new tip client against the
+//! current tip client, which is in cell deps. Here, only the heights of both
+//! clients are compared.
+
+use ckb_bitcoin_spv_verifier::types::{
+    core::SpvInfo,
+    packed::{self, SpvClientReader, SpvInfoReader},
+    prelude::*,
+};
+use ckb_std::{ckb_constants::Source, debug, high_level as hl};
+
+use crate::error::{Error, Result};
+
+/// Checks that the tip client of the SPV instance is replaced by a higher one.
+pub fn check_tip_advanced(spv_type_hash: &[u8; 32]) -> Result<()> {
+    let input_info = load_input_info(spv_type_hash)?;
+    let output_info = load_info(spv_type_hash, Source::Output)?;
+    debug!(
+        "tip client id: {} -> {}",
+        input_info.tip_client_id, output_info.tip_client_id
+    );
+    if input_info.tip_client_id == output_info.tip_client_id {
+        return Err(Error::TipNotAdvanced);
+    }
+    let old_tip_client = load_client(spv_type_hash, input_info.tip_client_id, Source::CellDep)?;
+    let new_tip_client = load_client(spv_type_hash, output_info.tip_client_id, Source::Output)?;
+    let old_height: u32 = old_tip_client.headers_mmr_root().max_height().unpack();
+    let new_height: u32 = new_tip_client.headers_mmr_root().max_height().unpack();
+    debug!("tip height: {old_height} -> {new_height}");
+    if new_height <= old_height {
+        return Err(Error::TipNotAdvanced);
+    }
+    Ok(())
+}
+
+// The SPV info cell should be unlocked by current lock.
+fn load_input_info(spv_type_hash: &[u8; 32]) -> Result<SpvInfo> {
+    for (index, type_hash_opt) in
+        hl::QueryIter::new(hl::load_cell_type_hash, Source::GroupInput).enumerate()
+    {
+        if type_hash_opt.as_ref() != Some(spv_type_hash) {
+            continue;
+        }
+        let data = hl::load_cell_data(index, Source::GroupInput)?;
+        if let Ok(info) = SpvInfoReader::from_slice(&data) {
+            debug!("input info = {info} (index={index}, in group)");
+            return Ok(info.unpack());
+        }
+    }
+    Err(Error::SpvInfoNotFound)
+}
+
+fn load_info(spv_type_hash: &[u8; 32], source: Source) -> Result<SpvInfo> {
+    for (index, type_hash_opt) in hl::QueryIter::new(hl::load_cell_type_hash, source).enumerate() {
+        if type_hash_opt.as_ref() != Some(spv_type_hash) {
+            continue;
+        }
+        let data = hl::load_cell_data(index, source)?;
+        if let Ok(info) = SpvInfoReader::from_slice(&data) {
+            debug!("info = {info} (index={index})");
+            return Ok(info.unpack());
+        }
+    }
+    Err(Error::SpvInfoNotFound)
+}
+
+fn load_client(
+    spv_type_hash: &[u8; 32],
+    client_id: u8,
+    source: Source,
+) -> Result<packed::SpvClient> {
+    for (index, type_hash_opt) in hl::QueryIter::new(hl::load_cell_type_hash, source).enumerate() {
+        if type_hash_opt.as_ref() != Some(spv_type_hash) {
+            continue;
+        }
+        let data = hl::load_cell_data(index, source)?;
+        if let Ok(client) = SpvClientReader::from_slice(&data) {
+            if u8::from(client.id()) == client_id {
+                debug!("client = {client} (index={index})");
+                return Ok(client.to_entity());
+            }
+        }
+    }
+    Err(Error::SpvClientNotFound)
+}
@@ -0,0 +1,102 @@
+//! Verify the signature of the owner.
+//!
+//! The signature is a secp256k1 recoverable signature over the sighash of the
+//! transaction, the sighash is calculated in the same way as the default lock
+//! script of CKB (`secp256k1_blake160_sighash_all`), so any existing wallet
+//! could sign for the owner.
+
+use ckb_hash::{blake2b_256, new_blake2b, Blake2b};
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{
+        packed::{BytesOpt, WitnessArgs},
+        prelude::*,
+    },
+    debug,
+    error::SysError,
+    high_level as hl,
+};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+
+use crate::error::{Error, Result};
+
+pub const SIGNATURE_SIZE: usize = 65;
+pub const PUBKEY_HASH_SIZE: usize = 20;
+
+/// Loads the witness of the first input in current script group, and returns
+/// it with the signature in its lock field.
+///
+/// Returns `None` if there is no signature.
+pub fn load_signed_witness() -> Result<Option<(WitnessArgs, [u8; SIGNATURE_SIZE])>> {
+    let witness = match hl::load_witness(0, Source::GroupInput) {
+        Ok(witness) => witness,
+        Err(SysError::IndexOutOfBound) => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    if witness.is_empty() {
+        return Ok(None);
+    }
+    let witness_args = WitnessArgs::from_slice(&witness).map_err(|_| Error::WitnessMalformed)?;
+    let Some(lock) = witness_args.lock().to_opt() else {
+        return Ok(None);
+    };
+    let signature = lock
+        .raw_data()
+        .as_ref()
+        .try_into()
+        .map_err(|_| Error::SignatureMalformed)?;
+    Ok(Some((witness_args, signature)))
+}
+
+/// Verifies that the signature is signed by the owner, whose public key hash
+/// is `pubkey_hash`.
+pub fn verify(
+    witness_args: WitnessArgs,
+    signature: &[u8; SIGNATURE_SIZE],
+    pubkey_hash: &[u8],
+) -> Result<()> {
+    let message = calculate_sighash(witness_args)?;
+    let recovery_id = RecoveryId::from_byte(signature[64]).ok_or(Error::SignatureMalformed)?;
+    let signature =
+        Signature::from_slice(&signature[..64]).map_err(|_| Error::SignatureMalformed)?;
+    let pubkey = VerifyingKey::recover_from_prehash(&message, &signature, recovery_id)
+        .map_err(|_| Error::SignatureIsIncorrect)?;
+    let actual = blake2b_256(pubkey.to_encoded_point(true).as_bytes());
+    if actual[..PUBKEY_HASH_SIZE] != pubkey_hash[..] {
+        debug!(
+            "the signer {:02x?} is not the owner {pubkey_hash:02x?}",
+            &actual[..PUBKEY_HASH_SIZE]
+        );
+        return Err(Error::SignatureIsIncorrect);
+    }
+    Ok(())
+}
+
+fn calculate_sighash(witness_args: WitnessArgs) -> Result<[u8; 32]> {
+    let tx_hash = hl::load_tx_hash()?;
+    let zeroed_lock = BytesOpt::new_builder()
+        .set(Some([0u8; SIGNATURE_SIZE][..].pack()))
+        .build();
+    let zeroed_witness = witness_args.as_builder().lock(zeroed_lock).build();
+
+    let mut hasher = new_blake2b();
+    hasher.update(&tx_hash);
+    update_witness(&mut hasher, zeroed_witness.as_slice());
+    // Other witnesses in current script group.
+    for witness in hl::QueryIter::new(hl::load_witness, Source::GroupInput).skip(1) {
+        update_witness(&mut hasher, &witness);
+    }
+    // Witnesses which are not associated with any inputs.
+    let inputs_count = hl::QueryIter::new(hl::load_input_since, Source::Input).count();
+    for witness in hl::QueryIter::new(hl::load_witness, Source::Input).skip(inputs_count) {
+        update_witness(&mut hasher, &witness);
+    }
+    let mut message = [0u8; 32];
+    hasher.finalize(&mut message);
+    Ok(message)
+}
+
+fn update_witness(hasher: &mut Blake2b, witness: &[u8]) {
+    hasher.update(&(witness.len() as u64).to_le_bytes());
+    hasher.update(witness);
+}
@@ -0,0 +1,41 @@
+use std::{fmt, result};
+
+pub type Result<T> = result::Result<T, Error>;
+
+/// Errors when an operation is not allowed for an SPV instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// An SPV instance requires at least 3 clients.
+    ClientsCountTooSmall(u8),
+    /// The id of a client is not less than the clients count.
+    ClientIdOutOfRange(u8),
+    ClientNotFound(u8),
+    /// A reorg requires at least 2 clients, otherwise, it's an update.
+    ReorgClientsNotEnough,
+    /// Only SPV instances for the testnet could be reset.
+    ResetNotAllowed,
+    /// The candidate header chain doesn't have any new headers for the fork
+    /// client.
+    CandidateChainTooShort,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ClientsCountTooSmall(count) => {
+                write!(f, "the count of SPV clients ({count}) is too small")
+            }
+            Self::ClientIdOutOfRange(id) => {
+                write!(f, "the SPV client id ({id}) is out of range")
+            }
+            Self::ClientNotFound(id) => write!(f, "the SPV client (id={id}) is not found"),
+            Self::ReorgClientsNotEnough => write!(f, "a reorg requires at least 2 clients"),
+            Self::ResetNotAllowed => write!(f, "only SPV instances for the testnet could be reset"),
+            Self::CandidateChainTooShort => {
+                write!(f, "the candidate header chain doesn't have new headers")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
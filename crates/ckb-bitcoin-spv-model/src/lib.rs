@@ -0,0 +1,21 @@
+//! An off-chain model of the state machine of Bitcoin SPV instances on CKB.
+//!
+//! It mirrors how the CKB Bitcoin SPV type script chooses an operation and
+//! which cells each operation requires, without loading any cell.
+//!
+//! Given the current state of an SPV instance and an operation, the model
+//! returns the expected SPV cells in inputs, outputs and cell deps, and the
+//! next state. Relayers could use it to plan transactions, and tests could
+//! use it as an oracle.
+
+pub mod error;
+mod operation;
+pub mod planner;
+pub mod ring;
+mod state;
+
+#[cfg(test)]
+mod tests;
+
+pub use operation::{Operation, OperationKind};
+pub use state::{SpvCell, SpvState, Transition};
@@ -0,0 +1,66 @@
+use ckb_bitcoin_spv_verifier::types::core::BitcoinChainType;
+
+/// An operation on an existing SPV instance.
+///
+/// To create an SPV instance, see [`SpvState::create`](crate::SpvState::create).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// Replaces the oldest client by a new client.
+    Update,
+    /// Replaces all clients after the fork client by a new client.
+    Reorg { fork_client_id: u8 },
+    /// Replaces all cells with a new bootstrap, only for the testnet.
+    Reset,
+    /// Consumes all cells.
+    Destroy,
+}
+
+/// The kind of an operation, which is chosen by the SPV type script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationKind {
+    Create,
+    Destroy,
+    Update,
+    Reorg,
+    Reset,
+    Unknown,
+}
+
+impl OperationKind {
+    /// Chooses the operation by the count of SPV cells in inputs and
+    /// outputs, as same as `entry::main` of the SPV type script.
+    pub fn classify(
+        inputs_count: usize,
+        outputs_count: usize,
+        clients_count: u8,
+        flags: u8,
+    ) -> Self {
+        let cells_count = 1 + usize::from(clients_count);
+        match (inputs_count, outputs_count) {
+            (0, _) => Self::Create,
+            (_, 0) => Self::Destroy,
+            (2, 2) => Self::Update,
+            (m, n) if m == n && m > 2 && m < cells_count => Self::Reorg,
+            (m, n)
+                if m == n
+                    && m > 2
+                    && m == cells_count
+                    && BitcoinChainType::Testnet == flags.into() =>
+            {
+                Self::Reset
+            }
+            (_, _) => Self::Unknown,
+        }
+    }
+}
+
+impl From<Operation> for OperationKind {
+    fn from(operation: Operation) -> Self {
+        match operation {
+            Operation::Update => Self::Update,
+            Operation::Reorg { .. } => Self::Reorg,
+            Operation::Reset => Self::Reset,
+            Operation::Destroy => Self::Destroy,
+        }
+    }
+}
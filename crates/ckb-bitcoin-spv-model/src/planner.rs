@@ -0,0 +1,144 @@
+//! Plan the next operation of an SPV instance, by the current clients and a
+//! candidate header chain from the prover.
+//!
+//! The best fork client is the newest client whose tip block is still in the
+//! candidate chain. If it's the tip client, new headers are appended by an
+//! update; otherwise, all clients after it are replaced by a reorg.
+//!
+//! If no client is in the candidate chain, the SPV instance has to be
+//! recovered.
+
+use std::ops::RangeInclusive;
+
+use ckb_bitcoin_spv_verifier::types::{packed::SpvClientReader, prelude::*};
+
+use crate::{
+    error::{Error, Result},
+    ring, Operation, SpvState, Transition,
+};
+
+/// The tip block of an SPV client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientTip {
+    pub id: u8,
+    pub height: u32,
+    /// The hash of the tip block, in the same byte order as in SPV clients.
+    pub block_hash: [u8; 32],
+}
+
+/// The block hashes of a candidate header chain, which are continuous.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CandidateChain {
+    /// The height of the first block hash.
+    pub start_height: u32,
+    pub block_hashes: Vec<[u8; 32]>,
+}
+
+/// The plan for the next operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Plan {
+    /// The tip client is in the candidate chain, and there are no new headers.
+    UpToDate,
+    /// Builds a new client based on the client `base_client_id`, with the
+    /// headers in `headers`, then applies the operation.
+    Apply {
+        base_client_id: u8,
+        headers: RangeInclusive<u32>,
+        operation: Operation,
+        transition: Transition,
+    },
+    /// No client is in the candidate chain, the SPV instance has to be
+    /// re-deployed (or reset, for the testnet).
+    RecoveryRequired,
+}
+
+impl ClientTip {
+    pub fn from_client(client: SpvClientReader) -> Self {
+        let mut block_hash = [0u8; 32];
+        block_hash.copy_from_slice(client.tip_block_hash().raw_data());
+        Self {
+            id: client.id().into(),
+            height: client.headers_mmr_root().max_height().unpack(),
+            block_hash,
+        }
+    }
+}
+
+impl CandidateChain {
+    pub fn tip_height(&self) -> Option<u32> {
+        let count = u32::try_from(self.block_hashes.len()).ok()?;
+        count
+            .checked_sub(1)
+            .map(|offset| self.start_height + offset)
+    }
+
+    pub fn block_hash(&self, height: u32) -> Option<&[u8; 32]> {
+        let offset = height.checked_sub(self.start_height)?;
+        self.block_hashes.get(usize::try_from(offset).ok()?)
+    }
+
+    /// Checks if the tip block of a client is in this chain.
+    ///
+    /// A client which is older than the first block hash is never in this
+    /// chain, since it couldn't be checked.
+    pub fn contains(&self, client: &ClientTip) -> bool {
+        self.block_hash(client.height) == Some(&client.block_hash)
+    }
+}
+
+/// Plans the next operation.
+///
+/// All clients of the SPV instance should be provided, in any order.
+pub fn plan(state: &SpvState, clients: &[ClientTip], candidate: &CandidateChain) -> Result<Plan> {
+    let client = |id: u8| {
+        clients
+            .iter()
+            .find(|client| client.id == id)
+            .ok_or(Error::ClientNotFound(id))
+    };
+    let candidate_tip_height = candidate
+        .tip_height()
+        .ok_or(Error::CandidateChainTooShort)?;
+
+    // Walks back from the tip client, to find the best fork client.
+    //
+    // Known Issue #2: when only 1 client is stale, the reorg has the same
+    // structure as an update; so the client just before the tip client is
+    // skipped, and one more client will be replaced.
+    let mut fork_client_opt = None;
+    let mut id = state.tip_client_id();
+    for stale_count in 0..state.clients_count() {
+        let current = client(id)?;
+        if stale_count != 1 && candidate.contains(current) {
+            fork_client_opt = Some(current);
+            break;
+        }
+        id = ring::prev_client_id(id, state.clients_count());
+    }
+    let Some(fork_client) = fork_client_opt else {
+        return Ok(Plan::RecoveryRequired);
+    };
+
+    let operation = if fork_client.id == state.tip_client_id() {
+        if candidate_tip_height <= fork_client.height {
+            return Ok(Plan::UpToDate);
+        }
+        Operation::Update
+    } else {
+        // The candidate chain should replace all stale clients.
+        if candidate_tip_height <= fork_client.height {
+            return Err(Error::CandidateChainTooShort);
+        }
+        Operation::Reorg {
+            fork_client_id: fork_client.id,
+        }
+    };
+
+    let transition = state.apply(operation)?;
+    Ok(Plan::Apply {
+        base_client_id: fork_client.id,
+        headers: (fork_client.height + 1)..=candidate_tip_height,
+        operation,
+        transition,
+    })
+}
@@ -0,0 +1,44 @@
+//! The SPV client cells form a ring: after the last client, it wraps around
+//! back to the first client.
+//!
+//! The client whose id is the `tip_client_id` of the SPV info cell has the
+//! newest data, and the next client has the oldest data.
+
+/// Returns the id of the previous client in the ring.
+pub fn prev_client_id(current: u8, count: u8) -> u8 {
+    if current == 0 {
+        count - 1
+    } else {
+        current - 1
+    }
+}
+
+/// Returns the id of the next client in the ring.
+pub fn next_client_id(current: u8, count: u8) -> u8 {
+    if current + 1 < count {
+        current + 1
+    } else {
+        0
+    }
+}
+
+/// Returns the ids of clients after `start` (exclusive) until `end`
+/// (inclusive), in the order of the ring.
+///
+/// # Panics
+///
+/// Panics if `start` or `end` is not less than `count`, since `end` could
+/// never be reached.
+pub fn client_ids_between(start: u8, end: u8, count: u8) -> Vec<u8> {
+    assert!(
+        start < count && end < count,
+        "client ids {start} and {end} should be less than {count}"
+    );
+    let mut ids = Vec::new();
+    let mut id = start;
+    while id != end {
+        id = next_client_id(id, count);
+        ids.push(id);
+    }
+    ids
+}
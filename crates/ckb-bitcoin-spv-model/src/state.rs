@@ -0,0 +1,171 @@
+use ckb_bitcoin_spv_verifier::types::core::BitcoinChainType;
+
+use crate::{
+    error::{Error, Result},
+    ring, Operation, OperationKind,
+};
+
+/// The state of an SPV instance.
+///
+/// It's always valid, since it could only be built by [`SpvState::new`],
+/// [`SpvState::create`] and [`SpvState::apply`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpvState {
+    clients_count: u8,
+    flags: u8,
+    tip_client_id: u8,
+}
+
+/// A cell of an SPV instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SpvCell {
+    Info,
+    Client(u8),
+}
+
+/// The SPV cells which an operation requires, and the state after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transition {
+    pub kind: OperationKind,
+    pub inputs: Vec<SpvCell>,
+    pub outputs: Vec<SpvCell>,
+    pub cell_deps: Vec<SpvCell>,
+    /// The state after the operation, `None` if the SPV instance is destroyed.
+    pub next: Option<SpvState>,
+}
+
+impl SpvState {
+    pub fn new(clients_count: u8, flags: u8, tip_client_id: u8) -> Result<Self> {
+        if clients_count < 3 {
+            return Err(Error::ClientsCountTooSmall(clients_count));
+        }
+        if tip_client_id >= clients_count {
+            return Err(Error::ClientIdOutOfRange(tip_client_id));
+        }
+        Ok(Self {
+            clients_count,
+            flags,
+            tip_client_id,
+        })
+    }
+
+    /// Creates an SPV instance.
+    ///
+    /// The info cell is the first output, then the client cells are in
+    /// order of their ids; the first client is the tip client.
+    pub fn create(clients_count: u8, flags: u8) -> Result<Transition> {
+        let next = Self::new(clients_count, flags, 0)?;
+        Ok(Transition {
+            kind: OperationKind::Create,
+            inputs: Vec::new(),
+            outputs: next.all_cells(),
+            cell_deps: Vec::new(),
+            next: Some(next),
+        })
+    }
+
+    /// The clients count in the args of the SPV type script.
+    pub fn clients_count(&self) -> u8 {
+        self.clients_count
+    }
+
+    /// The flags in the args of the SPV type script.
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    /// The `tip_client_id` in the SPV info cell.
+    pub fn tip_client_id(&self) -> u8 {
+        self.tip_client_id
+    }
+
+    /// The oldest client, which is next to the tip client.
+    pub fn oldest_client_id(&self) -> u8 {
+        ring::next_client_id(self.tip_client_id, self.clients_count)
+    }
+
+    pub fn is_testnet(&self) -> bool {
+        BitcoinChainType::Testnet == self.flags.into()
+    }
+
+    /// Applies an operation, then returns the transition.
+    pub fn apply(&self, operation: Operation) -> Result<Transition> {
+        let transition = match operation {
+            Operation::Update => {
+                // Same as `update::load_inputs`: the oldest client is replaced,
+                // the current tip client is the cell dep.
+                let client_id = self.oldest_client_id();
+                let cells = vec![SpvCell::Info, SpvCell::Client(client_id)];
+                Transition {
+                    kind: OperationKind::Update,
+                    inputs: cells.clone(),
+                    outputs: cells,
+                    cell_deps: vec![SpvCell::Client(self.tip_client_id)],
+                    next: Some(self.with_tip_client_id(client_id)),
+                }
+            }
+            Operation::Reorg { fork_client_id } => {
+                // Same as `reorg::load_inputs`: all clients after the fork
+                // client are replaced, the fork client is the cell dep.
+                if fork_client_id >= self.clients_count {
+                    return Err(Error::ClientIdOutOfRange(fork_client_id));
+                }
+                let client_ids = ring::client_ids_between(
+                    fork_client_id,
+                    self.tip_client_id,
+                    self.clients_count,
+                );
+                if client_ids.len() < 2 {
+                    return Err(Error::ReorgClientsNotEnough);
+                }
+                let new_tip_client_id = client_ids[0];
+                let cells = Some(SpvCell::Info)
+                    .into_iter()
+                    .chain(client_ids.into_iter().map(SpvCell::Client))
+                    .collect::<Vec<_>>();
+                Transition {
+                    kind: OperationKind::Reorg,
+                    inputs: cells.clone(),
+                    outputs: cells,
+                    cell_deps: vec![SpvCell::Client(fork_client_id)],
+                    next: Some(self.with_tip_client_id(new_tip_client_id)),
+                }
+            }
+            Operation::Reset => {
+                if !self.is_testnet() {
+                    return Err(Error::ResetNotAllowed);
+                }
+                Transition {
+                    kind: OperationKind::Reset,
+                    inputs: self.all_cells(),
+                    outputs: self.all_cells(),
+                    cell_deps: Vec::new(),
+                    next: Some(self.with_tip_client_id(0)),
+                }
+            }
+            Operation::Destroy => Transition {
+                kind: OperationKind::Destroy,
+                inputs: self.all_cells(),
+                outputs: Vec::new(),
+                cell_deps: Vec::new(),
+                next: None,
+            },
+        };
+        Ok(transition)
+    }
+
+    /// All cells: the info cell, then the client cells in order of their ids.
+    fn all_cells(&self) -> Vec<SpvCell> {
+        Some(SpvCell::Info)
+            .into_iter()
+            .chain((0..self.clients_count).map(SpvCell::Client))
+            .collect()
+    }
+
+    fn with_tip_client_id(&self, tip_client_id: u8) -> Self {
+        Self {
+            tip_client_id,
+            ..*self
+        }
+    }
+}
@@ -0,0 +1,4 @@
+mod operation;
+mod planner;
+mod ring;
+mod state;
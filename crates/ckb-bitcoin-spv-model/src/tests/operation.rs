@@ -0,0 +1,29 @@
+use ckb_bitcoin_spv_verifier::types::core::BitcoinChainType;
+
+use crate::OperationKind;
+
+#[test]
+fn classify() {
+    let testnet_flags = (0..4u8)
+        .map(|bits| bits << 6)
+        .find(|flags| BitcoinChainType::Testnet == (*flags).into())
+        .unwrap();
+    let mainnet_flags = 0;
+
+    let cases = [
+        (0, 6, mainnet_flags, OperationKind::Create),
+        (6, 0, mainnet_flags, OperationKind::Destroy),
+        (2, 2, mainnet_flags, OperationKind::Update),
+        (3, 3, mainnet_flags, OperationKind::Reorg),
+        (5, 5, mainnet_flags, OperationKind::Reorg),
+        (6, 6, mainnet_flags, OperationKind::Unknown),
+        (6, 6, testnet_flags, OperationKind::Reset),
+        (1, 1, mainnet_flags, OperationKind::Unknown),
+        (2, 3, mainnet_flags, OperationKind::Unknown),
+        (7, 7, testnet_flags, OperationKind::Unknown),
+    ];
+    for (inputs_count, outputs_count, flags, expected) in cases {
+        let actual = OperationKind::classify(inputs_count, outputs_count, 5, flags);
+        assert_eq!(actual, expected, "{inputs_count} -> {outputs_count}");
+    }
+}
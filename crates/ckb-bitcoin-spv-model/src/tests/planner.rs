@@ -0,0 +1,104 @@
+use crate::{
+    error::Error,
+    planner::{plan, CandidateChain, ClientTip, Plan},
+    Operation, SpvState,
+};
+
+const CLIENTS_COUNT: u8 = 5;
+const TIP_CLIENT_ID: u8 = 2;
+const TIP_HEIGHT: u32 = 100;
+
+fn block_hash(height: u32, fork: u8) -> [u8; 32] {
+    let mut hash = [fork; 32];
+    hash[..4].copy_from_slice(&height.to_le_bytes());
+    hash
+}
+
+// Clients 2, 1, 0, 4, 3 are at heights 100, 99, 98, 97, 96.
+fn clients() -> Vec<ClientTip> {
+    (0..CLIENTS_COUNT)
+        .map(|id| {
+            let distance = (TIP_CLIENT_ID + CLIENTS_COUNT - id) % CLIENTS_COUNT;
+            let height = TIP_HEIGHT - u32::from(distance);
+            ClientTip {
+                id,
+                height,
+                block_hash: block_hash(height, 0),
+            }
+        })
+        .collect()
+}
+
+// The candidate chain forks after `fork_height`.
+fn candidate(fork_height: u32, tip_height: u32) -> CandidateChain {
+    let start_height = 90;
+    let block_hashes = (start_height..=tip_height)
+        .map(|height| block_hash(height, u8::from(height > fork_height)))
+        .collect();
+    CandidateChain {
+        start_height,
+        block_hashes,
+    }
+}
+
+fn state() -> SpvState {
+    SpvState::new(CLIENTS_COUNT, 0, TIP_CLIENT_ID).unwrap()
+}
+
+fn check_apply(actual: Plan, base_client_id: u8, start_height: u32, operation: Operation) {
+    match actual {
+        Plan::Apply {
+            base_client_id: actual_base_client_id,
+            headers,
+            operation: actual_operation,
+            transition,
+        } => {
+            assert_eq!(actual_base_client_id, base_client_id);
+            assert_eq!(*headers.start(), start_height);
+            assert_eq!(actual_operation, operation);
+            assert_eq!(transition, state().apply(operation).unwrap());
+        }
+        _ => panic!("should be applied, but got {actual:?}"),
+    }
+}
+
+#[test]
+fn update() {
+    let actual = plan(&state(), &clients(), &candidate(u32::MAX, 105)).unwrap();
+    check_apply(actual, TIP_CLIENT_ID, TIP_HEIGHT + 1, Operation::Update);
+
+    let actual = plan(&state(), &clients(), &candidate(u32::MAX, TIP_HEIGHT)).unwrap();
+    assert_eq!(actual, Plan::UpToDate);
+}
+
+#[test]
+fn reorg() {
+    // Clients 1 and 2 are stale.
+    let actual = plan(&state(), &clients(), &candidate(98, 110)).unwrap();
+    let operation = Operation::Reorg { fork_client_id: 0 };
+    check_apply(actual, 0, 99, operation);
+
+    // Only client 2 is stale, but client 1 has to be replaced too.
+    let actual = plan(&state(), &clients(), &candidate(99, 105)).unwrap();
+    check_apply(actual, 0, 99, operation);
+
+    // The fork client is found, but the candidate chain has no new headers.
+    let result = plan(&state(), &clients(), &candidate(u32::MAX, 98));
+    assert_eq!(result.err(), Some(Error::CandidateChainTooShort));
+}
+
+#[test]
+fn recovery_required() {
+    let actual = plan(&state(), &clients(), &candidate(0, 110)).unwrap();
+    assert_eq!(actual, Plan::RecoveryRequired);
+
+    let empty = CandidateChain {
+        start_height: 90,
+        block_hashes: Vec::new(),
+    };
+    let result = plan(&state(), &clients(), &empty);
+    assert_eq!(result.err(), Some(Error::CandidateChainTooShort));
+
+    let result = plan(&state(), &clients()[1..], &candidate(98, 110));
+    assert_eq!(result.err(), Some(Error::ClientNotFound(0)));
+}
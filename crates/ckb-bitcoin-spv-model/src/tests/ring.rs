@@ -0,0 +1,27 @@
+use crate::ring::{client_ids_between, next_client_id, prev_client_id};
+
+#[test]
+fn walk_the_ring() {
+    let count = 5;
+    for id in 0..count {
+        let next = next_client_id(id, count);
+        assert!(next < count);
+        assert_eq!(prev_client_id(next, count), id);
+    }
+    assert_eq!(next_client_id(4, count), 0);
+    assert_eq!(prev_client_id(0, count), 4);
+}
+
+#[test]
+fn ids_between() {
+    assert_eq!(client_ids_between(1, 3, 5), vec![2, 3]);
+    assert_eq!(client_ids_between(3, 1, 5), vec![4, 0, 1]);
+    assert_eq!(client_ids_between(4, 0, 5), vec![0]);
+    assert!(client_ids_between(2, 2, 5).is_empty());
+}
+
+#[test]
+#[should_panic]
+fn ids_between_out_of_range() {
+    client_ids_between(1, 5, 5);
+}
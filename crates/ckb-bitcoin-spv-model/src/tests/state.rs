@@ -0,0 +1,124 @@
+use ckb_bitcoin_spv_verifier::types::core::BitcoinChainType;
+
+use crate::{error::Error, Operation, OperationKind, SpvCell, SpvState};
+
+const CLIENTS_COUNT: u8 = 5;
+
+fn testnet_flags() -> u8 {
+    (0..4u8)
+        .map(|bits| bits << 6)
+        .find(|flags| BitcoinChainType::Testnet == (*flags).into())
+        .unwrap()
+}
+
+#[test]
+fn create() {
+    let transition = SpvState::create(CLIENTS_COUNT, 0).unwrap();
+    assert!(transition.inputs.is_empty());
+    assert_eq!(transition.outputs.len(), usize::from(CLIENTS_COUNT) + 1);
+    assert_eq!(transition.outputs[0], SpvCell::Info);
+    assert_eq!(transition.outputs[1], SpvCell::Client(0));
+    assert_eq!(transition.next.unwrap().tip_client_id(), 0);
+
+    let result = SpvState::create(2, 0);
+    assert_eq!(result.err(), Some(Error::ClientsCountTooSmall(2)));
+}
+
+#[test]
+fn update() {
+    for tip_client_id in 0..CLIENTS_COUNT {
+        let state = SpvState::new(CLIENTS_COUNT, 0, tip_client_id).unwrap();
+        let oldest_client_id = state.oldest_client_id();
+        let transition = state.apply(Operation::Update).unwrap();
+        let expected = vec![SpvCell::Info, SpvCell::Client(oldest_client_id)];
+        assert_eq!(transition.inputs, expected);
+        assert_eq!(transition.outputs, expected);
+        assert_eq!(transition.cell_deps, vec![SpvCell::Client(tip_client_id)]);
+        assert_eq!(transition.next.unwrap().tip_client_id(), oldest_client_id);
+    }
+    assert_eq!(
+        SpvState::new(CLIENTS_COUNT, 0, 4)
+            .unwrap()
+            .oldest_client_id(),
+        0
+    );
+}
+
+#[test]
+fn reorg() {
+    let state = SpvState::new(CLIENTS_COUNT, 0, 1).unwrap();
+
+    // Clients 4, 0 and 1 are replaced.
+    let transition = state.apply(Operation::Reorg { fork_client_id: 3 }).unwrap();
+    let expected = vec![
+        SpvCell::Info,
+        SpvCell::Client(4),
+        SpvCell::Client(0),
+        SpvCell::Client(1),
+    ];
+    assert_eq!(transition.inputs, expected);
+    assert_eq!(transition.outputs, expected);
+    assert_eq!(transition.cell_deps, vec![SpvCell::Client(3)]);
+    assert_eq!(transition.next.unwrap().tip_client_id(), 4);
+
+    // Only one client is replaced, it's an update.
+    for fork_client_id in [0, 1] {
+        let result = state.apply(Operation::Reorg { fork_client_id });
+        assert_eq!(result.err(), Some(Error::ReorgClientsNotEnough));
+    }
+    let result = state.apply(Operation::Reorg {
+        fork_client_id: CLIENTS_COUNT,
+    });
+    assert_eq!(result.err(), Some(Error::ClientIdOutOfRange(CLIENTS_COUNT)));
+}
+
+#[test]
+fn reset_and_destroy() {
+    let state = SpvState::new(CLIENTS_COUNT, 0, 3).unwrap();
+    let result = state.apply(Operation::Reset);
+    assert_eq!(result.err(), Some(Error::ResetNotAllowed));
+
+    let state = SpvState::new(CLIENTS_COUNT, testnet_flags(), 3).unwrap();
+    let transition = state.apply(Operation::Reset).unwrap();
+    assert_eq!(transition.inputs.len(), usize::from(CLIENTS_COUNT) + 1);
+    assert_eq!(transition.outputs, transition.inputs);
+    assert_eq!(transition.next.unwrap().tip_client_id(), 0);
+
+    let transition = state.apply(Operation::Destroy).unwrap();
+    assert_eq!(transition.inputs.len(), usize::from(CLIENTS_COUNT) + 1);
+    assert!(transition.outputs.is_empty());
+    assert!(transition.next.is_none());
+}
+
+// All transitions should be recognized as the same operations by the SPV
+// type script.
+#[test]
+fn transitions_are_classified() {
+    for flags in [0, testnet_flags()] {
+        let transition = SpvState::create(CLIENTS_COUNT, flags).unwrap();
+        let mut transitions = vec![transition];
+        for tip_client_id in 0..CLIENTS_COUNT {
+            let state = SpvState::new(CLIENTS_COUNT, flags, tip_client_id).unwrap();
+            let operations = [Operation::Update, Operation::Reset, Operation::Destroy]
+                .into_iter()
+                .chain(
+                    (0..CLIENTS_COUNT).map(|fork_client_id| Operation::Reorg { fork_client_id }),
+                );
+            for operation in operations {
+                if let Ok(transition) = state.apply(operation) {
+                    assert_eq!(transition.kind, OperationKind::from(operation));
+                    transitions.push(transition);
+                }
+            }
+        }
+        for transition in transitions {
+            let kind = OperationKind::classify(
+                transition.inputs.len(),
+                transition.outputs.len(),
+                CLIENTS_COUNT,
+                flags,
+            );
+            assert_eq!(kind, transition.kind);
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! A service to build proofs of Bitcoin transactions, which could be verified
+//! by a Bitcoin SPV instance on CKB.
+//!
+//! The headers are loaded from the store of the relayer, so the relayer
+//! should be run with `--store`, and the proofs are built against the tip
+//! client of the SPV instance.
+//!
+//! It serves a local HTTP/JSON API, the request is a txid, or a raw
+//! transaction and the raw block which contains it, in hex:
+//!
+//! ```text
+//! POST /proof
+//! {"txid": "<txid>"}
+//! {"tx": "<raw transaction>", "block": "<raw block>"}
+//! ```
+//!
+//! The response contains the packed `TransactionProof`, the SPV client to
+//! use as the cell dep, and the max confirmations which could be verified.
+
+use std::{
+    fs,
+    net::{SocketAddr, TcpListener, TcpStream},
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+
+use bitcoin::{consensus::deserialize, Block, Transaction, Txid};
+use ckb_bitcoin_spv_relayer::{
+    chain::{CkbChain, RpcChain},
+    cli::SpvTypeScriptArgs,
+    error::Error,
+    proof::{TxProof, TxProver},
+    source::{BlockSource, RpcHeaderSource},
+    store::{Store, StoreState},
+};
+use ckb_bitcoin_spv_tx_builder::SpvInstance;
+use ckb_bitcoin_spv_verifier::types::prelude::Entity as _;
+use ckb_jsonrpc_types as json;
+use ckb_types::packed::Script;
+use clap::Parser;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+mod http;
+
+// The timeout to read a request or to write a response, so a stalled client
+// doesn't block the service.
+const STREAM_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// The address to listen on.
+    #[arg(long, default_value = "127.0.0.1:8118")]
+    listen: SocketAddr,
+    /// The file of the store of the relayer.
+    #[arg(long)]
+    store: PathBuf,
+    /// The URL of the JSON-RPC of a CKB node, which enables the indexer.
+    #[arg(long)]
+    ckb_rpc: String,
+    /// The URL of the JSON-RPC of a Bitcoin node, which enables `txindex`.
+    ///
+    /// Without it, only raw transactions and blocks are accepted.
+    #[arg(long)]
+    bitcoin_rpc: Option<String>,
+    #[command(flatten)]
+    spv_type_script: SpvTypeScriptArgs,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ProofRequest {
+    Txid { txid: String },
+    Raw { tx: String, block: String },
+}
+
+struct Service {
+    store: StoreCache,
+    chain: RpcChain,
+    blocks: Option<RpcHeaderSource>,
+    type_script: Script,
+    prover: TxProver,
+}
+
+fn main() {
+    env_logger::init();
+    let cli = Cli::parse();
+
+    let mut service = Service {
+        store: StoreCache::new(cli.store),
+        chain: RpcChain::new(&cli.ckb_rpc),
+        blocks: cli.bitcoin_rpc.as_deref().map(RpcHeaderSource::new),
+        type_script: cli.spv_type_script.script(),
+        prover: TxProver::default(),
+    };
+    let listener = TcpListener::bind(cli.listen).expect("failed to listen");
+    log::info!("listen on {}", cli.listen);
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(err) = stream
+                    .set_read_timeout(Some(STREAM_TIMEOUT))
+                    .and_then(|()| stream.set_write_timeout(Some(STREAM_TIMEOUT)))
+                {
+                    log::warn!("failed to set timeouts: {err}");
+                    continue;
+                }
+                service.handle(&stream);
+            }
+            Err(err) => log::warn!("failed to accept: {err}"),
+        }
+    }
+}
+
+impl Service {
+    fn handle(&mut self, stream: &TcpStream) {
+        let (status, body) = match http::read_request(stream) {
+            Ok(request) => match (request.method.as_str(), request.path.as_str()) {
+                ("POST", "/proof") => self.prove(&request.body),
+                _ => (404, json!({ "error": "not found" })),
+            },
+            Err(err) => (400, json!({ "error": err.to_string() })),
+        };
+        if let Err(err) = http::write_response(stream, status, &body) {
+            log::warn!("failed to respond: {err}");
+        }
+    }
+
+    fn prove(&mut self, body: &[u8]) -> (u16, Value) {
+        let bad_request = |msg: String| (400, json!({ "error": msg }));
+        let request: ProofRequest = match serde_json::from_slice(body) {
+            Ok(request) => request,
+            Err(err) => return bad_request(err.to_string()),
+        };
+        let (txid, block) = match request {
+            ProofRequest::Txid { txid } => {
+                let txid = match Txid::from_str(&txid) {
+                    Ok(txid) => txid,
+                    Err(err) => return bad_request(format!("txid: {err}")),
+                };
+                let Some(ref blocks) = self.blocks else {
+                    return bad_request("no Bitcoin node, send the raw block".to_owned());
+                };
+                match blocks.block_of_transaction(&txid) {
+                    Ok(block) => (txid, block),
+                    Err(err) => return error_response(&err),
+                }
+            }
+            ProofRequest::Raw { tx, block } => {
+                let tx: Transaction = match decode_hex(&tx) {
+                    Ok(tx) => tx,
+                    Err(msg) => return bad_request(format!("tx: {msg}")),
+                };
+                let block: Block = match decode_hex(&block) {
+                    Ok(block) => block,
+                    Err(msg) => return bad_request(format!("block: {msg}")),
+                };
+                (tx.txid(), block)
+            }
+        };
+
+        let result = self.store.state().and_then(|state| {
+            let cells = self.chain.cells_by_type(&self.type_script)?;
+            let instance = SpvInstance::load(cells)?;
+            self.prover.prove(&state.headers, &instance, &block, txid)
+        });
+        match result {
+            Ok(proof) => (200, proof_to_json(&proof)),
+            Err(err) => error_response(&err),
+        }
+    }
+}
+
+/// The state of the store of the relayer, which is reloaded only when the
+/// journal is changed: the relayer appends records to it, and replaces it
+/// with a compacted one when it restarts.
+struct StoreCache {
+    path: PathBuf,
+    // The length and the modified time of the loaded journal.
+    version: Option<(u64, SystemTime)>,
+    state: StoreState,
+}
+
+impl StoreCache {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            version: None,
+            state: StoreState::default(),
+        }
+    }
+
+    fn state(&mut self) -> Result<&StoreState, Error> {
+        let version = fs::metadata(&self.path)
+            .and_then(|metadata| Ok((metadata.len(), metadata.modified()?)))
+            .ok();
+        if version.is_none() || version != self.version {
+            self.state = Store::load(&self.path)?;
+            self.version = version;
+        }
+        Ok(&self.state)
+    }
+}
+
+fn decode_hex<T: bitcoin::consensus::Decodable>(input: &str) -> Result<T, String> {
+    let bytes = hex::decode(input.trim_start_matches("0x")).map_err(|err| err.to_string())?;
+    deserialize(&bytes).map_err(|err| err.to_string())
+}
+
+fn proof_to_json(proof: &TxProof) -> Value {
+    json!({
+        "txid": proof.txid.to_string(),
+        "block_hash": proof.block_hash.to_string(),
+        "height": proof.height,
+        "tx_index": proof.tx_index,
+        "tx_proof": format!("0x{}", hex::encode(proof.tx_proof.as_slice())),
+        "client_id": proof.client_id,
+        "client_out_point": json::OutPoint::from(proof.client_out_point.clone()),
+        "client_tip_height": proof.client_tip_height,
+        "confirmations": proof.confirmations,
+    })
+}
+
+fn error_response(err: &Error) -> (u16, Value) {
+    let status = match err {
+        Error::Unprovable(_) => 422,
+        Error::Rpc(_) => 502,
+        _ => 500,
+    };
+    (status, json!({ "error": err.to_string() }))
+}
@@ -0,0 +1,24 @@
+//! Access to the CKB chain.
+
+use ckb_bitcoin_spv_tx_builder::LiveCell;
+use ckb_types::{
+    core::TransactionView,
+    packed::{Byte32, Script},
+};
+
+use crate::error::Result;
+
+mod rpc;
+
+pub use rpc::RpcChain;
+
+/// The CKB chain, to load live cells and to submit transactions.
+pub trait CkbChain {
+    /// Returns the live cells whose type script is `type_script`.
+    fn cells_by_type(&self, type_script: &Script) -> Result<Vec<LiveCell>>;
+    /// Returns the live cells whose lock script is `lock_script`, and which
+    /// don't have any type script.
+    fn cells_by_lock(&self, lock_script: &Script) -> Result<Vec<LiveCell>>;
+    /// Submits a transaction, then returns its hash.
+    fn send_transaction(&self, tx: &TransactionView) -> Result<Byte32>;
+}
@@ -0,0 +1,91 @@
+use ckb_bitcoin_spv_tx_builder::LiveCell;
+use ckb_jsonrpc_types as json;
+use ckb_types::{
+    core::TransactionView,
+    packed::{Byte32, Script},
+    prelude::*,
+    H256,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::CkbChain;
+use crate::{error::Result, rpc::RpcClient};
+
+const PAGE_SIZE: u32 = 1000;
+
+#[derive(Deserialize)]
+struct Cells {
+    objects: Vec<Cell>,
+    last_cursor: json::JsonBytes,
+}
+
+#[derive(Deserialize)]
+struct Cell {
+    output: json::CellOutput,
+    output_data: json::JsonBytes,
+    out_point: json::OutPoint,
+}
+
+/// A CKB node, which enables the indexer, through its JSON-RPC.
+pub struct RpcChain {
+    client: RpcClient,
+}
+
+impl RpcChain {
+    pub fn new(url: &str) -> Self {
+        Self {
+            client: RpcClient::new(url),
+        }
+    }
+
+    fn get_cells(&self, search_key: Value) -> Result<Vec<LiveCell>> {
+        let mut live_cells = Vec::new();
+        let mut cursor = Value::Null;
+        loop {
+            let limit = json::Uint32::from(PAGE_SIZE);
+            let params = json!([search_key, "asc", limit, cursor]);
+            let cells: Cells = self.client.call("get_cells", params)?;
+            let count = cells.objects.len();
+            live_cells.extend(cells.objects.into_iter().map(|cell| LiveCell {
+                out_point: cell.out_point.into(),
+                output: cell.output.into(),
+                data: cell.output_data.into_bytes(),
+            }));
+            if count < PAGE_SIZE as usize {
+                break;
+            }
+            cursor = json!(cells.last_cursor);
+        }
+        Ok(live_cells)
+    }
+}
+
+impl CkbChain for RpcChain {
+    fn cells_by_type(&self, type_script: &Script) -> Result<Vec<LiveCell>> {
+        let search_key = json!({
+            "script": json::Script::from(type_script.clone()),
+            "script_type": "type",
+            "script_search_mode": "exact",
+        });
+        self.get_cells(search_key)
+    }
+
+    fn cells_by_lock(&self, lock_script: &Script) -> Result<Vec<LiveCell>> {
+        let search_key = json!({
+            "script": json::Script::from(lock_script.clone()),
+            "script_type": "lock",
+            "script_search_mode": "exact",
+            "filter": { "script_len_range": ["0x0", "0x1"] },
+        });
+        self.get_cells(search_key)
+    }
+
+    fn send_transaction(&self, tx: &TransactionView) -> Result<Byte32> {
+        let tx = json::Transaction::from(tx.data());
+        let tx_hash: H256 = self
+            .client
+            .call("send_transaction", json!([tx, "passthrough"]))?;
+        Ok(tx_hash.pack())
+    }
+}
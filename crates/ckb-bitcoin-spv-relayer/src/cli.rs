@@ -0,0 +1,57 @@
+//! Command line arguments which are shared by binaries.
+
+use std::str::FromStr;
+
+use ckb_types::{bytes::Bytes, core::ScriptHashType, packed::Script, prelude::*, H256};
+use clap::{Args, ValueEnum};
+
+/// The SPV type script of the SPV instance.
+#[derive(Args)]
+pub struct SpvTypeScriptArgs {
+    /// The code hash of the SPV type script.
+    #[arg(long, value_parser = parse_h256)]
+    spv_type_code_hash: H256,
+    /// The hash type of the SPV type script.
+    #[arg(long, value_enum, default_value = "type")]
+    spv_type_hash_type: HashType,
+    /// The args of the SPV type script, as a hex string.
+    #[arg(long, value_parser = parse_hex)]
+    spv_type_args: Bytes,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum HashType {
+    Type,
+    Data1,
+    Data2,
+}
+
+impl SpvTypeScriptArgs {
+    pub fn script(&self) -> Script {
+        Script::new_builder()
+            .code_hash(self.spv_type_code_hash.pack())
+            .hash_type(ScriptHashType::from(self.spv_type_hash_type).into())
+            .args(self.spv_type_args.pack())
+            .build()
+    }
+}
+
+impl From<HashType> for ScriptHashType {
+    fn from(hash_type: HashType) -> Self {
+        match hash_type {
+            HashType::Type => Self::Type,
+            HashType::Data1 => Self::Data1,
+            HashType::Data2 => Self::Data2,
+        }
+    }
+}
+
+fn parse_h256(input: &str) -> Result<H256, String> {
+    H256::from_str(input.trim_start_matches("0x")).map_err(|err| err.to_string())
+}
+
+fn parse_hex(input: &str) -> Result<Bytes, String> {
+    hex::decode(input.trim_start_matches("0x"))
+        .map(Bytes::from)
+        .map_err(|err| err.to_string())
+}
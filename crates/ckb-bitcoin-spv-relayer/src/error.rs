@@ -0,0 +1,74 @@
+use std::{fmt, result};
+
+use ckb_bitcoin_spv_model::error::Error as ModelError;
+use ckb_bitcoin_spv_tx_builder::error::Error as BuildError;
+
+pub type Result<T> = result::Result<T, Error>;
+
+/// Errors of the relayer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Failed to request a JSON-RPC server, or the server returns an error.
+    Rpc(String),
+    /// Failed to read a file.
+    Io(String),
+    /// The header at the height is not found in the header source.
+    HeaderNotFound(u32),
+    /// Failed to decode data from a header source or CKB.
+    Decode(String),
+    /// Failed to prove new headers.
+    Prover(String),
+    /// The client of the prover is different from the client on chain.
+    ProverMismatch(u8),
+    /// No client is in the chain of the header source, the SPV instance has
+    /// to be recovered.
+    RecoveryRequired,
+    /// The transaction fee couldn't be paid.
+    FeeNotPaid(String),
+    /// The SPV info cell is consumed by a transaction of another relayer.
+    Conflict,
+    /// The local store is corrupted.
+    Store(String),
+    /// The transaction couldn't be proven by the SPV instance.
+    Unprovable(String),
+    Model(ModelError),
+    Build(BuildError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rpc(msg) => write!(f, "rpc error: {msg}"),
+            Self::Io(msg) => write!(f, "io error: {msg}"),
+            Self::HeaderNotFound(height) => write!(f, "the header at {height} is not found"),
+            Self::Decode(msg) => write!(f, "failed to decode: {msg}"),
+            Self::Prover(msg) => write!(f, "prover error: {msg}"),
+            Self::ProverMismatch(id) => {
+                write!(f, "the prover is mismatched with the SPV client (id={id})")
+            }
+            Self::RecoveryRequired => {
+                write!(f, "no SPV client is in the chain, recovery is required")
+            }
+            Self::FeeNotPaid(msg) => write!(f, "the fee is not paid: {msg}"),
+            Self::Conflict => write!(f, "the SPV info cell is consumed by another transaction"),
+            Self::Store(msg) => write!(f, "store error: {msg}"),
+            Self::Unprovable(msg) => write!(f, "the transaction couldn't be proven: {msg}"),
+            Self::Model(err) => write!(f, "{err}"),
+            Self::Build(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ModelError> for Error {
+    fn from(err: ModelError) -> Self {
+        Self::Model(err)
+    }
+}
+
+impl From<BuildError> for Error {
+    fn from(err: BuildError) -> Self {
+        Self::Build(err)
+    }
+}
@@ -0,0 +1,229 @@
+//! Pay the fee of transactions.
+
+use ckb_bitcoin_spv_model::Operation;
+use ckb_bitcoin_spv_tx_builder::LiveCell;
+use ckb_crypto::secp::Privkey;
+use ckb_hash::{blake2b_256, new_blake2b};
+use ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, ScriptHashType, TransactionView},
+    h256,
+    packed::{BytesOpt, CellDep, Script, WitnessArgs},
+    prelude::*,
+    H256,
+};
+
+use crate::{
+    chain::CkbChain,
+    error::{Error, Result},
+};
+
+// The offset of the max reward in the args of the permissionless update
+// lock: the type hash of the SPV instance, and the hash of the owner's public
+// key.
+const MAX_REWARD_OFFSET: usize = 32 + 20;
+const MAX_REWARD_SIZE: usize = 8;
+
+// The type hash of `secp256k1_blake160_sighash_all`, the default lock script
+// of CKB, it's same on the mainnet and the testnet.
+const SIGHASH_ALL_TYPE_HASH: H256 =
+    h256!("0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8");
+const SIGNATURE_SIZE: usize = 65;
+
+/// Pays the fee of a transaction, which is built by the transaction builder.
+pub trait FeePayer {
+    fn pay(
+        &self,
+        chain: &dyn CkbChain,
+        tx: TransactionView,
+        operation: Operation,
+    ) -> Result<TransactionView>;
+}
+
+/// Takes the fee from the reward pool of the permissionless update lock.
+///
+/// The SPV cells should be locked by the permissionless update lock with
+/// the reward mode. The lock only rewards updates, so reorgs are not paid.
+///
+/// The reward pool cells have no type script, and their data is the type
+/// hash of the SPV instance.
+pub struct RewardPoolPayer {
+    pub fee: u64,
+}
+
+impl FeePayer for RewardPoolPayer {
+    fn pay(
+        &self,
+        chain: &dyn CkbChain,
+        tx: TransactionView,
+        operation: Operation,
+    ) -> Result<TransactionView> {
+        if operation != Operation::Update {
+            return Err(Error::FeeNotPaid("only updates are rewarded".to_owned()));
+        }
+        // The SPV info cell is always the first output.
+        let info_cell = tx
+            .outputs()
+            .get(0)
+            .ok_or_else(|| Error::FeeNotPaid("no SPV cells".to_owned()))?;
+        let lock_script = info_cell.lock();
+        let spv_type_hash = info_cell
+            .type_()
+            .to_opt()
+            .ok_or_else(|| Error::FeeNotPaid("no SPV type script".to_owned()))?
+            .calc_script_hash();
+        let args = lock_script.args().raw_data();
+        let max_reward = args
+            .get(MAX_REWARD_OFFSET..MAX_REWARD_OFFSET + MAX_REWARD_SIZE)
+            .map(|bytes| {
+                let mut buf = [0u8; MAX_REWARD_SIZE];
+                buf.copy_from_slice(bytes);
+                u64::from_le_bytes(buf)
+            })
+            .ok_or_else(|| Error::FeeNotPaid("the reward mode is disabled".to_owned()))?;
+        if self.fee > max_reward {
+            let msg = format!(
+                "the fee {} is greater than the max reward {max_reward}",
+                self.fee
+            );
+            return Err(Error::FeeNotPaid(msg));
+        }
+
+        let pool_cell = chain
+            .cells_by_lock(&lock_script)?
+            .into_iter()
+            .filter(|cell| {
+                cell.output.type_().is_none() && cell.data.as_ref() == spv_type_hash.as_slice()
+            })
+            .find(|cell| could_pay(cell, self.fee))
+            .ok_or_else(|| Error::FeeNotPaid("the reward pool is empty".to_owned()))?;
+        let capacity: u64 = pool_cell.output.capacity().unpack();
+        let output = pool_cell
+            .output
+            .clone()
+            .as_builder()
+            .capacity((capacity - self.fee).pack())
+            .build();
+        let tx = tx
+            .as_advanced_builder()
+            .input(pool_cell.as_input())
+            .output(output)
+            .output_data(pool_cell.data.pack())
+            .build();
+        Ok(tx)
+    }
+}
+
+/// Takes the fee from the cells of the relayer, which are locked by the
+/// default lock script of CKB (`secp256k1_blake160_sighash_all`), so all
+/// operations, includes reorgs, could be paid.
+pub struct Secp256k1Payer {
+    pub fee: u64,
+    pub private_key: Privkey,
+    /// The dep group of the default lock script.
+    pub cell_dep: CellDep,
+}
+
+impl Secp256k1Payer {
+    /// The lock script of the cells which pay the fee.
+    pub fn lock_script(&self) -> Result<Script> {
+        let pubkey = self
+            .private_key
+            .pubkey()
+            .map_err(|err| Error::FeeNotPaid(format!("bad private key: {err}")))?;
+        let pubkey_hash = &blake2b_256(pubkey.serialize())[..20];
+        let script = Script::new_builder()
+            .code_hash(SIGHASH_ALL_TYPE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(Bytes::copy_from_slice(pubkey_hash).pack())
+            .build();
+        Ok(script)
+    }
+}
+
+impl FeePayer for Secp256k1Payer {
+    fn pay(
+        &self,
+        chain: &dyn CkbChain,
+        tx: TransactionView,
+        _operation: Operation,
+    ) -> Result<TransactionView> {
+        let lock_script = self.lock_script()?;
+        let fee_cell = chain
+            .cells_by_lock(&lock_script)?
+            .into_iter()
+            .find(|cell| could_pay(cell, self.fee))
+            .ok_or_else(|| Error::FeeNotPaid("no cells to pay the fee".to_owned()))?;
+        let capacity: u64 = fee_cell.output.capacity().unpack();
+        let output = fee_cell
+            .output
+            .clone()
+            .as_builder()
+            .capacity((capacity - self.fee).pack())
+            .build();
+
+        // The witness of the fee cell is at the same index as its input.
+        let input_index = tx.inputs().len();
+        let mut witnesses = tx.witnesses().into_iter().collect::<Vec<_>>();
+        if witnesses.len() > input_index {
+            return Err(Error::FeeNotPaid(
+                "the witness of the fee cell is used".to_owned(),
+            ));
+        }
+        witnesses.resize(input_index, Bytes::new().pack());
+        witnesses.push(witness_with_lock(&[0u8; SIGNATURE_SIZE]).pack());
+        let tx = tx
+            .as_advanced_builder()
+            .cell_dep(self.cell_dep.clone())
+            .input(fee_cell.as_input())
+            .output(output)
+            .output_data(fee_cell.data.pack())
+            .set_witnesses(witnesses)
+            .build();
+
+        let message = sighash_all_message(&tx, input_index);
+        let signature = self
+            .private_key
+            .sign_recoverable(&message)
+            .map_err(|err| Error::FeeNotPaid(format!("failed to sign: {err}")))?
+            .serialize();
+        let mut witnesses = tx.witnesses().into_iter().collect::<Vec<_>>();
+        witnesses[input_index] = witness_with_lock(&signature).pack();
+        Ok(tx.as_advanced_builder().set_witnesses(witnesses).build())
+    }
+}
+
+// Checks if the cell could pay the fee, and still has its occupied capacity.
+fn could_pay(cell: &LiveCell, fee: u64) -> bool {
+    let capacity: u64 = cell.output.capacity().unpack();
+    let occupied = Capacity::bytes(cell.data.len())
+        .and_then(|data_capacity| cell.output.occupied_capacity(data_capacity))
+        .map(|capacity| capacity.as_u64())
+        .unwrap_or(u64::MAX);
+    capacity >= occupied.saturating_add(fee)
+}
+
+// Same as `secp256k1_blake160_sighash_all`, the fee cell is the only input
+// in its script group, and the lock field of its witness is zeroed.
+pub(crate) fn sighash_all_message(tx: &TransactionView, input_index: usize) -> H256 {
+    let witnesses = tx.witnesses();
+    let mut hasher = new_blake2b();
+    hasher.update(tx.hash().as_slice());
+    for witness in Some(input_index)
+        .into_iter()
+        .chain(tx.inputs().len()..witnesses.len())
+        .filter_map(|index| witnesses.get(index))
+    {
+        let witness = witness.raw_data();
+        hasher.update(&(witness.len() as u64).to_le_bytes());
+        hasher.update(&witness);
+    }
+    let mut message = [0u8; 32];
+    hasher.finalize(&mut message);
+    H256(message)
+}
+
+fn witness_with_lock(lock: &[u8]) -> Bytes {
+    let lock = BytesOpt::new_builder().set(Some(lock.pack())).build();
+    WitnessArgs::new_builder().lock(lock).build().as_bytes()
+}
@@ -0,0 +1,30 @@
+//! A relayer to synchronize Bitcoin headers into Bitcoin SPV instances on
+//! CKB.
+//!
+//! The relayer loops: fetches new headers from a header source, plans an
+//! update or a reorg, proves the new headers, builds the transaction, then
+//! submits it to CKB.
+//!
+//! Header sources, CKB and the fee payer are behind traits, so they could be
+//! replaced, e.g. by mock servers in tests.
+//!
+//! With a store, the proven headers and the pending transaction are kept
+//! across restarts, and reconciled against the cells on chain.
+//!
+//! The same store backs the proof service, which builds proofs of Bitcoin
+//! transactions for users of the SPV instance.
+
+pub mod chain;
+pub mod cli;
+pub mod error;
+pub mod fee;
+pub mod proof;
+mod relayer;
+pub(crate) mod rpc;
+pub mod source;
+pub mod store;
+
+#[cfg(test)]
+mod tests;
+
+pub use relayer::{Relayed, Relayer, RelayerConfig};
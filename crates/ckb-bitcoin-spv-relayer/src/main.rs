@@ -0,0 +1,265 @@
+//! A relayer to synchronize Bitcoin headers into a Bitcoin SPV instance on
+//! CKB.
+//!
+//! The transaction fee is taken from the reward pool of the permissionless
+//! update lock, which locks the SPV cells, or from the cells of the relayer,
+//! which are locked by the default lock script of CKB. The reward pool only
+//! pays for updates, so reorgs require the latter.
+
+use std::{fs, path::PathBuf, str::FromStr, time::Duration};
+
+use ckb_bitcoin_spv_relayer::{
+    chain::RpcChain,
+    cli::SpvTypeScriptArgs,
+    fee::{FeePayer, RewardPoolPayer, Secp256k1Payer},
+    source::{FileHeaderSource, HeaderSource, RpcHeaderSource},
+    store::Store,
+    Relayer, RelayerConfig,
+};
+use ckb_crypto::secp::Privkey;
+use ckb_types::{
+    core::DepType,
+    packed::{CellDep, OutPoint},
+    prelude::*,
+    H256,
+};
+use clap::{error::ErrorKind, ArgGroup, CommandFactory as _, Parser, ValueEnum};
+
+#[derive(Parser)]
+#[command(version, about)]
+#[command(group(ArgGroup::new("source").required(true).args(["bitcoin_rpc", "headers_dir"])))]
+struct Cli {
+    /// The URL of the JSON-RPC of a Bitcoin node.
+    #[arg(long)]
+    bitcoin_rpc: Option<String>,
+    /// A directory of headers, each header is in a file named by its height,
+    /// e.g. `0822528.bin`.
+    #[arg(long)]
+    headers_dir: Option<PathBuf>,
+    /// The URL of the JSON-RPC of a CKB node, which enables the indexer.
+    #[arg(long)]
+    ckb_rpc: String,
+    #[command(flatten)]
+    spv_type_script: SpvTypeScriptArgs,
+    /// The fee for each transaction, in shannons.
+    #[arg(long, default_value_t = 100_000)]
+    fee: u64,
+    /// Where the fee is taken from.
+    #[arg(long, value_enum, default_value = "reward-pool")]
+    fee_payer: FeePayerKind,
+    /// A file which contains the private key of the relayer, as a hex
+    /// string, for the fee payer `secp256k1`.
+    #[arg(long, required_if_eq("fee_payer", "secp256k1"))]
+    private_key_file: Option<PathBuf>,
+    /// The out point of the dep group of the default lock script, as
+    /// `<tx hash>:<index>`, for the fee payer `secp256k1`.
+    #[arg(long, value_parser = parse_out_point, required_if_eq("fee_payer", "secp256k1"))]
+    secp256k1_dep_group: Option<OutPoint>,
+    /// The max count of headers in one transaction; but a reorg always
+    /// contains the headers up to the height next to the old tip.
+    #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(u32).range(1..))]
+    max_headers: u32,
+    /// How many times to rebuild the transaction in one round, when the SPV
+    /// instance is updated by other relayers.
+    #[arg(long, default_value_t = 2)]
+    conflict_retries: u32,
+    /// The interval between rounds, in seconds.
+    #[arg(long, default_value_t = 60)]
+    interval: u64,
+    /// The file of the local store, to keep the state across restarts.
+    #[arg(long)]
+    store: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum FeePayerKind {
+    /// The reward pool of the permissionless update lock, only for updates.
+    RewardPool,
+    /// The cells of the relayer, which are locked by the default lock
+    /// script, for all operations.
+    Secp256k1,
+}
+
+fn main() {
+    env_logger::init();
+    let cli = Cli::parse();
+
+    match cli.fee_payer {
+        FeePayerKind::RewardPool => {
+            let payer = RewardPoolPayer { fee: cli.fee };
+            start(cli, payer);
+        }
+        FeePayerKind::Secp256k1 => {
+            let payer = secp256k1_payer(&cli);
+            start(cli, payer);
+        }
+    }
+}
+
+fn secp256k1_payer(cli: &Cli) -> Secp256k1Payer {
+    let (Some(path), Some(out_point)) = (&cli.private_key_file, &cli.secp256k1_dep_group) else {
+        Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "the fee payer secp256k1 requires --private-key-file and --secp256k1-dep-group",
+            )
+            .exit()
+    };
+    let private_key = fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|content| {
+            H256::from_str(content.trim().trim_start_matches("0x")).map_err(|err| err.to_string())
+        })
+        .unwrap_or_else(|err| {
+            Cli::command()
+                .error(
+                    ErrorKind::InvalidValue,
+                    format!("failed to load the private key: {err}"),
+                )
+                .exit()
+        });
+    let cell_dep = CellDep::new_builder()
+        .out_point(out_point.clone())
+        .dep_type(DepType::DepGroup.into())
+        .build();
+    Secp256k1Payer {
+        fee: cli.fee,
+        private_key: Privkey::from(private_key),
+        cell_dep,
+    }
+}
+
+fn start<P: FeePayer>(cli: Cli, payer: P) {
+    let config = RelayerConfig {
+        type_script: cli.spv_type_script.script(),
+        max_headers: cli.max_headers,
+        conflict_retries: cli.conflict_retries,
+    };
+    let chain = RpcChain::new(&cli.ckb_rpc);
+    let interval = Duration::from_secs(cli.interval);
+    let store = cli
+        .store
+        .map(|path| Store::open(path).expect("failed to open the store"));
+
+    match (cli.bitcoin_rpc, cli.headers_dir) {
+        (Some(url), None) => {
+            let source = RpcHeaderSource::new(&url);
+            run(config, source, chain, payer, store, interval);
+        }
+        (None, Some(dir)) => {
+            let source = FileHeaderSource::new(dir);
+            run(config, source, chain, payer, store, interval);
+        }
+        // Exactly one of them is required by the argument group "source",
+        // this is only a guard, so the relayer never exits silently.
+        _ => Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "exactly one of --bitcoin-rpc and --headers-dir is required",
+            )
+            .exit(),
+    }
+}
+
+fn run<S: HeaderSource, P: FeePayer>(
+    config: RelayerConfig,
+    source: S,
+    chain: RpcChain,
+    payer: P,
+    store: Option<Store>,
+    interval: Duration,
+) {
+    let mut relayer = Relayer::new(config, source, chain, payer);
+    if let Some(store) = store {
+        relayer = relayer
+            .with_store(store)
+            .expect("failed to restore from the store");
+    }
+    relayer.run(interval);
+}
+
+fn parse_out_point(input: &str) -> Result<OutPoint, String> {
+    let (tx_hash, index) = input
+        .split_once(':')
+        .ok_or_else(|| "should be <tx hash>:<index>".to_owned())?;
+    let tx_hash =
+        H256::from_str(tx_hash.trim_start_matches("0x")).map_err(|err| err.to_string())?;
+    let index: u32 = index.parse().map_err(|err| format!("{err}"))?;
+    let out_point = OutPoint::new_builder()
+        .tx_hash(tx_hash.pack())
+        .index(index.pack())
+        .build();
+    Ok(out_point)
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{error::ErrorKind, CommandFactory as _, Parser as _};
+
+    use super::Cli;
+
+    const REQUIRED: &[&str] = &[
+        "relayer",
+        "--ckb-rpc",
+        "http://127.0.0.1:8114",
+        "--spv-type-code-hash",
+        "0x0000000000000000000000000000000000000000000000000000000000000000",
+        "--spv-type-args",
+        "0x00",
+    ];
+
+    fn parse(extra: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(REQUIRED.iter().chain(extra))
+    }
+
+    #[test]
+    fn verify_cli() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn require_one_header_source() {
+        let err = parse(&[]).err().expect("no header source");
+        assert_eq!(err.kind(), ErrorKind::MissingRequiredArgument);
+        let err = parse(&[
+            "--bitcoin-rpc",
+            "http://127.0.0.1:8332",
+            "--headers-dir",
+            ".",
+        ])
+        .err()
+        .expect("both header sources");
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+        assert!(parse(&["--bitcoin-rpc", "http://127.0.0.1:8332"]).is_ok());
+        assert!(parse(&["--headers-dir", "."]).is_ok());
+    }
+
+    #[test]
+    fn require_key_for_secp256k1_payer() {
+        let source = ["--headers-dir", "."];
+        let err = parse(&[&source[..], &["--fee-payer", "secp256k1"]].concat())
+            .err()
+            .expect("no private key");
+        assert_eq!(err.kind(), ErrorKind::MissingRequiredArgument);
+        let cli = parse(
+            &[
+                &source[..],
+                &[
+                    "--fee-payer",
+                    "secp256k1",
+                    "--private-key-file",
+                    "key",
+                    "--secp256k1-dep-group",
+                    "0x0000000000000000000000000000000000000000000000000000000000000001:0",
+                ],
+            ]
+            .concat(),
+        )
+        .unwrap();
+        assert!(cli.secp256k1_dep_group.is_some());
+        let err = parse(&[&source[..], &["--secp256k1-dep-group", "0x01"]].concat())
+            .err()
+            .expect("bad out point");
+        assert_eq!(err.kind(), ErrorKind::ValueValidation);
+    }
+}
@@ -0,0 +1,140 @@
+//! Build proofs of Bitcoin transactions, which could be verified by the SPV
+//! instance on CKB.
+//!
+//! The headers are from the store of the relayer, and the proofs are built
+//! against the tip client, so the tip client should be used as the cell dep.
+
+use bitcoin::{consensus::serialize, Block, BlockHash, MerkleBlock, Txid};
+use ckb_bitcoin_spv_prover::DummyService;
+use ckb_bitcoin_spv_tx_builder::SpvInstance;
+use ckb_bitcoin_spv_verifier::types::{
+    core,
+    packed::{self, SpvClientReader},
+    prelude::*,
+};
+use ckb_types::packed::{Byte, OutPoint};
+
+use crate::{
+    error::{Error, Result},
+    relayer::{heights, is_same_client},
+    store::ProvenHeaders,
+};
+
+/// The proof of a transaction, and the inputs to verify it.
+#[derive(Debug, Clone)]
+pub struct TxProof {
+    pub txid: Txid,
+    pub block_hash: BlockHash,
+    pub height: u32,
+    pub tx_index: u32,
+    pub tx_proof: packed::TransactionProof,
+    /// The SPV client which should be used as the cell dep.
+    pub client_id: u8,
+    pub client_out_point: OutPoint,
+    pub client_tip_height: u32,
+    /// The max confirmations which could be verified by the client.
+    pub confirmations: u32,
+}
+
+/// Builds proofs of transactions.
+///
+/// The prover of the last tip client is cached, since rebuilding it needs
+/// all headers.
+#[derive(Default)]
+pub struct TxProver {
+    service: Option<DummyService>,
+}
+
+impl TxProver {
+    /// Builds the proof of the transaction `txid` in the `block`.
+    pub fn prove(
+        &mut self,
+        headers: &ProvenHeaders,
+        instance: &SpvInstance,
+        block: &Block,
+        txid: Txid,
+    ) -> Result<TxProof> {
+        let tip_cell = instance.tip_client();
+        let client: core::SpvClient = SpvClientReader::from_slice(&tip_cell.data)
+            .map_err(|err| Error::Decode(format!("SPV client: {err}")))?
+            .unpack();
+        let (min_height, tip_height) = heights(&client);
+
+        if !block.check_merkle_root() {
+            return Err(Error::Unprovable(
+                "the merkle root is mismatched".to_owned(),
+            ));
+        }
+        let tx_index = block
+            .txdata
+            .iter()
+            .position(|tx| tx.txid() == txid)
+            .ok_or_else(|| Error::Unprovable(format!("{txid} is not in the block")))?;
+        let block_hash = block.block_hash();
+        let height = (min_height..=tip_height)
+            .find(|height| {
+                headers
+                    .header(*height)
+                    .is_some_and(|header| header.block_hash() == block_hash)
+            })
+            .ok_or_else(|| {
+                let msg = format!("the block {block_hash} is not in the SPV client");
+                Error::Unprovable(msg)
+            })?;
+
+        let service = self.service(headers, &client, tip_height)?;
+        let header_proof = service
+            .generate_header_proof(height)
+            .map_err(|err| Error::Prover(format!("{err:?}")))?
+            .ok_or(Error::HeaderNotFound(height))?;
+        let merkle_block = MerkleBlock::from_block_with_predicate(block, |id| *id == txid);
+        let txout_proof = packed::Bytes::new_builder()
+            .set(
+                serialize(&merkle_block)
+                    .into_iter()
+                    .map(Byte::new)
+                    .collect(),
+            )
+            .build();
+        let tx_index = tx_index as u32;
+        let tx_proof = packed::TransactionProof::new_builder()
+            .tx_index(tx_index.pack())
+            .height(height.pack())
+            .transaction_proof(txout_proof)
+            .header_proof(header_proof.pack())
+            .build();
+
+        Ok(TxProof {
+            txid,
+            block_hash,
+            height,
+            tx_index,
+            tx_proof,
+            client_id: client.id,
+            client_out_point: tip_cell.out_point.clone(),
+            client_tip_height: tip_height,
+            confirmations: tip_height - height,
+        })
+    }
+
+    // Returns the prover whose tip client is same as the client.
+    fn service(
+        &mut self,
+        headers: &ProvenHeaders,
+        client: &core::SpvClient,
+        tip_height: u32,
+    ) -> Result<&DummyService> {
+        let service = match self.service.take() {
+            Some(service) if is_same_client(&service.tip_client(), client) => service,
+            _ => {
+                let service = headers.prover(tip_height)?;
+                if !is_same_client(&service.tip_client(), client) {
+                    let msg = "the headers in the store are not same as the SPV client";
+                    return Err(Error::Unprovable(msg.to_owned()));
+                }
+                service
+            }
+        };
+        Ok(self.service.insert(service))
+    }
+}
@@ -0,0 +1,425 @@
+use std::{
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bitcoin::hashes::Hash as _;
+use ckb_bitcoin_spv_model::{
+    planner::{self, CandidateChain, ClientTip, Plan},
+    Operation, SpvState,
+};
+use ckb_bitcoin_spv_prover::DummyService;
+use ckb_bitcoin_spv_tx_builder::{build_reorg, build_update, SpvInstance};
+use ckb_bitcoin_spv_verifier::types::{
+    core,
+    packed::{self, SpvClientReader},
+    prelude::*,
+};
+use ckb_types::packed::{Byte32, OutPoint, Script};
+
+use crate::{
+    chain::CkbChain,
+    error::{Error, Result},
+    fee::FeePayer,
+    source::HeaderSource,
+    store::{InstanceState, Store},
+};
+
+// The error of CKB when a transaction is already in the pool.
+const DUPLICATED_TX: &str = "PoolRejectedDuplicatedTransaction";
+// The errors of CKB when an input of a transaction is consumed by another
+// transaction, which is committed or in the pool.
+const CONFLICT_ERRORS: &[&str] = &["Resolve failed", "PoolRejectedRBF", "RBFRejected"];
+// The max multiple of the interval to back off after conflicts.
+const MAX_BACKOFF_MULTIPLE: u32 = 8;
+
+pub struct RelayerConfig {
+    /// The type script of the SPV instance.
+    pub type_script: Script,
+    /// The max count of headers in one transaction; but a reorg always
+    /// contains the headers up to the height next to the old tip.
+    pub max_headers: u32,
+    /// How many times to rebuild the transaction in one round, when the SPV
+    /// instance is updated by other relayers.
+    pub conflict_retries: u32,
+}
+
+/// The result of one round of the relayer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Relayed {
+    /// The SPV instance is already synchronized.
+    UpToDate,
+    /// A transaction is submitted.
+    Submitted {
+        tx_hash: Byte32,
+        operation: Operation,
+        /// The height of the new tip client.
+        tip_height: u32,
+    },
+    /// A transaction, which was submitted before, is not committed yet, so it
+    /// is submitted again.
+    Pending { tx_hash: Byte32 },
+}
+
+pub struct Relayer<S, C, P> {
+    config: RelayerConfig,
+    source: S,
+    chain: C,
+    payer: P,
+    // The prover, which has all headers since the bootstrap, so it's built
+    // lazily and kept between rounds.
+    service: Option<DummyService>,
+    store: Option<Store>,
+}
+
+impl<S: HeaderSource, C: CkbChain, P: FeePayer> Relayer<S, C, P> {
+    pub fn new(config: RelayerConfig, source: S, chain: C, payer: P) -> Self {
+        Self {
+            config,
+            source,
+            chain,
+            payer,
+            service: None,
+            store: None,
+        }
+    }
+
+    /// Keeps the state in a store, and restores the prover from the proven
+    /// headers in it.
+    pub fn with_store(mut self, store: Store) -> Result<Self> {
+        let proven = &store.state().headers;
+        if let Some(tip_height) = proven.tip_height() {
+            log::info!(
+                "restore the prover from {} to {tip_height}",
+                proven.start_height
+            );
+            self.service = Some(proven.prover(tip_height)?);
+        }
+        self.store = Some(store);
+        Ok(self)
+    }
+
+    pub fn chain(&self) -> &C {
+        &self.chain
+    }
+
+    pub fn store(&self) -> Option<&Store> {
+        self.store.as_ref()
+    }
+
+    /// Runs rounds forever; errors are logged, then retried in next round.
+    ///
+    /// After conflicts with other relayers, it backs off for a while, to
+    /// reduce wasted fees.
+    pub fn run(&mut self, interval: Duration) {
+        let mut conflicts = 0;
+        loop {
+            let result = self.run_once();
+            if matches!(result, Err(Error::Conflict)) {
+                conflicts += 1;
+            } else {
+                conflicts = 0;
+            }
+            match result {
+                Ok(Relayed::UpToDate) => log::debug!("the SPV instance is up to date"),
+                Ok(Relayed::Submitted {
+                    tx_hash,
+                    operation,
+                    tip_height,
+                }) => log::info!("submitted {operation:?} to {tip_height}, tx: {tx_hash:#x}"),
+                Ok(Relayed::Pending { tx_hash }) => {
+                    log::info!("tx {tx_hash:#x} is pending, submitted it again")
+                }
+                Err(err) => log::error!("failed to relay: {err}"),
+            }
+            thread::sleep(interval + backoff(interval, conflicts));
+        }
+    }
+
+    /// Runs one round: loads the SPV instance, plans the next operation,
+    /// then builds and submits the transaction.
+    ///
+    /// If the SPV instance is updated by another relayer before the
+    /// transaction is committed, the transaction is dropped, then rebuilt on
+    /// the new SPV instance, if it still needs to be updated.
+    pub fn run_once(&mut self) -> Result<Relayed> {
+        let mut conflicts = 0;
+        loop {
+            match self.relay() {
+                Err(Error::Conflict) if conflicts < self.config.conflict_retries => {
+                    conflicts += 1;
+                    log::info!("the SPV instance is updated by others, rebuild ({conflicts})");
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn relay(&mut self) -> Result<Relayed> {
+        let cells = self.chain.cells_by_type(&self.config.type_script)?;
+        let instance = SpvInstance::load(cells)?;
+        let state = SpvState::new(
+            instance.clients_count(),
+            instance.type_args.flags,
+            instance.tip_client_id,
+        )?;
+        if let Some(relayed) = self.reconcile(&instance)? {
+            return Ok(relayed);
+        }
+        let clients = instance
+            .clients
+            .iter()
+            .map(|cell| {
+                SpvClientReader::from_slice(&cell.data)
+                    .map(|client| client.to_entity())
+                    .map_err(|err| Error::Decode(format!("SPV client: {err}")))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let tips = clients
+            .iter()
+            .map(|client| ClientTip::from_client(client.as_reader()))
+            .collect::<Vec<_>>();
+        let tip_height = tips
+            .iter()
+            .find(|tip| tip.id == instance.tip_client_id)
+            .map(|tip| tip.height)
+            .unwrap_or_default();
+        if let Some(last) = self
+            .store
+            .as_ref()
+            .and_then(|s| s.state().instance.as_ref())
+        {
+            if last.tip_height > tip_height {
+                log::warn!(
+                    "the SPV instance goes back from {} to {tip_height}, is CKB reorganized?",
+                    last.tip_height
+                );
+            }
+        }
+        self.record(|store| {
+            store.set_instance(InstanceState {
+                info_out_point: instance.info.out_point.clone(),
+                tip_client_id: instance.tip_client_id,
+                tip_height,
+            })
+        })?;
+        let max_headers = self.max_headers();
+        let candidate = {
+            let start_height = tips.iter().map(|tip| tip.height).min().unwrap_or(0);
+            // No more headers are sent in this round, and a reorg only needs
+            // the headers up to the height next to the old tip.
+            let end_height = self
+                .source
+                .tip_height()?
+                .min(tip_height.saturating_add(max_headers));
+            let block_hashes = self
+                .source
+                .headers(start_height, end_height)?
+                .into_iter()
+                .map(|header| header.block_hash().to_byte_array())
+                .collect();
+            CandidateChain {
+                start_height,
+                block_hashes,
+            }
+        };
+
+        let (base_client_id, headers, operation) = match planner::plan(&state, &tips, &candidate)? {
+            Plan::UpToDate => return Ok(Relayed::UpToDate),
+            Plan::RecoveryRequired => return Err(Error::RecoveryRequired),
+            Plan::Apply {
+                base_client_id,
+                headers,
+                operation,
+                ..
+            } => (base_client_id, headers, operation),
+        };
+        log::debug!("plan {operation:?} based on client {base_client_id}, headers {headers:?}");
+
+        let base_client: core::SpvClient =
+            clients[usize::from(base_client_id)].as_reader().unpack();
+        let start_height = *headers.start();
+        let mut end_height = (*headers.end()).min(start_height.saturating_add(max_headers - 1));
+        if let Operation::Reorg { .. } = operation {
+            // The new chain should have more work than the old one, so a
+            // reorg is never capped below the old tip.
+            end_height = end_height.max((tip_height + 1).min(*headers.end()));
+        }
+        let new_headers = self.source.headers(start_height, end_height)?;
+        let mut service = self.prepare_service(&base_client)?;
+        let update = service
+            .update(new_headers.clone())
+            .map_err(|err| Error::Prover(format!("{err:?}")))?;
+        let new_client = service.tip_client();
+        self.service = Some(service);
+        self.record(|store| store.append_headers(start_height, &new_headers))?;
+
+        let tx = match operation {
+            Operation::Reorg { fork_client_id } => {
+                build_reorg(&instance, fork_client_id, new_client, &update)?
+            }
+            _ => build_update(&instance, new_client, &update)?,
+        };
+        let tx = self.payer.pay(&self.chain, tx, operation)?;
+        // The transaction is recorded before it's submitted; if it's failed
+        // to submit, it will be submitted again in next round.
+        let info_out_point = instance.info.out_point.clone();
+        self.record(|store| store.set_pending(&tx, info_out_point.clone()))?;
+        let tx_hash = match self.chain.send_transaction(&tx) {
+            Ok(tx_hash) => tx_hash,
+            Err(err) if self.is_conflicted(&info_out_point, &err) => {
+                log::info!("drop tx {:#x}: {err}", tx.hash());
+                self.record(|store| store.resolve_pending(false))?;
+                return Err(Error::Conflict);
+            }
+            Err(err) => return Err(err),
+        };
+        Ok(Relayed::Submitted {
+            tx_hash,
+            operation,
+            tip_height: end_height,
+        })
+    }
+
+    // Reconciles the pending transaction against the SPV instance on chain.
+    //
+    // Returns a result if this round should be ended.
+    fn reconcile(&mut self, instance: &SpvInstance) -> Result<Option<Relayed>> {
+        let Some(pending) = self.store.as_ref().and_then(|s| s.state().pending.clone()) else {
+            return Ok(None);
+        };
+        let tx_hash = pending.tx.hash();
+        let info_out_point = &instance.info.out_point;
+        if info_out_point.tx_hash() == tx_hash {
+            log::info!("tx {tx_hash:#x} is committed");
+            self.record(|store| store.resolve_pending(true))?;
+            Ok(None)
+        } else if *info_out_point == pending.info_out_point {
+            // Submits the same transaction again, so it's never double
+            // submitted.
+            match self.chain.send_transaction(&pending.tx) {
+                Ok(_) => Ok(Some(Relayed::Pending { tx_hash })),
+                Err(Error::Rpc(msg)) if msg.contains(DUPLICATED_TX) => {
+                    Ok(Some(Relayed::Pending { tx_hash }))
+                }
+                Err(err) => {
+                    log::warn!("drop tx {tx_hash:#x}: {err}");
+                    self.record(|store| store.resolve_pending(false))?;
+                    Err(err)
+                }
+            }
+        } else {
+            log::info!("drop tx {tx_hash:#x}, the SPV info cell is consumed by another tx");
+            self.record(|store| store.resolve_pending(false))?;
+            Ok(None)
+        }
+    }
+
+    // Checks if the transaction is failed since the SPV info cell, which it
+    // consumes, is consumed by another transaction.
+    fn is_conflicted(&self, info_out_point: &OutPoint, err: &Error) -> bool {
+        if let Error::Rpc(msg) = err {
+            if CONFLICT_ERRORS.iter().any(|pattern| msg.contains(pattern)) {
+                return true;
+            }
+        }
+        self.chain
+            .cells_by_type(&self.config.type_script)
+            .and_then(|cells| Ok(SpvInstance::load(cells)?))
+            .map(|instance| instance.info.out_point != *info_out_point)
+            .unwrap_or(false)
+    }
+
+    fn record<F>(&mut self, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut Store) -> Result<()>,
+    {
+        self.store.as_mut().map_or(Ok(()), f)
+    }
+
+    // At least 1 header is sent in a transaction.
+    fn max_headers(&self) -> u32 {
+        self.config.max_headers.max(1)
+    }
+
+    /// Returns the prover whose tip client is same as the base client.
+    fn prepare_service(&mut self, base_client: &core::SpvClient) -> Result<DummyService> {
+        let (min_height, base_height) = heights(base_client);
+        let mut service = match self.service.take() {
+            Some(service) => service,
+            None => {
+                log::info!("bootstrap the prover at {min_height}");
+                let header = self.source.header(min_height)?;
+                let service = DummyService::bootstrap(min_height, header)
+                    .map_err(|err| Error::Prover(format!("{err:?}")))?;
+                self.record(|store| store.bootstrap(min_height, header))?;
+                service
+            }
+        };
+
+        let (_, prover_height) = heights(&service.tip_client());
+        if prover_height > base_height {
+            service
+                .rollback_to(base_client.clone())
+                .map_err(|err| Error::Prover(format!("{err:?}")))?;
+            self.record(|store| store.rollback(base_height))?;
+        } else {
+            let mut height = prover_height;
+            while height < base_height {
+                let end_height = base_height.min(height.saturating_add(self.max_headers()));
+                let headers = self.source.headers(height + 1, end_height)?;
+                service
+                    .update(headers.clone())
+                    .map_err(|err| Error::Prover(format!("{err:?}")))?;
+                self.record(|store| store.append_headers(height + 1, &headers))?;
+                height = end_height;
+            }
+        }
+
+        // The prover is dropped if it's not on the same chain, so it will be
+        // bootstrapped again in next round.
+        if !is_same_client(&service.tip_client(), base_client) {
+            return Err(Error::ProverMismatch(base_client.id));
+        }
+        Ok(service)
+    }
+}
+
+// The extra time to wait after continuous conflicts: the interval doubles
+// for each conflict, with a jitter, so the relayers which conflicted don't
+// retry at the same time.
+fn backoff(interval: Duration, conflicts: u32) -> Duration {
+    if conflicts == 0 {
+        return Duration::ZERO;
+    }
+    let multiple = 1u32
+        .checked_shl(conflicts - 1)
+        .unwrap_or(u32::MAX)
+        .min(MAX_BACKOFF_MULTIPLE);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.subsec_nanos())
+        .unwrap_or_default();
+    let jitter = interval.mul_f64(f64::from(nanos) / 1e9);
+    interval * multiple + jitter
+}
+
+// Returns the min height and the max height of a client.
+pub(crate) fn heights(client: &core::SpvClient) -> (u32, u32) {
+    let packed_client: packed::SpvClient = client.pack();
+    let headers_mmr_root = packed_client.headers_mmr_root();
+    (
+        headers_mmr_root.min_height().unpack(),
+        headers_mmr_root.max_height().unpack(),
+    )
+}
+
+// Checks if two clients are same, except their ids.
+pub(crate) fn is_same_client(lhs: &core::SpvClient, rhs: &core::SpvClient) -> bool {
+    let mut lhs = lhs.clone();
+    lhs.id = rhs.id;
+    let lhs: packed::SpvClient = lhs.pack();
+    let rhs: packed::SpvClient = rhs.pack();
+    lhs.as_slice() == rhs.as_slice()
+}
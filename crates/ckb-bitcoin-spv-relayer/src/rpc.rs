@@ -0,0 +1,45 @@
+//! A minimal JSON-RPC 2.0 client over HTTP.
+
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+
+use crate::error::{Error, Result};
+
+#[derive(Deserialize)]
+struct Response {
+    result: Option<Value>,
+    error: Option<Value>,
+}
+
+pub(crate) struct RpcClient {
+    url: String,
+}
+
+impl RpcClient {
+    pub(crate) fn new(url: &str) -> Self {
+        Self {
+            url: url.to_owned(),
+        }
+    }
+
+    pub(crate) fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        // Some servers, e.g. Bitcoin Core, return errors with HTTP error codes.
+        let response: Response = match ureq::post(&self.url).send_json(request) {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(err) => return Err(Error::Rpc(format!("{method}: {err}"))),
+        }
+        .into_json()
+        .map_err(|err| Error::Rpc(format!("{method}: {err}")))?;
+        if let Some(err) = response.error {
+            return Err(Error::Rpc(format!("{method}: {err}")));
+        }
+        let result = response.result.unwrap_or(Value::Null);
+        serde_json::from_value(result).map_err(|err| Error::Decode(format!("{method}: {err}")))
+    }
+}
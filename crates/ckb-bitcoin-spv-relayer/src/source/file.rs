@@ -0,0 +1,49 @@
+use std::{fs, path::PathBuf};
+
+use ckb_bitcoin_spv_prover::utilities::decode_from_bin_file;
+use ckb_bitcoin_spv_verifier::types::core;
+
+use super::HeaderSource;
+use crate::error::{Error, Result};
+
+/// Headers in a directory, in the same layout as the test data: each header
+/// is in a file named by its height with 7 digits, e.g. `0822528.bin`.
+pub struct FileHeaderSource {
+    dir: PathBuf,
+}
+
+impl FileHeaderSource {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, height: u32) -> PathBuf {
+        self.dir.join(format!("{height:07}.bin"))
+    }
+}
+
+impl HeaderSource for FileHeaderSource {
+    fn tip_height(&self) -> Result<u32> {
+        let entries = fs::read_dir(&self.dir)
+            .map_err(|err| Error::Io(format!("{}: {err}", self.dir.display())))?;
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.extension()? != "bin" {
+                    return None;
+                }
+                path.file_stem()?.to_str()?.parse::<u32>().ok()
+            })
+            .max()
+            .ok_or_else(|| Error::Io(format!("{}: no headers", self.dir.display())))
+    }
+
+    fn header(&self, height: u32) -> Result<core::Header> {
+        let path = self.path(height);
+        if !path.is_file() {
+            return Err(Error::HeaderNotFound(height));
+        }
+        decode_from_bin_file(&path).map_err(|err| Error::Decode(format!("{err:?}")))
+    }
+}
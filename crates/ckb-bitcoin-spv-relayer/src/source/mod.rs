@@ -0,0 +1,30 @@
+//! Sources of Bitcoin headers and blocks.
+
+use bitcoin::{Block, Txid};
+use ckb_bitcoin_spv_verifier::types::core;
+
+use crate::error::Result;
+
+mod file;
+mod rpc;
+
+pub use file::FileHeaderSource;
+pub use rpc::RpcHeaderSource;
+
+/// A source of Bitcoin headers of the best chain.
+pub trait HeaderSource {
+    /// The height of the best block.
+    fn tip_height(&self) -> Result<u32>;
+    fn header(&self, height: u32) -> Result<core::Header>;
+
+    /// Returns the headers in `start..=end`.
+    fn headers(&self, start: u32, end: u32) -> Result<Vec<core::Header>> {
+        (start..=end).map(|height| self.header(height)).collect()
+    }
+}
+
+/// A source of Bitcoin blocks, to find the block of a transaction.
+pub trait BlockSource {
+    /// Returns the block which contains the transaction.
+    fn block_of_transaction(&self, txid: &Txid) -> Result<Block>;
+}
@@ -0,0 +1,60 @@
+use bitcoin::{consensus::deserialize, Block, Txid};
+use ckb_bitcoin_spv_verifier::types::core;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{BlockSource, HeaderSource};
+use crate::{
+    error::{Error, Result},
+    rpc::RpcClient,
+};
+
+#[derive(Deserialize)]
+struct TransactionInfo {
+    // Not existed if the transaction is not confirmed.
+    blockhash: Option<String>,
+}
+
+/// Headers from the JSON-RPC of a Bitcoin node.
+///
+/// To find blocks of transactions, the node should enable `txindex`.
+pub struct RpcHeaderSource {
+    client: RpcClient,
+}
+
+impl RpcHeaderSource {
+    pub fn new(url: &str) -> Self {
+        Self {
+            client: RpcClient::new(url),
+        }
+    }
+}
+
+impl HeaderSource for RpcHeaderSource {
+    fn tip_height(&self) -> Result<u32> {
+        self.client.call("getblockcount", json!([]))
+    }
+
+    fn header(&self, height: u32) -> Result<core::Header> {
+        let block_hash: String = self.client.call("getblockhash", json!([height]))?;
+        let header_hex: String = self
+            .client
+            .call("getblockheader", json!([block_hash, false]))?;
+        let header = hex::decode(header_hex).map_err(|err| Error::Decode(format!("{err}")))?;
+        deserialize(&header).map_err(|err| Error::Decode(format!("header: {err}")))
+    }
+}
+
+impl BlockSource for RpcHeaderSource {
+    fn block_of_transaction(&self, txid: &Txid) -> Result<Block> {
+        let info: TransactionInfo = self
+            .client
+            .call("getrawtransaction", json!([txid.to_string(), true]))?;
+        let block_hash = info
+            .blockhash
+            .ok_or_else(|| Error::Rpc(format!("the transaction {txid} is not confirmed")))?;
+        let block_hex: String = self.client.call("getblock", json!([block_hash, 0]))?;
+        let block = hex::decode(block_hex).map_err(|err| Error::Decode(format!("{err}")))?;
+        deserialize(&block).map_err(|err| Error::Decode(format!("block: {err}")))
+    }
+}
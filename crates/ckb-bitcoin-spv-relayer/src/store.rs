@@ -0,0 +1,371 @@
+//! A durable local store of the relayer, as an append-only journal.
+//!
+//! Each change is appended as one line of JSON, then synced to the disk
+//! before the relayer goes on, so after a crash, the store has all changes
+//! except the one which was being written. A torn line at the end of the
+//! journal is discarded when the store is opened.
+//!
+//! When the store is opened, the journal is compacted: the current state is
+//! written into a temporary file, which then replaces the journal.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write as _,
+    path::{Path, PathBuf},
+};
+
+use bitcoin::consensus::{deserialize, serialize};
+use ckb_bitcoin_spv_prover::DummyService;
+use ckb_bitcoin_spv_verifier::types::core;
+use ckb_jsonrpc_types as json;
+use ckb_types::{
+    core::TransactionView,
+    packed::{OutPoint, Transaction},
+    prelude::*,
+    H256,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+// The count of headers to rebuild a prover in each step.
+const PROVER_CHUNK_SIZE: usize = 2016;
+
+/// The headers which have been proven by the prover, since its bootstrap.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProvenHeaders {
+    /// The height of the first header, which is the bootstrap.
+    pub start_height: u32,
+    pub headers: Vec<core::Header>,
+}
+
+/// A transaction which is submitted, but not confirmed yet.
+#[derive(Debug, Clone)]
+pub struct PendingTx {
+    pub tx: TransactionView,
+    /// The SPV info cell which is consumed by the transaction.
+    pub info_out_point: OutPoint,
+}
+
+/// The last known state of the SPV instance on chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstanceState {
+    pub info_out_point: OutPoint,
+    pub tip_client_id: u8,
+    pub tip_height: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StoreState {
+    pub headers: ProvenHeaders,
+    pub pending: Option<PendingTx>,
+    pub instance: Option<InstanceState>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Record {
+    /// Bootstraps the prover again, all proven headers are discarded.
+    Bootstrap { height: u32, header: String },
+    /// Appends proven headers, from `start_height`.
+    Headers {
+        start_height: u32,
+        headers: Vec<String>,
+    },
+    /// Discards the proven headers after `height`.
+    Rollback { height: u32 },
+    Pending {
+        tx: json::Transaction,
+        info_out_point: json::OutPoint,
+    },
+    /// The pending transaction is committed, or dropped.
+    Resolved { tx_hash: H256, committed: bool },
+    Instance {
+        info_out_point: json::OutPoint,
+        tip_client_id: u8,
+        tip_height: u32,
+    },
+}
+
+pub struct Store {
+    path: PathBuf,
+    file: File,
+    state: StoreState,
+}
+
+impl ProvenHeaders {
+    pub fn tip_height(&self) -> Option<u32> {
+        let count = u32::try_from(self.headers.len()).ok()?;
+        count
+            .checked_sub(1)
+            .map(|offset| self.start_height + offset)
+    }
+
+    pub fn header(&self, height: u32) -> Option<&core::Header> {
+        let offset = height.checked_sub(self.start_height)?;
+        self.headers.get(usize::try_from(offset).ok()?)
+    }
+
+    /// Rebuilds a prover with the headers up to `end_height`.
+    pub fn prover(&self, end_height: u32) -> Result<DummyService> {
+        let count = end_height
+            .checked_sub(self.start_height)
+            .map(|offset| offset as usize + 1)
+            .filter(|count| *count <= self.headers.len())
+            .ok_or(Error::HeaderNotFound(end_height))?;
+        let mut service = DummyService::bootstrap(self.start_height, self.headers[0])
+            .map_err(|err| Error::Prover(format!("{err:?}")))?;
+        for chunk in self.headers[1..count].chunks(PROVER_CHUNK_SIZE) {
+            service
+                .update(chunk.to_vec())
+                .map_err(|err| Error::Prover(format!("{err:?}")))?;
+        }
+        Ok(service)
+    }
+}
+
+impl PendingTx {
+    pub fn tx_hash(&self) -> H256 {
+        self.tx.hash().unpack()
+    }
+}
+
+impl Store {
+    /// Opens the store at `path`, or creates a new store if it doesn't exist.
+    pub fn open<P: Into<PathBuf>>(path: P) -> Result<Self> {
+        let path = path.into();
+        let state = Self::load(&path)?;
+
+        // Compacts the journal.
+        let tmp_path = path.with_extension("tmp");
+        {
+            let mut tmp_file = File::create(&tmp_path).map_err(|err| io_error(&tmp_path, err))?;
+            for record in state.records() {
+                write_record(&mut tmp_file, &record).map_err(|err| io_error(&tmp_path, err))?;
+            }
+            tmp_file
+                .sync_all()
+                .map_err(|err| io_error(&tmp_path, err))?;
+        }
+        fs::rename(&tmp_path, &path).map_err(|err| io_error(&path, err))?;
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            File::open(dir)
+                .and_then(|dir| dir.sync_all())
+                .map_err(|err| io_error(dir, err))?;
+        }
+
+        let file = OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .map_err(|err| io_error(&path, err))?;
+        Ok(Self { path, file, state })
+    }
+
+    /// Loads the state of the store at `path`, without changing it, so it
+    /// could be used when the relayer is running.
+    pub fn load(path: &Path) -> Result<StoreState> {
+        let mut state = StoreState::default();
+        if !path.exists() {
+            return Ok(state);
+        }
+        let journal = fs::read(path).map_err(|err| io_error(path, err))?;
+        let mut lines = journal.split(|byte| *byte == b'\n').peekable();
+        while let Some(line) = lines.next() {
+            // The last line is not finished, since the relayer crashed, or
+            // it's being written.
+            if lines.peek().is_none() {
+                if !line.is_empty() {
+                    log::warn!("discard a torn record in {}", path.display());
+                }
+                break;
+            }
+            let record = serde_json::from_slice(line)
+                .map_err(|err| Error::Store(format!("{}: {err}", path.display())))?;
+            state.apply(record)?;
+        }
+        Ok(state)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn state(&self) -> &StoreState {
+        &self.state
+    }
+
+    pub(crate) fn bootstrap(&mut self, height: u32, header: core::Header) -> Result<()> {
+        self.append(Record::Bootstrap {
+            height,
+            header: hex::encode(serialize(&header)),
+        })
+    }
+
+    pub(crate) fn append_headers(
+        &mut self,
+        start_height: u32,
+        headers: &[core::Header],
+    ) -> Result<()> {
+        self.append(Record::Headers {
+            start_height,
+            headers: headers
+                .iter()
+                .map(|header| hex::encode(serialize(header)))
+                .collect(),
+        })
+    }
+
+    pub(crate) fn rollback(&mut self, height: u32) -> Result<()> {
+        self.append(Record::Rollback { height })
+    }
+
+    pub(crate) fn set_pending(
+        &mut self,
+        tx: &TransactionView,
+        info_out_point: OutPoint,
+    ) -> Result<()> {
+        self.append(Record::Pending {
+            tx: tx.data().into(),
+            info_out_point: info_out_point.into(),
+        })
+    }
+
+    pub(crate) fn resolve_pending(&mut self, committed: bool) -> Result<()> {
+        let Some(tx_hash) = self.state.pending.as_ref().map(PendingTx::tx_hash) else {
+            return Ok(());
+        };
+        self.append(Record::Resolved { tx_hash, committed })
+    }
+
+    pub(crate) fn set_instance(&mut self, instance: InstanceState) -> Result<()> {
+        if self.state.instance.as_ref() == Some(&instance) {
+            return Ok(());
+        }
+        self.append(Record::Instance {
+            info_out_point: instance.info_out_point.into(),
+            tip_client_id: instance.tip_client_id,
+            tip_height: instance.tip_height,
+        })
+    }
+
+    // Applies the record, then persists it.
+    //
+    // The record is applied first, so an invalid record is never persisted.
+    fn append(&mut self, record: Record) -> Result<()> {
+        let mut state = self.state.clone();
+        let mut line = serde_json::to_vec(&record).map_err(|err| Error::Store(err.to_string()))?;
+        line.push(b'\n');
+        state.apply(record)?;
+        self.file
+            .write_all(&line)
+            .and_then(|()| self.file.sync_data())
+            .map_err(|err| io_error(&self.path, err))?;
+        self.state = state;
+        Ok(())
+    }
+}
+
+impl StoreState {
+    fn apply(&mut self, record: Record) -> Result<()> {
+        match record {
+            Record::Bootstrap { height, header } => {
+                self.headers = ProvenHeaders {
+                    start_height: height,
+                    headers: vec![decode_header(&header)?],
+                };
+            }
+            Record::Headers {
+                start_height,
+                headers,
+            } => {
+                let expected = self.headers.tip_height().map(|height| height + 1);
+                if expected != Some(start_height) {
+                    let msg = format!("headers from {start_height} are not continuous");
+                    return Err(Error::Store(msg));
+                }
+                for header in headers {
+                    self.headers.headers.push(decode_header(&header)?);
+                }
+            }
+            Record::Rollback { height } => {
+                let count = height
+                    .checked_sub(self.headers.start_height)
+                    .map(|offset| offset as usize + 1)
+                    .unwrap_or(0);
+                self.headers.headers.truncate(count);
+            }
+            Record::Pending { tx, info_out_point } => {
+                self.pending = Some(PendingTx {
+                    tx: Transaction::from(tx).into_view(),
+                    info_out_point: info_out_point.into(),
+                });
+            }
+            Record::Resolved { tx_hash, .. } => {
+                if self.pending.as_ref().map(PendingTx::tx_hash) == Some(tx_hash) {
+                    self.pending = None;
+                }
+            }
+            Record::Instance {
+                info_out_point,
+                tip_client_id,
+                tip_height,
+            } => {
+                self.instance = Some(InstanceState {
+                    info_out_point: info_out_point.into(),
+                    tip_client_id,
+                    tip_height,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // The minimal records to rebuild this state.
+    fn records(&self) -> Vec<Record> {
+        let mut records = Vec::new();
+        let mut headers = self.headers.headers.iter();
+        if let Some(bootstrap) = headers.next() {
+            records.push(Record::Bootstrap {
+                height: self.headers.start_height,
+                header: hex::encode(serialize(bootstrap)),
+            });
+            if headers.len() > 0 {
+                records.push(Record::Headers {
+                    start_height: self.headers.start_height + 1,
+                    headers: headers
+                        .map(|header| hex::encode(serialize(header)))
+                        .collect(),
+                });
+            }
+        }
+        if let Some(ref instance) = self.instance {
+            records.push(Record::Instance {
+                info_out_point: instance.info_out_point.clone().into(),
+                tip_client_id: instance.tip_client_id,
+                tip_height: instance.tip_height,
+            });
+        }
+        if let Some(ref pending) = self.pending {
+            records.push(Record::Pending {
+                tx: pending.tx.data().into(),
+                info_out_point: pending.info_out_point.clone().into(),
+            });
+        }
+        records
+    }
+}
+
+fn write_record(file: &mut File, record: &Record) -> std::io::Result<()> {
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+    file.write_all(&line)
+}
+
+fn decode_header(header: &str) -> Result<core::Header> {
+    let bytes = hex::decode(header).map_err(|err| Error::Store(format!("header: {err}")))?;
+    deserialize(&bytes).map_err(|err| Error::Store(format!("header: {err}")))
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> Error {
+    Error::Io(format!("{}: {err}", path.display()))
+}
@@ -0,0 +1,140 @@
+//! Two relayers race to update the same SPV instance.
+
+use std::{cell::RefCell, rc::Rc};
+
+use bitcoin::hashes::Hash as _;
+use ckb_bitcoin_spv_tx_builder::LiveCell;
+use ckb_types::packed::Byte32;
+
+use super::{mock::MockBitcoin, *};
+use crate::{error::Error, fee::RewardPoolPayer, source::RpcHeaderSource, Relayed};
+
+type Hook = Box<dyn FnOnce()>;
+
+/// Runs a hook before the first submission, e.g. lets another relayer
+/// submit its transaction.
+struct RacingChain {
+    inner: RpcChain,
+    before_send: RefCell<Option<Hook>>,
+}
+
+impl CkbChain for RacingChain {
+    fn cells_by_type(&self, type_script: &Script) -> Result<Vec<LiveCell>> {
+        self.inner.cells_by_type(type_script)
+    }
+
+    fn cells_by_lock(&self, lock_script: &Script) -> Result<Vec<LiveCell>> {
+        self.inner.cells_by_lock(lock_script)
+    }
+
+    fn send_transaction(&self, tx: &TransactionView) -> Result<Byte32> {
+        let hook = self.before_send.borrow_mut().take();
+        if let Some(hook) = hook {
+            hook();
+        }
+        self.inner.send_transaction(tx)
+    }
+}
+
+type SimpleRelayer = Relayer<RpcHeaderSource, RpcChain, RewardPoolPayer>;
+type RacingRelayer = Relayer<RpcHeaderSource, RacingChain, RewardPoolPayer>;
+
+/// Starts two relayers: the other one submits its transaction just before
+/// the racing one does.
+fn race(
+    bitcoin_url: &str,
+    ckb_url: &str,
+    type_script: &Script,
+    conflict_retries: u32,
+) -> (RacingRelayer, Rc<RefCell<SimpleRelayer>>) {
+    let config = || RelayerConfig {
+        type_script: type_script.clone(),
+        max_headers: 20,
+        conflict_retries,
+    };
+    let payer = || RewardPoolPayer { fee: FEE };
+
+    let other = Relayer::new(
+        config(),
+        RpcHeaderSource::new(bitcoin_url),
+        RpcChain::new(ckb_url),
+        payer(),
+    );
+    let other = Rc::new(RefCell::new(other));
+    let hook: Hook = {
+        let other = Rc::clone(&other);
+        Box::new(move || {
+            let relayed = other.borrow_mut().run_once().unwrap();
+            assert!(matches!(relayed, Relayed::Submitted { .. }));
+        })
+    };
+    let chain = RacingChain {
+        inner: RpcChain::new(ckb_url),
+        before_send: RefCell::new(Some(hook)),
+    };
+    let racing = Relayer::new(config(), RpcHeaderSource::new(bitcoin_url), chain, payer());
+    (racing, other)
+}
+
+#[test]
+fn rebuild_after_conflict() {
+    let ckb = MockCkb::default();
+    let type_script = setup(&ckb);
+    let bitcoin = MockBitcoin::new(START_HEIGHT, main_headers(START_HEIGHT + 30));
+    let bitcoin_server = bitcoin.serve();
+    let ckb_server = ckb.serve();
+    let (mut racing, other) = race(bitcoin_server.url(), ckb_server.url(), &type_script, 1);
+
+    // The other relayer updates to `START_HEIGHT + 20` first, so the
+    // transaction is rebuilt to update the rest.
+    let relayed = racing.run_once().unwrap();
+    assert!(matches!(
+        relayed,
+        Relayed::Submitted { tip_height, operation: Operation::Update, .. }
+            if tip_height == START_HEIGHT + 30
+    ));
+    assert_eq!(ckb.state.lock().unwrap().txs.len(), 2);
+    assert_eq!(other.borrow_mut().run_once().unwrap(), Relayed::UpToDate);
+    assert_eq!(racing.run_once().unwrap(), Relayed::UpToDate);
+    assert_eq!(pool_capacity(&ckb), POOL_CAPACITY - FEE * 2);
+}
+
+#[test]
+fn drop_after_conflict() {
+    let ckb = MockCkb::default();
+    let type_script = setup(&ckb);
+    let bitcoin = MockBitcoin::new(START_HEIGHT, main_headers(START_HEIGHT + 20));
+    let bitcoin_server = bitcoin.serve();
+    let ckb_server = ckb.serve();
+    let (mut racing, _other) = race(bitcoin_server.url(), ckb_server.url(), &type_script, 1);
+
+    // The other relayer updates to the same height, so the transaction is
+    // dropped.
+    assert_eq!(racing.run_once().unwrap(), Relayed::UpToDate);
+    assert_eq!(ckb.state.lock().unwrap().txs.len(), 1);
+    let instance = load_instance(&ckb, &type_script);
+    let header = main_chain().header(START_HEIGHT + 20).unwrap();
+    assert_eq!(
+        tip_block_hash(&instance),
+        header.block_hash().to_byte_array()
+    );
+}
+
+#[test]
+fn give_up_after_retries() {
+    let ckb = MockCkb::default();
+    let type_script = setup(&ckb);
+    let bitcoin = MockBitcoin::new(START_HEIGHT, main_headers(START_HEIGHT + 30));
+    let bitcoin_server = bitcoin.serve();
+    let ckb_server = ckb.serve();
+    let (mut racing, _other) = race(bitcoin_server.url(), ckb_server.url(), &type_script, 0);
+
+    assert_eq!(racing.run_once(), Err(Error::Conflict));
+    assert_eq!(ckb.state.lock().unwrap().txs.len(), 1);
+    // Rebuilt in next round.
+    let relayed = racing.run_once().unwrap();
+    assert!(matches!(
+        relayed,
+        Relayed::Submitted { tip_height, .. } if tip_height == START_HEIGHT + 30
+    ));
+}
@@ -0,0 +1,261 @@
+//! Local mock servers of the Bitcoin JSON-RPC and the CKB JSON-RPC.
+
+use std::{
+    io::{self, BufRead as _, BufReader, Read as _, Write as _},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use bitcoin::consensus::serialize;
+use ckb_bitcoin_spv_tx_builder::LiveCell;
+use ckb_bitcoin_spv_verifier::types::core;
+use ckb_jsonrpc_types as json;
+use ckb_types::{
+    core::TransactionView,
+    packed::{CellOutput, OutPoint, Script, Transaction},
+    prelude::*,
+    H256,
+};
+use serde_json::{json, Value};
+
+type Handler = dyn Fn(&str, &Value) -> Result<Value, String> + Send + Sync;
+
+/// A minimal JSON-RPC server over HTTP, which handles one request per
+/// connection.
+pub(crate) struct MockServer {
+    url: String,
+}
+
+impl MockServer {
+    pub(crate) fn start(handler: Arc<Handler>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                if let Err(err) = handle(&mut stream, handler.as_ref()) {
+                    log::warn!("mock server: {err}");
+                }
+            }
+        });
+        Self { url }
+    }
+
+    pub(crate) fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+fn handle(stream: &mut TcpStream, handler: &Handler) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let request: Value = serde_json::from_slice(&body)?;
+    let method = request["method"].as_str().unwrap_or_default();
+    let response = match handler(method, &request["params"]) {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+        Err(message) => json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": { "code": -1, "message": message },
+        }),
+    };
+    let body = response.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+        Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// A Bitcoin node, whose best chain could be replaced.
+#[derive(Clone)]
+pub(crate) struct MockBitcoin {
+    chain: Arc<Mutex<(u32, Vec<core::Header>)>>,
+}
+
+impl MockBitcoin {
+    pub(crate) fn new(start_height: u32, headers: Vec<core::Header>) -> Self {
+        Self {
+            chain: Arc::new(Mutex::new((start_height, headers))),
+        }
+    }
+
+    /// Replaces the best chain.
+    pub(crate) fn set_chain(&self, start_height: u32, headers: Vec<core::Header>) {
+        *self.chain.lock().unwrap() = (start_height, headers);
+    }
+
+    pub(crate) fn serve(&self) -> MockServer {
+        let chain = Arc::clone(&self.chain);
+        MockServer::start(Arc::new(move |method, params| {
+            let guard = chain.lock().unwrap();
+            let (start_height, ref headers) = *guard;
+            let header_by_hash = |hash: &str| {
+                headers
+                    .iter()
+                    .find(|header| header.block_hash().to_string() == hash)
+                    .ok_or_else(|| format!("block {hash} is not found"))
+            };
+            match method {
+                "getblockcount" => Ok(json!(start_height + headers.len() as u32 - 1)),
+                "getblockhash" => {
+                    let height = params[0].as_u64().ok_or("no height")? as u32;
+                    height
+                        .checked_sub(start_height)
+                        .and_then(|offset| headers.get(offset as usize))
+                        .map(|header| json!(header.block_hash().to_string()))
+                        .ok_or_else(|| format!("block {height} is not found"))
+                }
+                "getblockheader" => {
+                    let hash = params[0].as_str().ok_or("no hash")?;
+                    let header = header_by_hash(hash)?;
+                    Ok(json!(hex::encode(serialize(header))))
+                }
+                _ => Err(format!("method {method} is not supported")),
+            }
+        }))
+    }
+}
+
+/// The live cells and the committed transactions of a CKB node.
+///
+/// Scripts are not verified.
+#[derive(Default)]
+pub(crate) struct MockCkbState {
+    pub(crate) cells: Vec<LiveCell>,
+    pub(crate) txs: Vec<TransactionView>,
+}
+
+impl MockCkbState {
+    /// Consumes the inputs and adds the outputs, if all inputs are live.
+    pub(crate) fn commit(&mut self, tx: &TransactionView) -> Result<(), String> {
+        let input_out_points = tx.input_pts_iter().collect::<Vec<_>>();
+        for out_point in &input_out_points {
+            if !self
+                .cells
+                .iter()
+                .any(|cell| is_same(&cell.out_point, out_point))
+            {
+                // Same as the error of CKB.
+                return Err(format!(
+                    "TransactionFailedToResolve: Resolve failed Dead({out_point})"
+                ));
+            }
+        }
+        self.cells.retain(|cell| {
+            !input_out_points
+                .iter()
+                .any(|out_point| is_same(&cell.out_point, out_point))
+        });
+        for (index, (output, data)) in tx.outputs_with_data_iter().enumerate() {
+            let out_point = OutPoint::new_builder()
+                .tx_hash(tx.hash())
+                .index((index as u32).pack())
+                .build();
+            self.cells.push(LiveCell {
+                out_point,
+                output,
+                data,
+            });
+        }
+        self.txs.push(tx.clone());
+        Ok(())
+    }
+
+    /// Adds a cell, as an output of a genesis transaction.
+    pub(crate) fn add_cell(&mut self, output: CellOutput, data: Vec<u8>) -> OutPoint {
+        let out_point = OutPoint::new_builder()
+            .tx_hash(H256([0xff; 32]).pack())
+            .index((self.cells.len() as u32).pack())
+            .build();
+        self.cells.push(LiveCell {
+            out_point: out_point.clone(),
+            output,
+            data: data.into(),
+        });
+        out_point
+    }
+}
+
+/// A CKB node, which enables the indexer.
+#[derive(Clone, Default)]
+pub(crate) struct MockCkb {
+    pub(crate) state: Arc<Mutex<MockCkbState>>,
+}
+
+impl MockCkb {
+    pub(crate) fn serve(&self) -> MockServer {
+        let state = Arc::clone(&self.state);
+        MockServer::start(Arc::new(move |method, params| {
+            let mut state = state.lock().unwrap();
+            match method {
+                "get_cells" => {
+                    let search_key = &params[0];
+                    let script: json::Script = serde_json::from_value(search_key["script"].clone())
+                        .map_err(|err| err.to_string())?;
+                    let script = Script::from(script);
+                    let by_type = search_key["script_type"] == "type";
+                    let without_type = !search_key["filter"].is_null();
+                    let objects = state
+                        .cells
+                        .iter()
+                        .filter(|cell| {
+                            if by_type {
+                                cell.output
+                                    .type_()
+                                    .to_opt()
+                                    .is_some_and(|type_script| is_same(&type_script, &script))
+                            } else {
+                                is_same(&cell.output.lock(), &script)
+                                    && (!without_type || cell.output.type_().is_none())
+                            }
+                        })
+                        .map(|cell| {
+                            json!({
+                                "output": json::CellOutput::from(cell.output.clone()),
+                                "output_data": json::JsonBytes::from_bytes(cell.data.clone()),
+                                "out_point": json::OutPoint::from(cell.out_point.clone()),
+                                "block_number": "0x0",
+                                "tx_index": "0x0",
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    Ok(json!({ "objects": objects, "last_cursor": "0x" }))
+                }
+                "send_transaction" => {
+                    let tx: json::Transaction =
+                        serde_json::from_value(params[0].clone()).map_err(|err| err.to_string())?;
+                    let tx = Transaction::from(tx).into_view();
+                    state.commit(&tx)?;
+                    let tx_hash: H256 = tx.hash().unpack();
+                    Ok(json!(tx_hash))
+                }
+                _ => Err(format!("method {method} is not supported")),
+            }
+        }))
+    }
+}
+
+fn is_same<T: Entity>(lhs: &T, rhs: &T) -> bool {
+    lhs.as_slice() == rhs.as_slice()
+}
@@ -0,0 +1,192 @@
+mod contention;
+mod mock;
+mod proof;
+mod recovery;
+mod relayer;
+mod store;
+
+use std::{
+    env, fs,
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use ckb_bitcoin_spv_model::Operation;
+use ckb_bitcoin_spv_tx_builder::{build_create, CreateParams, SpvInstance};
+use ckb_bitcoin_spv_verifier::types::{
+    core, packed,
+    prelude::{Pack as VPack, Unpack as VUnpack},
+};
+use ckb_types::{
+    bytes::Bytes,
+    core::{ScriptHashType, TransactionView},
+    packed::{CellInput, CellOutput, Script},
+    prelude::*,
+    H256,
+};
+
+use crate::{
+    chain::{CkbChain, RpcChain},
+    error::Result,
+    fee::FeePayer,
+    source::{FileHeaderSource, HeaderSource},
+    Relayer, RelayerConfig,
+};
+use mock::{MockCkb, MockServer};
+
+const START_HEIGHT: u32 = 822528;
+const STALE_HEIGHT: u32 = 823226;
+const CLIENTS_COUNT: u8 = 5;
+const FEE: u64 = 100_000;
+const MAX_REWARD: u64 = 1_000_000;
+const POOL_CAPACITY: u64 = 1_000 * 100_000_000;
+
+/// Pays nothing, since scripts are not verified by the mock CKB.
+struct NoFee;
+
+impl FeePayer for NoFee {
+    fn pay(
+        &self,
+        _chain: &dyn CkbChain,
+        tx: TransactionView,
+        _operation: Operation,
+    ) -> Result<TransactionView> {
+        Ok(tx)
+    }
+}
+
+fn data_dir(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../tests/data/main-chain/headers")
+        .join(path)
+}
+
+fn main_chain() -> FileHeaderSource {
+    FileHeaderSource::new(data_dir("continuous/case-0822528_0830592"))
+}
+
+fn main_headers(end_height: u32) -> Vec<core::Header> {
+    main_chain().headers(START_HEIGHT, end_height).unwrap()
+}
+
+// The SPV cells are locked by the permissionless update lock, with the
+// reward mode. Scripts are not verified by the mock CKB, so the type hash of
+// the SPV instance in the args is a dummy one.
+fn spv_lock_script() -> Script {
+    let mut args = Vec::new();
+    args.extend_from_slice(&[3u8; 32]);
+    args.extend_from_slice(&[5u8; 20]);
+    args.extend_from_slice(&MAX_REWARD.to_le_bytes());
+    Script::new_builder()
+        .code_hash(H256([4u8; 32]).pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(args).pack())
+        .build()
+}
+
+/// Creates an SPV instance and a reward pool cell in the mock CKB, then
+/// returns the SPV type script.
+fn setup(ckb: &MockCkb) -> Script {
+    let header = main_chain().header(START_HEIGHT).unwrap();
+    setup_with_bootstrap(ckb, START_HEIGHT, header)
+}
+
+fn setup_with_bootstrap(ckb: &MockCkb, height: u32, header: core::Header) -> Script {
+    let mut state = ckb.state.lock().unwrap();
+    let funding = state.add_cell(CellOutput::new_builder().build(), Vec::new());
+
+    let bootstrap = packed::SpvBootstrap::new_builder()
+        .height(VPack::pack(&height))
+        .header(header.pack())
+        .build();
+    let params = CreateParams {
+        first_input: CellInput::new(funding, 0),
+        lock_script: spv_lock_script(),
+        type_script: Script::new_builder()
+            .code_hash(H256([2u8; 32]).pack())
+            .hash_type(ScriptHashType::Type.into())
+            .build(),
+        clients_count: CLIENTS_COUNT,
+        flags: 0,
+        cell_capacity: 500 * 100_000_000,
+        bootstrap,
+    };
+    let tx = build_create(params).unwrap();
+    state.commit(&tx).unwrap();
+
+    let type_script = tx.outputs().get(0).unwrap().type_().to_opt().unwrap();
+    let pool = CellOutput::new_builder()
+        .capacity(POOL_CAPACITY.pack())
+        .lock(spv_lock_script())
+        .build();
+    state.add_cell(pool, type_script.calc_script_hash().as_slice().to_vec());
+
+    type_script
+}
+
+fn load_instance(ckb: &MockCkb, type_script: &Script) -> SpvInstance {
+    let state = ckb.state.lock().unwrap();
+    let cells = state
+        .cells
+        .iter()
+        .filter(|cell| {
+            cell.output
+                .type_()
+                .to_opt()
+                .is_some_and(|script| script.as_slice() == type_script.as_slice())
+        })
+        .cloned()
+        .collect();
+    SpvInstance::load(cells).unwrap()
+}
+
+fn tip_client(instance: &SpvInstance) -> core::SpvClient {
+    packed::SpvClientReader::from_slice(&instance.tip_client().data)
+        .unwrap()
+        .unpack()
+}
+
+fn tip_block_hash(instance: &SpvInstance) -> [u8; 32] {
+    let mut block_hash = [0u8; 32];
+    let client = packed::SpvClientReader::from_slice(&instance.tip_client().data).unwrap();
+    block_hash.copy_from_slice(client.tip_block_hash().raw_data());
+    block_hash
+}
+
+fn pool_capacity(ckb: &MockCkb) -> u64 {
+    let state = ckb.state.lock().unwrap();
+    state
+        .cells
+        .iter()
+        .filter(|cell| cell.output.type_().is_none())
+        .filter(|cell| cell.output.lock().as_slice() == spv_lock_script().as_slice())
+        .map(|cell| Unpack::<u64>::unpack(&cell.output.capacity()))
+        .sum()
+}
+
+fn relayer<S: HeaderSource, P: FeePayer>(
+    source: S,
+    ckb: &MockCkb,
+    type_script: Script,
+    max_headers: u32,
+    payer: P,
+) -> (Relayer<S, RpcChain, P>, MockServer) {
+    let ckb_server = ckb.serve();
+    let config = RelayerConfig {
+        type_script,
+        max_headers,
+        conflict_retries: 0,
+    };
+    let chain = RpcChain::new(ckb_server.url());
+    (Relayer::new(config, source, chain, payer), ckb_server)
+}
+
+/// Returns a path in the temporary directory, which doesn't exist.
+fn temp_path(name: &str) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let count = COUNTER.fetch_add(1, Ordering::SeqCst);
+    let filename = format!("spv-relayer-{}-{count}-{name}", std::process::id());
+    let path = env::temp_dir().join(filename);
+    let _ = fs::remove_file(&path);
+    path
+}
@@ -0,0 +1,130 @@
+use bitcoin::{
+    absolute::LockTime, block, hashes::Hash as _, transaction::Version, Amount, Block, BlockHash,
+    CompactTarget, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxMerkleNode, TxOut, Witness,
+};
+use ckb_bitcoin_spv_prover::DummyService;
+use ckb_bitcoin_spv_tx_builder::build_update;
+
+use super::*;
+use crate::{error::Error, proof::TxProver, store::Store};
+
+// A height at the start of a difficulty period, which is easy to mine.
+const HEIGHT: u32 = 2016 * 400;
+// The easiest target, same as the `regtest`.
+const EASIEST_BITS: u32 = 0x207f_ffff;
+
+fn transaction(tag: u8) -> Transaction {
+    Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::null(),
+            script_sig: ScriptBuf::from_bytes(vec![tag]),
+            sequence: Sequence::MAX,
+            witness: Witness::default(),
+        }],
+        output: vec![TxOut {
+            value: Amount::from_sat(100_000),
+            script_pubkey: ScriptBuf::from_bytes(vec![0x51]),
+        }],
+    }
+}
+
+fn mine(prev_blockhash: BlockHash, txdata: Vec<Transaction>) -> Block {
+    let header = block::Header {
+        version: block::Version::ONE,
+        prev_blockhash,
+        merkle_root: TxMerkleNode::all_zeros(),
+        time: 1_700_000_000,
+        bits: CompactTarget::from_consensus(EASIEST_BITS),
+        nonce: 0,
+    };
+    let mut block = Block { header, txdata };
+    block.header.merkle_root = block.compute_merkle_root().unwrap();
+    while block.header.validate_pow(block.header.target()).is_err() {
+        block.header.nonce += 1;
+    }
+    block
+}
+
+/// Mines 3 blocks, the first one contains the transaction, then syncs them
+/// into the store and the SPV instance.
+fn prepare(ckb: &MockCkb, store: &mut Store, tx: Transaction) -> (Vec<Block>, Script) {
+    let mut blocks = vec![mine(BlockHash::all_zeros(), vec![transaction(0), tx])];
+    for tag in 1..3 {
+        let prev_blockhash = blocks.last().unwrap().block_hash();
+        blocks.push(mine(prev_blockhash, vec![transaction(tag)]));
+    }
+    let headers = blocks.iter().map(|block| block.header).collect::<Vec<_>>();
+
+    store.bootstrap(HEIGHT, headers[0]).unwrap();
+    store.append_headers(HEIGHT + 1, &headers[1..]).unwrap();
+
+    let type_script = setup_with_bootstrap(ckb, HEIGHT, headers[0]);
+    let mut service = DummyService::bootstrap(HEIGHT, headers[0]).unwrap();
+    let update = service.update(headers[1..].to_vec()).unwrap();
+    let instance = load_instance(ckb, &type_script);
+    let tx = build_update(&instance, service.tip_client(), &update).unwrap();
+    ckb.state.lock().unwrap().commit(&tx).unwrap();
+    (blocks, type_script)
+}
+
+#[test]
+fn prove_transaction() {
+    let ckb = MockCkb::default();
+    let mut store = Store::open(temp_path("proof")).unwrap();
+    let tx = transaction(0xff);
+    let txid = tx.txid();
+    let (blocks, type_script) = prepare(&ckb, &mut store, tx);
+    let instance = load_instance(&ckb, &type_script);
+
+    let mut prover = TxProver::default();
+    let proof = prover
+        .prove(&store.state().headers, &instance, &blocks[0], txid)
+        .unwrap();
+    assert_eq!(proof.height, HEIGHT);
+    assert_eq!(proof.tx_index, 1);
+    assert_eq!(proof.block_hash, blocks[0].block_hash());
+    assert_eq!(proof.client_id, instance.tip_client_id);
+    assert_eq!(proof.client_out_point, instance.tip_client().out_point);
+    assert_eq!(proof.client_tip_height, HEIGHT + 2);
+    assert_eq!(proof.confirmations, 2);
+
+    // The proof is verified by the tip client.
+    let client = packed::SpvClientReader::from_slice(&instance.tip_client().data)
+        .unwrap()
+        .to_entity();
+    client
+        .verify_transaction(
+            core::Hash::from_bytes_ref(&txid.to_byte_array()),
+            proof.tx_proof.as_reader(),
+            proof.confirmations,
+        )
+        .unwrap();
+
+    // The cached prover builds the same proof.
+    let cached = prover
+        .prove(&store.state().headers, &instance, &blocks[0], txid)
+        .unwrap();
+    assert_eq!(cached.tx_proof.as_slice(), proof.tx_proof.as_slice());
+}
+
+#[test]
+fn unprovable_transactions() {
+    let ckb = MockCkb::default();
+    let mut store = Store::open(temp_path("unprovable")).unwrap();
+    let tx = transaction(0xff);
+    let (blocks, type_script) = prepare(&ckb, &mut store, tx.clone());
+    let instance = load_instance(&ckb, &type_script);
+    let headers = &store.state().headers;
+    let mut prover = TxProver::default();
+
+    // The transaction is not in the block.
+    let result = prover.prove(headers, &instance, &blocks[1], tx.txid());
+    assert!(matches!(result, Err(Error::Unprovable(_))));
+
+    // The block is not in the SPV client.
+    let stale = mine(blocks[0].block_hash(), vec![transaction(0xfe), tx.clone()]);
+    let result = prover.prove(headers, &instance, &stale, tx.txid());
+    assert!(matches!(result, Err(Error::Unprovable(_))));
+}
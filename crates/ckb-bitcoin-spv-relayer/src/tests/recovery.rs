@@ -0,0 +1,126 @@
+//! Simulates crashes of the relayer when it's submitting a transaction, by
+//! panics, then restarts it from the store.
+
+use std::{
+    cell::Cell,
+    panic::{self, AssertUnwindSafe},
+};
+
+use ckb_bitcoin_spv_tx_builder::LiveCell;
+use ckb_types::packed::Byte32;
+
+use super::{mock::MockBitcoin, *};
+use crate::{source::RpcHeaderSource, store::Store, Relayed};
+
+#[derive(Clone, Copy)]
+enum Crash {
+    BeforeSend,
+    AfterSend,
+}
+
+/// Crashes at the first submission.
+struct CrashingChain {
+    inner: RpcChain,
+    crash: Cell<Option<Crash>>,
+}
+
+impl CkbChain for CrashingChain {
+    fn cells_by_type(&self, type_script: &Script) -> Result<Vec<LiveCell>> {
+        self.inner.cells_by_type(type_script)
+    }
+
+    fn cells_by_lock(&self, lock_script: &Script) -> Result<Vec<LiveCell>> {
+        self.inner.cells_by_lock(lock_script)
+    }
+
+    fn send_transaction(&self, tx: &TransactionView) -> Result<Byte32> {
+        match self.crash.take() {
+            Some(Crash::BeforeSend) => panic!("crash before sending"),
+            Some(Crash::AfterSend) => {
+                self.inner.send_transaction(tx)?;
+                panic!("crash after sending");
+            }
+            None => self.inner.send_transaction(tx),
+        }
+    }
+}
+
+fn crash_when_submitting(crash: Crash) {
+    let ckb = MockCkb::default();
+    let type_script = setup(&ckb);
+    let bitcoin = MockBitcoin::new(START_HEIGHT, main_headers(START_HEIGHT + 30));
+    let bitcoin_server = bitcoin.serve();
+    let ckb_server = ckb.serve();
+    let path = temp_path("recovery");
+
+    let start = |crash: Option<Crash>| {
+        let config = RelayerConfig {
+            type_script: type_script.clone(),
+            max_headers: 20,
+            conflict_retries: 0,
+        };
+        let source = RpcHeaderSource::new(bitcoin_server.url());
+        let chain = CrashingChain {
+            inner: RpcChain::new(ckb_server.url()),
+            crash: Cell::new(crash),
+        };
+        let store = Store::open(&path).unwrap();
+        Relayer::new(config, source, chain, NoFee)
+            .with_store(store)
+            .unwrap()
+    };
+    let txs_count = || ckb.state.lock().unwrap().txs.len();
+
+    let mut relayer = start(Some(crash));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| relayer.run_once()));
+    assert!(result.is_err());
+    drop(relayer);
+    let committed = match crash {
+        Crash::BeforeSend => 0,
+        Crash::AfterSend => 1,
+    };
+    assert_eq!(txs_count(), committed);
+
+    // Restarts, the prover is restored from the store.
+    let mut relayer = start(None);
+    let state = relayer.store().unwrap().state();
+    assert_eq!(state.headers.tip_height(), Some(START_HEIGHT + 20));
+    let pending_tx_hash = state.pending.as_ref().unwrap().tx.hash();
+
+    if let Crash::BeforeSend = crash {
+        // The same transaction is submitted again.
+        let relayed = relayer.run_once().unwrap();
+        let expected = Relayed::Pending {
+            tx_hash: pending_tx_hash.clone(),
+        };
+        assert_eq!(relayed, expected);
+        assert_eq!(txs_count(), 1);
+    }
+    let relayed = relayer.run_once().unwrap();
+    assert!(matches!(
+        relayed,
+        Relayed::Submitted { tip_height, .. } if tip_height == START_HEIGHT + 30
+    ));
+    assert_eq!(relayer.run_once().unwrap(), Relayed::UpToDate);
+    assert!(relayer.store().unwrap().state().pending.is_none());
+
+    // Nothing is submitted twice.
+    let state = ckb.state.lock().unwrap();
+    assert_eq!(state.txs.len(), 2);
+    assert_eq!(state.txs[0].hash(), pending_tx_hash);
+    drop(state);
+    let instance = load_instance(&ckb, &type_script);
+    let stored = relayer.store().unwrap().state().instance.clone().unwrap();
+    assert_eq!(stored.info_out_point, instance.info.out_point);
+    assert_eq!(stored.tip_height, START_HEIGHT + 30);
+}
+
+#[test]
+fn crash_before_sending() {
+    crash_when_submitting(Crash::BeforeSend);
+}
+
+#[test]
+fn crash_after_sending() {
+    crash_when_submitting(Crash::AfterSend);
+}
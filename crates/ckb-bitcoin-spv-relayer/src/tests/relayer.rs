@@ -0,0 +1,253 @@
+use std::cell::Cell;
+
+use bitcoin::hashes::Hash as _;
+use ckb_bitcoin_spv_model::ring;
+use ckb_crypto::secp::{Generator, Signature};
+use ckb_types::packed::{CellDep, WitnessArgs};
+
+use super::{mock::MockBitcoin, *};
+use crate::{
+    fee::{sighash_all_message, RewardPoolPayer, Secp256k1Payer},
+    source::RpcHeaderSource,
+    Relayed,
+};
+
+#[test]
+fn update_from_rpc() {
+    let ckb = MockCkb::default();
+    let type_script = setup(&ckb);
+    let bitcoin = MockBitcoin::new(START_HEIGHT, main_headers(START_HEIGHT + 30));
+    let bitcoin_server = bitcoin.serve();
+    let source = RpcHeaderSource::new(bitcoin_server.url());
+    let payer = RewardPoolPayer { fee: FEE };
+    let (mut relayer, _ckb_server) = relayer(source, &ckb, type_script.clone(), 20, payer);
+
+    for (round, expected_height) in [START_HEIGHT + 20, START_HEIGHT + 30]
+        .into_iter()
+        .enumerate()
+    {
+        match relayer.run_once().unwrap() {
+            Relayed::Submitted {
+                operation,
+                tip_height,
+                ..
+            } => {
+                assert_eq!(operation, Operation::Update);
+                assert_eq!(tip_height, expected_height);
+            }
+            relayed => panic!("round {round}: {relayed:?}"),
+        }
+        let instance = load_instance(&ckb, &type_script);
+        assert_eq!(usize::from(instance.tip_client_id), round + 1);
+        let client = tip_client(&instance);
+        let packed_client: packed::SpvClient = client.pack();
+        let max_height: u32 = packed_client.headers_mmr_root().max_height().unpack();
+        assert_eq!(max_height, expected_height);
+        let fee = FEE * (round as u64 + 1);
+        assert_eq!(pool_capacity(&ckb), POOL_CAPACITY - fee);
+    }
+    assert_eq!(relayer.run_once().unwrap(), Relayed::UpToDate);
+    assert_eq!(ckb.state.lock().unwrap().txs.len(), 3);
+}
+
+#[test]
+fn update_from_files() {
+    let ckb = MockCkb::default();
+    let type_script = setup(&ckb);
+    let (mut relayer, _ckb_server) = relayer(main_chain(), &ckb, type_script.clone(), 50, NoFee);
+
+    for round in 1..=3 {
+        let relayed = relayer.run_once().unwrap();
+        let expected_height = START_HEIGHT + 50 * round;
+        assert!(matches!(
+            relayed,
+            Relayed::Submitted { tip_height, .. } if tip_height == expected_height
+        ));
+    }
+    let instance = load_instance(&ckb, &type_script);
+    let header = main_chain().header(START_HEIGHT + 150).unwrap();
+    assert_eq!(
+        tip_block_hash(&instance),
+        header.block_hash().to_byte_array()
+    );
+}
+
+// Records the highest header requested from the main chain.
+struct HighestHeader(Cell<u32>);
+
+impl HeaderSource for &HighestHeader {
+    fn tip_height(&self) -> Result<u32> {
+        main_chain().tip_height()
+    }
+
+    fn header(&self, height: u32) -> Result<core::Header> {
+        self.0.set(self.0.get().max(height));
+        main_chain().header(height)
+    }
+}
+
+#[test]
+fn update_without_max_headers() {
+    let ckb = MockCkb::default();
+    let type_script = setup(&ckb);
+    let source = HighestHeader(Cell::new(0));
+    let (mut relayer, _ckb_server) = relayer(&source, &ckb, type_script.clone(), 0, NoFee);
+
+    // At least 1 header is sent, and the headers far beyond it are never
+    // downloaded.
+    for round in 1..=3 {
+        let relayed = relayer.run_once().unwrap();
+        let expected_height = START_HEIGHT + round;
+        assert!(matches!(
+            relayed,
+            Relayed::Submitted { tip_height, .. } if tip_height == expected_height
+        ));
+        assert_eq!(source.0.get(), expected_height);
+    }
+}
+
+#[test]
+fn reorg_from_rpc() {
+    let ckb = MockCkb::default();
+    let type_script = setup(&ckb);
+    let end_height = STALE_HEIGHT + 4;
+    let (bitcoin_server, stale_tip_client_id) = sync_to_stale_tip(&ckb, &type_script, end_height);
+    let source = RpcHeaderSource::new(bitcoin_server.url());
+    let (mut relayer, _ckb_server) = relayer(source, &ckb, type_script.clone(), 200, NoFee);
+
+    let Relayed::Submitted { operation, .. } = relayer.run_once().unwrap() else {
+        panic!("the reorg is not submitted");
+    };
+    let Operation::Reorg { fork_client_id } = operation else {
+        panic!("the operation should be a reorg, but got {operation:?}");
+    };
+    // Known Issue #2: at least 2 clients are replaced.
+    assert_ne!(
+        ring::next_client_id(fork_client_id, CLIENTS_COUNT),
+        stale_tip_client_id
+    );
+
+    while relayer.run_once().unwrap() != Relayed::UpToDate {}
+    let instance = load_instance(&ckb, &type_script);
+    let header = main_chain().header(end_height).unwrap();
+    assert_eq!(
+        tip_block_hash(&instance),
+        header.block_hash().to_byte_array()
+    );
+}
+
+#[test]
+fn reorg_with_small_max_headers() {
+    let ckb = MockCkb::default();
+    let type_script = setup(&ckb);
+    let end_height = STALE_HEIGHT + 4;
+    let (bitcoin_server, _) = sync_to_stale_tip(&ckb, &type_script, end_height);
+    let source = RpcHeaderSource::new(bitcoin_server.url());
+    let (mut relayer, _ckb_server) = relayer(source, &ckb, type_script.clone(), 1, NoFee);
+
+    // The reorg goes beyond the old tip, though it contains more headers
+    // than the max headers.
+    let Relayed::Submitted {
+        operation,
+        tip_height,
+        ..
+    } = relayer.run_once().unwrap()
+    else {
+        panic!("the reorg is not submitted");
+    };
+    assert!(matches!(operation, Operation::Reorg { .. }));
+    assert_eq!(tip_height, STALE_HEIGHT + 1);
+
+    while relayer.run_once().unwrap() != Relayed::UpToDate {}
+    let instance = load_instance(&ckb, &type_script);
+    let header = main_chain().header(end_height).unwrap();
+    assert_eq!(
+        tip_block_hash(&instance),
+        header.block_hash().to_byte_array()
+    );
+}
+
+#[test]
+fn reorg_paid_by_secp256k1_payer() {
+    let ckb = MockCkb::default();
+    let type_script = setup(&ckb);
+    let end_height = STALE_HEIGHT + 4;
+    let (bitcoin_server, _) = sync_to_stale_tip(&ckb, &type_script, end_height);
+
+    let payer = Secp256k1Payer {
+        fee: FEE,
+        private_key: Generator::random_privkey(),
+        cell_dep: CellDep::default(),
+    };
+    let pubkey = payer.private_key.pubkey().unwrap();
+    let lock_script = payer.lock_script().unwrap();
+    let fee_cell = CellOutput::new_builder()
+        .capacity(POOL_CAPACITY.pack())
+        .lock(lock_script.clone())
+        .build();
+    ckb.state.lock().unwrap().add_cell(fee_cell, Vec::new());
+    let source = RpcHeaderSource::new(bitcoin_server.url());
+    let (mut relayer, _ckb_server) = relayer(source, &ckb, type_script.clone(), 200, payer);
+
+    let Relayed::Submitted { operation, .. } = relayer.run_once().unwrap() else {
+        panic!("the reorg is not submitted");
+    };
+    assert!(matches!(operation, Operation::Reorg { .. }));
+
+    // The fee cell is the last input, and it's signed by the relayer.
+    let tx = ckb.state.lock().unwrap().txs.last().unwrap().clone();
+    let index = tx.inputs().len() - 1;
+    let witness = WitnessArgs::from_slice(&tx.witnesses().get(index).unwrap().raw_data()).unwrap();
+    let signature = witness.lock().to_opt().unwrap().raw_data();
+    let zeroed_tx = {
+        let zeroed_witness = witness
+            .as_builder()
+            .lock(Some(Bytes::from(vec![0u8; 65])).pack())
+            .build();
+        let mut witnesses = tx.witnesses().into_iter().collect::<Vec<_>>();
+        witnesses[index] = zeroed_witness.as_bytes().pack();
+        tx.as_advanced_builder().set_witnesses(witnesses).build()
+    };
+    let message = sighash_all_message(&zeroed_tx, index);
+    let signature = Signature::from_slice(&signature).unwrap();
+    assert_eq!(signature.recover(&message).unwrap(), pubkey);
+
+    let fee_capacity: u64 = ckb
+        .state
+        .lock()
+        .unwrap()
+        .cells
+        .iter()
+        .filter(|cell| cell.output.lock().as_slice() == lock_script.as_slice())
+        .map(|cell| Unpack::<u64>::unpack(&cell.output.capacity()))
+        .sum();
+    assert_eq!(fee_capacity, POOL_CAPACITY - FEE);
+    // The reward pool is untouched.
+    assert_eq!(pool_capacity(&ckb), POOL_CAPACITY);
+}
+
+// Synchronizes the SPV instance to a chain which has a stale block at its
+// tip, then replaces the chain by the main chain, which is longer.
+//
+// Returns the server of the Bitcoin node and the id of the stale tip client.
+fn sync_to_stale_tip(ckb: &MockCkb, type_script: &Script, end_height: u32) -> (MockServer, u8) {
+    let mut headers = main_headers(STALE_HEIGHT - 1);
+    let stale_header = FileHeaderSource::new(data_dir("stale"))
+        .header(STALE_HEIGHT)
+        .unwrap();
+    headers.push(stale_header);
+    let bitcoin = MockBitcoin::new(START_HEIGHT, headers);
+    let bitcoin_server = bitcoin.serve();
+    let source = RpcHeaderSource::new(bitcoin_server.url());
+    let (mut relayer, _ckb_server) = relayer(source, ckb, type_script.clone(), 200, NoFee);
+
+    while relayer.run_once().unwrap() != Relayed::UpToDate {}
+    let instance = load_instance(ckb, type_script);
+    assert_eq!(
+        tip_block_hash(&instance),
+        stale_header.block_hash().to_byte_array()
+    );
+
+    bitcoin.set_chain(START_HEIGHT, main_headers(end_height));
+    (bitcoin_server, instance.tip_client_id)
+}
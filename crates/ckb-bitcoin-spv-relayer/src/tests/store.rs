@@ -0,0 +1,122 @@
+use std::{fs, io::Write as _};
+
+use ckb_types::{core::TransactionBuilder, packed::OutPoint};
+
+use super::*;
+use crate::{
+    error::Error,
+    store::{InstanceState, Store},
+};
+
+fn append_to(path: &PathBuf, bytes: &[u8]) {
+    let mut file = fs::OpenOptions::new().append(true).open(path).unwrap();
+    file.write_all(bytes).unwrap();
+}
+
+fn out_point(index: u32) -> OutPoint {
+    OutPoint::new_builder()
+        .tx_hash(H256([7u8; 32]).pack())
+        .index(index.pack())
+        .build()
+}
+
+#[test]
+fn reopen() {
+    let path = temp_path("reopen");
+    let headers = main_headers(START_HEIGHT + 10);
+    let tx = TransactionBuilder::default()
+        .input(CellInput::new(out_point(0), 0))
+        .build();
+    let instance = InstanceState {
+        info_out_point: out_point(0),
+        tip_client_id: 1,
+        tip_height: START_HEIGHT + 10,
+    };
+
+    {
+        let mut store = Store::open(&path).unwrap();
+        store.bootstrap(START_HEIGHT, headers[0]).unwrap();
+        store
+            .append_headers(START_HEIGHT + 1, &headers[1..])
+            .unwrap();
+        store.rollback(START_HEIGHT + 5).unwrap();
+        store
+            .append_headers(START_HEIGHT + 6, &headers[6..])
+            .unwrap();
+        store.set_instance(instance.clone()).unwrap();
+        store.set_pending(&tx, out_point(0)).unwrap();
+    }
+
+    for _ in 0..2 {
+        let store = Store::open(&path).unwrap();
+        let state = store.state();
+        assert_eq!(state.headers.start_height, START_HEIGHT);
+        assert_eq!(state.headers.headers, headers);
+        assert_eq!(state.instance.as_ref(), Some(&instance));
+        let pending = state.pending.as_ref().unwrap();
+        assert_eq!(pending.tx.hash(), tx.hash());
+        assert_eq!(pending.info_out_point, out_point(0));
+    }
+
+    let mut store = Store::open(&path).unwrap();
+    store.resolve_pending(true).unwrap();
+    store.bootstrap(START_HEIGHT + 3, headers[3]).unwrap();
+    drop(store);
+    let store = Store::open(&path).unwrap();
+    assert!(store.state().pending.is_none());
+    assert_eq!(store.state().headers.start_height, START_HEIGHT + 3);
+    assert_eq!(store.state().headers.tip_height(), Some(START_HEIGHT + 3));
+}
+
+#[test]
+fn discard_torn_record() {
+    let path = temp_path("torn");
+    let headers = main_headers(START_HEIGHT + 2);
+    {
+        let mut store = Store::open(&path).unwrap();
+        store.bootstrap(START_HEIGHT, headers[0]).unwrap();
+    }
+    // Crashes when the second record is being written.
+    append_to(&path, br#"{"kind":"headers","start_height":822529,"hea"#);
+
+    let mut store = Store::open(&path).unwrap();
+    assert_eq!(store.state().headers.tip_height(), Some(START_HEIGHT));
+    store
+        .append_headers(START_HEIGHT + 1, &headers[1..])
+        .unwrap();
+    drop(store);
+
+    let store = Store::open(&path).unwrap();
+    assert_eq!(store.state().headers.headers, headers);
+}
+
+#[test]
+fn reject_corrupted_journal() {
+    let path = temp_path("corrupted");
+    {
+        let mut store = Store::open(&path).unwrap();
+        let header = main_chain().header(START_HEIGHT).unwrap();
+        store.bootstrap(START_HEIGHT, header).unwrap();
+    }
+    append_to(&path, b"not a record\n");
+    let result = Store::open(&path);
+    assert!(matches!(result, Err(Error::Store(_))));
+}
+
+#[test]
+fn reject_discontinuous_headers() {
+    let path = temp_path("discontinuous");
+    let headers = main_headers(START_HEIGHT + 5);
+    let mut store = Store::open(&path).unwrap();
+
+    let result = store.append_headers(START_HEIGHT, &headers);
+    assert!(matches!(result, Err(Error::Store(_))));
+    store.bootstrap(START_HEIGHT, headers[0]).unwrap();
+    let result = store.append_headers(START_HEIGHT + 2, &headers[2..]);
+    assert!(matches!(result, Err(Error::Store(_))));
+    drop(store);
+
+    // The rejected records are not persisted.
+    let store = Store::open(&path).unwrap();
+    assert_eq!(store.state().headers.tip_height(), Some(START_HEIGHT));
+}
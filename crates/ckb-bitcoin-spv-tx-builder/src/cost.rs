@@ -0,0 +1,252 @@
+//! Estimate the capacity and the cost of an SPV instance.
+//!
+//! The occupied capacities are exact, since they only depend on the sizes
+//! of the scripts and the data.
+//!
+//! The costs of transactions are estimated from transactions which are
+//! built by the builders, with dummy SPV updates of the same sizes, and one
+//! more cell, which is locked by the same lock, to pay the fee, e.g. the
+//! reward pool of the permissionless update lock, whose data is the type hash
+//! of the SPV instance. The witnesses of the lock script are not included.
+//!
+//! The cycles are estimated by a linear model, since they couldn't be known
+//! without running the scripts. The default model is checked against the
+//! cycles of transactions which are run by `ckb-testtool`, see the test
+//! `cycles_model` in the `tests` crate.
+
+use ckb_bitcoin_spv_verifier::types::{
+    core,
+    packed::{self, SpvTypeArgsReader},
+    prelude::{Pack as VPack, Unpack as VUnpack},
+};
+use ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, TransactionView},
+    packed::{CellInput, CellOutput, OutPoint, Script},
+    prelude::*,
+};
+
+use crate::{
+    build_reorg, build_update,
+    error::{Error, Result},
+    LiveCell, SpvInstance,
+};
+
+// Same as the `DEFAULT_BYTES_PER_CYCLES` of CKB, which converts cycles to
+// the weight of a transaction.
+const BYTES_PER_CYCLES: f64 = 0.000_170_571_4;
+
+/// The minimal occupied capacities of the cells of an SPV instance, in
+/// shannons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpvCapacity {
+    pub info: u64,
+    pub client: u64,
+    pub clients_count: u8,
+}
+
+/// A linear model to estimate the cycles of a transaction.
+///
+/// The base covers the lock script and the SPV type script, the SPV type
+/// script verifies each new header and writes each new client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CyclesModel {
+    pub base: u64,
+    pub per_header: u64,
+    pub per_client: u64,
+}
+
+/// Parameters to estimate the cost of transactions.
+pub struct CostParams {
+    /// The lock script of all SPV cells.
+    pub lock_script: Script,
+    /// The SPV type script, its args should be `SpvTypeArgs`.
+    pub type_script: Script,
+    /// The count of headers in the MMR of the SPV client, which determines
+    /// the size of the MMR proof.
+    pub mmr_leaves: u32,
+    /// The fee rate, in shannons per KB.
+    pub fee_rate: u64,
+    pub cycles: CyclesModel,
+}
+
+/// The estimated cost of a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxCost {
+    /// The serialized size in a block, in bytes.
+    pub size: u64,
+    pub cycles: u64,
+    /// The minimal fee under the fee rate, in shannons.
+    pub fee: u64,
+}
+
+impl SpvCapacity {
+    /// The total capacity of the SPV info cell and all SPV client cells.
+    pub fn total(&self) -> u64 {
+        self.info + self.client * u64::from(self.clients_count)
+    }
+}
+
+impl Default for CyclesModel {
+    /// An upper bound of the measured cycles, but not more than twice of
+    /// them; re-calibrate it when the contracts are changed.
+    fn default() -> Self {
+        Self {
+            base: 1_000_000,
+            per_header: 100_000,
+            per_client: 50_000,
+        }
+    }
+}
+
+impl CyclesModel {
+    pub fn cycles(&self, headers_count: u32, clients_count: u8) -> u64 {
+        self.base
+            .saturating_add(self.per_header.saturating_mul(u64::from(headers_count)))
+            .saturating_add(self.per_client.saturating_mul(u64::from(clients_count)))
+    }
+}
+
+/// Calculates the minimal occupied capacities of the cells of an SPV
+/// instance, under the lock script and the SPV type script.
+pub fn occupied_capacity(lock_script: &Script, type_script: &Script) -> Result<SpvCapacity> {
+    let clients_count = clients_count(type_script)?;
+    let info_data_len = packed::SpvInfo::new_builder().build().as_slice().len();
+    let client_data_len = packed::SpvClient::new_builder().build().as_slice().len();
+    Ok(SpvCapacity {
+        info: cell_capacity(lock_script, type_script, info_data_len),
+        client: cell_capacity(lock_script, type_script, client_data_len),
+        clients_count,
+    })
+}
+
+/// Estimates the cost of a transaction to update an SPV instance with
+/// `headers_count` new headers.
+pub fn update_cost(params: &CostParams, headers_count: u32) -> Result<TxCost> {
+    let instance = dummy_instance(params)?;
+    let tx = build_update(
+        &instance,
+        dummy_client(),
+        &dummy_update(params, headers_count),
+    )?;
+    Ok(tx_cost(params, tx, headers_count, 1))
+}
+
+/// Estimates the cost of a transaction to reorg an SPV instance, which
+/// replaces `replaced_clients` clients with `headers_count` new headers.
+pub fn reorg_cost(params: &CostParams, replaced_clients: u8, headers_count: u32) -> Result<TxCost> {
+    let instance = dummy_instance(params)?;
+    let clients_count = instance.clients_count();
+    if replaced_clients >= clients_count {
+        return Err(Error::ReorgClientsTooMany(replaced_clients));
+    }
+    // The tip client is the last one, so the fork client is always before
+    // it, without wrapping around.
+    let fork_client_id = instance.tip_client_id - replaced_clients;
+    let tx = build_reorg(
+        &instance,
+        fork_client_id,
+        dummy_client(),
+        &dummy_update(params, headers_count),
+    )?;
+    Ok(tx_cost(params, tx, headers_count, replaced_clients))
+}
+
+fn clients_count(type_script: &Script) -> Result<u8> {
+    let args = type_script.args().raw_data();
+    let type_args: core::SpvTypeArgs = SpvTypeArgsReader::from_slice(&args)
+        .map_err(|_| Error::TypeArgsMalformed)?
+        .unpack();
+    if type_args.clients_count < 3 {
+        return Err(Error::ClientsCountTooSmall(type_args.clients_count));
+    }
+    Ok(type_args.clients_count)
+}
+
+fn cell_capacity(lock_script: &Script, type_script: &Script, data_len: usize) -> u64 {
+    let output = CellOutput::new_builder()
+        .lock(lock_script.clone())
+        .type_(Some(type_script.clone()).pack())
+        .build();
+    Capacity::bytes(data_len)
+        .and_then(|data_capacity| output.occupied_capacity(data_capacity))
+        .expect("the capacity of an SPV cell overflows")
+        .as_u64()
+}
+
+fn dummy_client() -> core::SpvClient {
+    packed::SpvClient::new_builder().build().unpack()
+}
+
+fn dummy_update(params: &CostParams, headers_count: u32) -> packed::SpvUpdate {
+    let headers = packed::HeaderVec::new_builder()
+        .set(vec![packed::Header::default(); headers_count as usize])
+        .build();
+    // The MMR proof of new headers has about one item for each level.
+    let proof_items = (u32::BITS - params.mmr_leaves.leading_zeros()) as usize;
+    let proof = packed::MmrProof::new_builder()
+        .set(vec![packed::HeaderDigest::default(); proof_items])
+        .build();
+    packed::SpvUpdate::new_builder()
+        .headers(headers)
+        .new_headers_mmr_proof(proof)
+        .build()
+}
+
+// An SPV instance whose cells have the minimal occupied capacities, and the
+// last client is the tip client.
+fn dummy_instance(params: &CostParams) -> Result<SpvInstance> {
+    let capacity = occupied_capacity(&params.lock_script, &params.type_script)?;
+    let cell = |index: u32, capacity: u64, data: Bytes| {
+        let output = CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(params.lock_script.clone())
+            .type_(Some(params.type_script.clone()).pack())
+            .build();
+        LiveCell {
+            out_point: OutPoint::new(Default::default(), index),
+            output,
+            data,
+        }
+    };
+    let tip_client_id = capacity.clients_count - 1;
+    let info = packed::SpvInfo::new_builder()
+        .tip_client_id(tip_client_id.into())
+        .build();
+    let mut cells = vec![cell(0, capacity.info, info.as_bytes())];
+    for id in 0..capacity.clients_count {
+        let mut client = dummy_client();
+        client.id = id;
+        let packed_client: packed::SpvClient = client.pack();
+        cells.push(cell(
+            u32::from(id) + 1,
+            capacity.client,
+            packed_client.as_bytes(),
+        ));
+    }
+    SpvInstance::load(cells)
+}
+
+fn tx_cost(
+    params: &CostParams,
+    tx: TransactionView,
+    headers_count: u32,
+    clients_count: u8,
+) -> TxCost {
+    let fee_cell = CellOutput::new_builder()
+        .lock(params.lock_script.clone())
+        .build();
+    let fee_cell_data = params.type_script.calc_script_hash();
+    let tx = tx
+        .as_advanced_builder()
+        .input(CellInput::new(OutPoint::new(Default::default(), 0), 0))
+        .output(fee_cell)
+        .output_data(fee_cell_data.as_bytes().pack())
+        .build();
+    let size = tx.data().serialized_size_in_block() as u64;
+    let cycles = params.cycles.cycles(headers_count, clients_count);
+    let weight = size.max((cycles as f64 * BYTES_PER_CYCLES) as u64);
+    // Same as the `FeeRate::fee` of CKB.
+    let fee = params.fee_rate.saturating_mul(weight) / 1000;
+    TxCost { size, cycles, fee }
+}
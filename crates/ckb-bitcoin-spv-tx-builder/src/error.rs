@@ -0,0 +1,58 @@
+use std::{fmt, result};
+
+pub type Result<T> = result::Result<T, Error>;
+
+/// Errors when build transactions for an SPV instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A cell doesn't have any type script.
+    TypeScriptNotFound,
+    /// Cells have different type scripts.
+    TypeScriptMismatch,
+    /// The args of the type script is not `SpvTypeArgs`.
+    TypeArgsMalformed,
+    /// The data of a cell is neither `SpvInfo` nor `SpvClient`.
+    CellDataMalformed,
+    InfoNotFound,
+    InfoDuplicated,
+    ClientNotFound(u8),
+    ClientDuplicated(u8),
+    /// An SPV instance requires at least 3 clients.
+    ClientsCountTooSmall(u8),
+    /// The bootstrap could not initialize an SPV client.
+    BootstrapFailed,
+    /// A reorg requires at least 2 clients, otherwise, it's an update.
+    ReorgClientsNotEnough,
+    /// A reorg couldn't replace all clients, the fork client is kept.
+    ReorgClientsTooMany(u8),
+    /// Only SPV instances for the testnet could be reset.
+    ResetNotAllowed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TypeScriptNotFound => write!(f, "the cell doesn't have any type script"),
+            Self::TypeScriptMismatch => write!(f, "cells have different type scripts"),
+            Self::TypeArgsMalformed => write!(f, "the args of the SPV type script is malformed"),
+            Self::CellDataMalformed => write!(f, "the data of the SPV cell is malformed"),
+            Self::InfoNotFound => write!(f, "the SPV info cell is not found"),
+            Self::InfoDuplicated => write!(f, "more than one SPV info cells"),
+            Self::ClientNotFound(id) => write!(f, "the SPV client cell (id={id}) is not found"),
+            Self::ClientDuplicated(id) => {
+                write!(f, "more than one SPV client cells (id={id})")
+            }
+            Self::ClientsCountTooSmall(count) => {
+                write!(f, "the count of SPV clients ({count}) is too small")
+            }
+            Self::BootstrapFailed => write!(f, "failed to initialize the SPV client"),
+            Self::ReorgClientsNotEnough => write!(f, "a reorg requires at least 2 clients"),
+            Self::ReorgClientsTooMany(count) => {
+                write!(f, "a reorg couldn't replace {count} clients")
+            }
+            Self::ResetNotAllowed => write!(f, "only SPV instances for the testnet could be reset"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
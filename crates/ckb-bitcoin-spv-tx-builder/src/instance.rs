@@ -0,0 +1,138 @@
+use ckb_bitcoin_spv_verifier::types::{
+    core,
+    packed::{SpvClientReader, SpvInfoReader, SpvTypeArgsReader},
+    prelude::Unpack as VUnpack,
+};
+use ckb_types::{
+    bytes::Bytes,
+    core::DepType,
+    packed::{CellDep, CellInput, CellOutput, OutPoint, Script},
+    prelude::*,
+};
+
+use crate::error::{Error, Result};
+
+/// A live cell on CKB.
+#[derive(Debug, Clone)]
+pub struct LiveCell {
+    pub out_point: OutPoint,
+    pub output: CellOutput,
+    pub data: Bytes,
+}
+
+/// All live cells of an SPV instance.
+pub struct SpvInstance {
+    pub type_script: Script,
+    pub type_args: core::SpvTypeArgs,
+    pub info: LiveCell,
+    pub tip_client_id: u8,
+    /// The client cells, sorted by their ids.
+    pub clients: Vec<LiveCell>,
+}
+
+impl LiveCell {
+    pub fn as_input(&self) -> CellInput {
+        CellInput::new_builder()
+            .previous_output(self.out_point.clone())
+            .build()
+    }
+
+    pub fn as_cell_dep(&self) -> CellDep {
+        CellDep::new_builder()
+            .out_point(self.out_point.clone())
+            .dep_type(DepType::Code.into())
+            .build()
+    }
+}
+
+impl SpvInstance {
+    /// Loads an SPV instance from its live cells, in any order.
+    ///
+    /// All cells should have the same type script, and all cells of the SPV
+    /// instance should be provided.
+    pub fn load(cells: Vec<LiveCell>) -> Result<Self> {
+        let type_script = cells
+            .first()
+            .ok_or(Error::InfoNotFound)?
+            .output
+            .type_()
+            .to_opt()
+            .ok_or(Error::TypeScriptNotFound)?;
+        let type_args: core::SpvTypeArgs =
+            SpvTypeArgsReader::from_slice(&type_script.args().raw_data())
+                .map_err(|_| Error::TypeArgsMalformed)?
+                .unpack();
+        let clients_count = type_args.clients_count;
+        if clients_count < 3 {
+            return Err(Error::ClientsCountTooSmall(clients_count));
+        }
+
+        let mut info_opt = None;
+        let mut clients_opts = vec![None; usize::from(clients_count)];
+        for cell in cells {
+            let is_same_type = cell
+                .output
+                .type_()
+                .to_opt()
+                .is_some_and(|script| script.as_slice() == type_script.as_slice());
+            if !is_same_type {
+                return Err(Error::TypeScriptMismatch);
+            }
+            if let Ok(info) = SpvInfoReader::from_slice(&cell.data) {
+                if info_opt.is_some() {
+                    return Err(Error::InfoDuplicated);
+                }
+                let tip_client_id: u8 = info.tip_client_id().into();
+                info_opt = Some((cell, tip_client_id));
+            } else if let Ok(client) = SpvClientReader::from_slice(&cell.data) {
+                let id: u8 = client.id().into();
+                let slot = clients_opts
+                    .get_mut(usize::from(id))
+                    .ok_or(Error::CellDataMalformed)?;
+                if slot.is_some() {
+                    return Err(Error::ClientDuplicated(id));
+                }
+                *slot = Some(cell);
+            } else {
+                return Err(Error::CellDataMalformed);
+            }
+        }
+
+        let (info, tip_client_id) = info_opt.ok_or(Error::InfoNotFound)?;
+        if tip_client_id >= clients_count {
+            return Err(Error::ClientNotFound(tip_client_id));
+        }
+        let clients = clients_opts
+            .into_iter()
+            .enumerate()
+            .map(|(id, cell_opt)| cell_opt.ok_or(Error::ClientNotFound(id as u8)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            type_script,
+            type_args,
+            info,
+            tip_client_id,
+            clients,
+        })
+    }
+
+    pub fn clients_count(&self) -> u8 {
+        self.type_args.clients_count
+    }
+
+    pub fn client(&self, id: u8) -> Result<&LiveCell> {
+        self.clients
+            .get(usize::from(id))
+            .ok_or(Error::ClientNotFound(id))
+    }
+
+    pub fn tip_client(&self) -> &LiveCell {
+        &self.clients[usize::from(self.tip_client_id)]
+    }
+
+    /// All cells of the SPV instance: the info cell, then the client cells
+    /// which are sorted by their ids.
+    pub fn cells(&self) -> impl Iterator<Item = &LiveCell> {
+        Some(&self.info).into_iter().chain(self.clients.iter())
+    }
+}
@@ -0,0 +1,27 @@
+//! Build transactions to operate Bitcoin SPV instances on CKB.
+//!
+//! A Bitcoin SPV instance is a group of cells which are managed by the CKB
+//! Bitcoin SPV type script: one SPV info cell and several SPV client cells,
+//! the client cells form a ring.
+//!
+//! Each builder takes the live cells of an SPV instance, and the output of
+//! the prover, then returns a transaction skeleton which only contains the
+//! SPV cells, the SPV cell deps and the SPV witness.
+//!
+//! The SPV info cell is always the first output, and the SPV witness is put
+//! at the same index, so more inputs, outputs and cell deps, e.g. the cells
+//! to pay the fee, should be appended to the skeleton, not be inserted.
+
+pub mod cost;
+pub mod error;
+mod instance;
+mod operations;
+
+#[cfg(test)]
+mod tests;
+
+pub use ckb_bitcoin_spv_model::ring;
+pub use instance::{LiveCell, SpvInstance};
+pub use operations::{
+    build_create, build_destroy, build_reorg, build_reset, build_update, CreateParams,
+};
@@ -0,0 +1,96 @@
+use ckb_bitcoin_spv_verifier::types::{core, packed, prelude::Pack as VPack};
+use ckb_hash::{new_blake2b, BLAKE2B_LEN};
+use ckb_types::{
+    core::{TransactionBuilder, TransactionView},
+    packed::{CellInput, CellOutput, Script},
+    prelude::*,
+};
+
+use super::spv_witness;
+use crate::error::{Error, Result};
+
+/// Parameters to create an SPV instance.
+pub struct CreateParams {
+    /// The first input of the transaction, which determines the type id.
+    pub first_input: CellInput,
+    /// The lock script of all SPV cells.
+    pub lock_script: Script,
+    /// The SPV type script, its args will be replaced.
+    pub type_script: Script,
+    pub clients_count: u8,
+    pub flags: u8,
+    /// The capacity of each SPV cell.
+    pub cell_capacity: u64,
+    pub bootstrap: packed::SpvBootstrap,
+}
+
+/// Builds a transaction to create an SPV instance.
+///
+/// Outputs are the SPV info cell, then the SPV client cells, which are
+/// sorted by their ids.
+pub fn build_create(params: CreateParams) -> Result<TransactionView> {
+    let CreateParams {
+        first_input,
+        lock_script,
+        type_script,
+        clients_count,
+        flags,
+        cell_capacity,
+        bootstrap,
+    } = params;
+    if clients_count < 3 {
+        return Err(Error::ClientsCountTooSmall(clients_count));
+    }
+    let cells_count = usize::from(clients_count) + 1;
+
+    let type_script = {
+        let type_id_array = calculate_type_id(&first_input, cells_count);
+        let type_id = core::Hash::from_bytes_ref(&type_id_array);
+        let args = packed::SpvTypeArgs::new_builder()
+            .type_id(type_id.pack())
+            .clients_count(clients_count.into())
+            .flags(flags.into())
+            .build();
+        type_script
+            .as_builder()
+            .args(args.as_slice().pack())
+            .build()
+    };
+    let output = CellOutput::new_builder()
+        .capacity(cell_capacity.pack())
+        .lock(lock_script)
+        .type_(Some(type_script).pack())
+        .build();
+
+    let outputs_data = {
+        let spv_info = packed::SpvInfo::new_builder().build();
+        let mut outputs_data = vec![spv_info.as_bytes()];
+        let mut client = bootstrap
+            .initialize_spv_client()
+            .map_err(|_| Error::BootstrapFailed)?;
+        for id in 0..clients_count {
+            client.id = id;
+            let packed_client: packed::SpvClient = client.pack();
+            outputs_data.push(packed_client.as_bytes());
+        }
+        outputs_data
+    };
+
+    let tx = TransactionBuilder::default()
+        .input(first_input)
+        .outputs(vec![output; cells_count])
+        .outputs_data(outputs_data.pack())
+        .witness(spv_witness(bootstrap.as_slice()).pack())
+        .build();
+    Ok(tx)
+}
+
+// Same as the type id in the SPV type script.
+fn calculate_type_id(input: &CellInput, outputs_count: usize) -> [u8; BLAKE2B_LEN] {
+    let mut blake2b = new_blake2b();
+    blake2b.update(input.as_slice());
+    blake2b.update(&(outputs_count as u64).to_le_bytes());
+    let mut ret = [0; BLAKE2B_LEN];
+    blake2b.finalize(&mut ret);
+    ret
+}
@@ -0,0 +1,13 @@
+use ckb_types::core::{TransactionBuilder, TransactionView};
+
+use crate::SpvInstance;
+
+/// Builds a transaction to destroy all cells of an SPV instance.
+///
+/// There are no outputs, the outputs to take the capacity back should be
+/// appended.
+pub fn build_destroy(instance: &SpvInstance) -> TransactionView {
+    TransactionBuilder::default()
+        .inputs(instance.cells().map(|cell| cell.as_input()))
+        .build()
+}
@@ -0,0 +1,27 @@
+use ckb_types::{
+    bytes::Bytes,
+    packed::{BytesOpt, WitnessArgs},
+    prelude::*,
+};
+
+mod create;
+mod destroy;
+mod reorg;
+mod reset;
+mod update;
+
+pub use create::{build_create, CreateParams};
+pub use destroy::build_destroy;
+pub use reorg::build_reorg;
+pub use reset::build_reset;
+pub use update::build_update;
+
+// The witness for the SPV type script, the proof is in the field
+// `output_type`.
+fn spv_witness(proof: &[u8]) -> Bytes {
+    let output_type = BytesOpt::new_builder().set(Some(proof.pack())).build();
+    WitnessArgs::new_builder()
+        .output_type(output_type)
+        .build()
+        .as_bytes()
+}
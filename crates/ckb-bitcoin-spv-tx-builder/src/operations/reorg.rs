@@ -0,0 +1,63 @@
+use ckb_bitcoin_spv_verifier::types::{core, packed, prelude::Pack as VPack};
+use ckb_types::{
+    core::{TransactionBuilder, TransactionView},
+    prelude::*,
+};
+
+use super::spv_witness;
+use crate::{
+    error::{Error, Result},
+    ring, SpvInstance,
+};
+
+/// Builds a transaction to reorg an SPV instance.
+///
+/// The fork client is the client whose tip block is the best common ancestor
+/// of both the old chain and the new chain. All clients after the fork
+/// client are replaced by the new client, and the client next to the fork
+/// client becomes the new tip client.
+///
+/// The new client and the update are the output of the prover, which are
+/// based on the fork client.
+pub fn build_reorg(
+    instance: &SpvInstance,
+    fork_client_id: u8,
+    new_client: core::SpvClient,
+    update: &packed::SpvUpdate,
+) -> Result<TransactionView> {
+    let clients_count = instance.clients_count();
+    let fork_client = instance.client(fork_client_id)?;
+    let client_ids =
+        ring::client_ids_between(fork_client_id, instance.tip_client_id, clients_count);
+    if client_ids.len() < 2 {
+        return Err(Error::ReorgClientsNotEnough);
+    }
+    let new_tip_client_id = client_ids[0];
+
+    let mut inputs = vec![instance.info.as_input()];
+    let mut outputs = vec![instance.info.output.clone()];
+    let mut outputs_data = {
+        let output_info = packed::SpvInfo::new_builder()
+            .tip_client_id(new_tip_client_id.into())
+            .build();
+        vec![output_info.as_bytes()]
+    };
+    let mut client = new_client;
+    for id in client_ids {
+        let input_client = instance.client(id)?;
+        inputs.push(input_client.as_input());
+        outputs.push(input_client.output.clone());
+        client.id = id;
+        let packed_client: packed::SpvClient = client.pack();
+        outputs_data.push(packed_client.as_bytes());
+    }
+
+    let tx = TransactionBuilder::default()
+        .cell_dep(fork_client.as_cell_dep())
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .witness(spv_witness(update.as_slice()).pack())
+        .build();
+    Ok(tx)
+}
@@ -0,0 +1,46 @@
+use ckb_bitcoin_spv_verifier::types::{core::BitcoinChainType, packed, prelude::Pack as VPack};
+use ckb_types::{
+    core::{TransactionBuilder, TransactionView},
+    prelude::*,
+};
+
+use super::spv_witness;
+use crate::{
+    error::{Error, Result},
+    SpvInstance,
+};
+
+/// Builds a transaction to reset an SPV instance with a new bootstrap.
+///
+/// Only SPV instances for the testnet could be reset. All cells are
+/// replaced, in the same order as they are created.
+pub fn build_reset(
+    instance: &SpvInstance,
+    bootstrap: &packed::SpvBootstrap,
+) -> Result<TransactionView> {
+    if BitcoinChainType::Testnet != instance.type_args.flags.into() {
+        return Err(Error::ResetNotAllowed);
+    }
+
+    let outputs_data = {
+        let spv_info = packed::SpvInfo::new_builder().build();
+        let mut outputs_data = vec![spv_info.as_bytes()];
+        let mut client = bootstrap
+            .initialize_spv_client()
+            .map_err(|_| Error::BootstrapFailed)?;
+        for id in 0..instance.clients_count() {
+            client.id = id;
+            let packed_client: packed::SpvClient = client.pack();
+            outputs_data.push(packed_client.as_bytes());
+        }
+        outputs_data
+    };
+
+    let tx = TransactionBuilder::default()
+        .inputs(instance.cells().map(|cell| cell.as_input()))
+        .outputs(instance.cells().map(|cell| cell.output.clone()))
+        .outputs_data(outputs_data.pack())
+        .witness(spv_witness(bootstrap.as_slice()).pack())
+        .build();
+    Ok(tx)
+}
@@ -0,0 +1,45 @@
+use ckb_bitcoin_spv_verifier::types::{core, packed, prelude::Pack as VPack};
+use ckb_types::{
+    core::{TransactionBuilder, TransactionView},
+    prelude::*,
+};
+
+use super::spv_witness;
+use crate::{error::Result, ring, SpvInstance};
+
+/// Builds a transaction to update an SPV instance.
+///
+/// The oldest client, which is next to the tip client, is replaced by the
+/// new client, then it becomes the new tip client.
+///
+/// The new client and the update are the output of the prover.
+pub fn build_update(
+    instance: &SpvInstance,
+    new_client: core::SpvClient,
+    update: &packed::SpvUpdate,
+) -> Result<TransactionView> {
+    let tip_client_id = instance.tip_client_id;
+    let next_client_id = ring::next_client_id(tip_client_id, instance.clients_count());
+    let input_client = instance.client(next_client_id)?;
+
+    let output_info = packed::SpvInfo::new_builder()
+        .tip_client_id(next_client_id.into())
+        .build();
+    let output_client: packed::SpvClient = {
+        let mut client = new_client;
+        client.id = next_client_id;
+        client.pack()
+    };
+
+    let tx = TransactionBuilder::default()
+        .cell_dep(instance.tip_client().as_cell_dep())
+        .inputs(vec![instance.info.as_input(), input_client.as_input()])
+        .outputs(vec![
+            instance.info.output.clone(),
+            input_client.output.clone(),
+        ])
+        .outputs_data([output_info.as_bytes(), output_client.as_bytes()].pack())
+        .witness(spv_witness(update.as_slice()).pack())
+        .build();
+    Ok(tx)
+}
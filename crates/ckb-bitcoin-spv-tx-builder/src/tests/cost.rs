@@ -0,0 +1,110 @@
+use ckb_bitcoin_spv_verifier::types::{core, packed, prelude::Pack as VPack};
+use ckb_types::{bytes::Bytes, core::ScriptHashType, packed::Script, prelude::*, H256};
+
+use crate::{cost::*, error::Error};
+
+const CLIENTS_COUNT: u8 = 5;
+// The args of the permissionless update lock, with the reward mode.
+const LOCK_ARGS_SIZE: usize = 32 + 20 + 8;
+
+fn lock_script() -> Script {
+    Script::new_builder()
+        .code_hash(H256([4u8; 32]).pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(vec![5u8; LOCK_ARGS_SIZE]).pack())
+        .build()
+}
+
+fn type_script(clients_count: u8) -> Script {
+    let args = packed::SpvTypeArgs::new_builder()
+        .type_id(core::Hash::from_bytes_ref(&[1u8; 32]).pack())
+        .clients_count(clients_count.into())
+        .build();
+    Script::new_builder()
+        .code_hash(H256([2u8; 32]).pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(args.as_slice().pack())
+        .build()
+}
+
+fn params() -> CostParams {
+    CostParams {
+        lock_script: lock_script(),
+        type_script: type_script(CLIENTS_COUNT),
+        mmr_leaves: 2016,
+        fee_rate: 1000,
+        cycles: CyclesModel::default(),
+    }
+}
+
+#[test]
+fn occupied_capacity_of_cells() {
+    let capacity = occupied_capacity(&lock_script(), &type_script(CLIENTS_COUNT)).unwrap();
+    // The capacity field, the lock script and the type script.
+    let cell_size = 8 + (32 + 1 + LOCK_ARGS_SIZE) + (32 + 1 + packed::SpvTypeArgs::TOTAL_SIZE);
+    let shannons = |size: usize| size as u64 * 100_000_000;
+    assert_eq!(
+        capacity.info,
+        shannons(cell_size + packed::SpvInfo::TOTAL_SIZE)
+    );
+    assert_eq!(
+        capacity.client,
+        shannons(cell_size + packed::SpvClient::TOTAL_SIZE)
+    );
+    assert_eq!(capacity.clients_count, CLIENTS_COUNT);
+    assert_eq!(capacity.total(), capacity.info + capacity.client * 5);
+}
+
+#[test]
+fn occupied_capacity_with_bad_type_args() {
+    let type_script = type_script(CLIENTS_COUNT)
+        .as_builder()
+        .args(Bytes::from(vec![0u8; 3]).pack())
+        .build();
+    let result = occupied_capacity(&lock_script(), &type_script);
+    assert_eq!(result.unwrap_err(), Error::TypeArgsMalformed);
+
+    let result = occupied_capacity(&lock_script(), &self::type_script(2));
+    assert_eq!(result.unwrap_err(), Error::ClientsCountTooSmall(2));
+}
+
+#[test]
+fn update_cost_by_headers() {
+    let params = params();
+    let one = update_cost(&params, 1).unwrap();
+    let ten = update_cost(&params, 10).unwrap();
+    assert_eq!(ten.size - one.size, 9 * packed::Header::TOTAL_SIZE as u64);
+    assert_eq!(ten.cycles, params.cycles.cycles(10, 1));
+    assert!(ten.fee > one.fee);
+
+    // The fee is charged by the larger one of the size and the cycles.
+    let heavy = CostParams {
+        cycles: CyclesModel {
+            base: 100_000_000,
+            per_header: 0,
+            per_client: 0,
+        },
+        ..self::params()
+    };
+    let cost = update_cost(&heavy, 1).unwrap();
+    assert_eq!(cost.size, one.size);
+    assert_eq!(cost.fee, 17_057);
+}
+
+#[test]
+fn reorg_cost_by_clients() {
+    let params = params();
+    let two = reorg_cost(&params, 2, 10).unwrap();
+    let four = reorg_cost(&params, 4, 10).unwrap();
+    assert!(four.size > two.size);
+    assert!(two.size > update_cost(&params, 10).unwrap().size);
+    assert_eq!(four.cycles, params.cycles.cycles(10, 4));
+
+    let result = reorg_cost(&params, 1, 10);
+    assert_eq!(result.unwrap_err(), Error::ReorgClientsNotEnough);
+    let result = reorg_cost(&params, CLIENTS_COUNT, 10);
+    assert_eq!(
+        result.unwrap_err(),
+        Error::ReorgClientsTooMany(CLIENTS_COUNT)
+    );
+}
@@ -0,0 +1,265 @@
+use bitcoin::{blockdata::constants::genesis_block, Network};
+use ckb_bitcoin_spv_model::{Operation, SpvState};
+use ckb_bitcoin_spv_verifier::types::{
+    core::{self, BitcoinChainType},
+    packed,
+    prelude::{Pack as VPack, Unpack as VUnpack},
+};
+use ckb_types::{
+    bytes::Bytes,
+    core::{ScriptHashType, TransactionView},
+    packed::{CellInput, CellOutput, OutPoint, Script, WitnessArgs},
+    prelude::*,
+    H256,
+};
+
+use crate::{error::Error, *};
+
+const CLIENTS_COUNT: u8 = 5;
+
+fn type_script(flags: u8) -> Script {
+    let args = packed::SpvTypeArgs::new_builder()
+        .type_id(core::Hash::from_bytes_ref(&[1u8; 32]).pack())
+        .clients_count(CLIENTS_COUNT.into())
+        .flags(flags.into())
+        .build();
+    Script::new_builder()
+        .code_hash(H256([2u8; 32]).pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(args.as_slice().pack())
+        .build()
+}
+
+fn live_cell(type_script: &Script, index: u32, data: Bytes) -> LiveCell {
+    let out_point = OutPoint::new_builder()
+        .tx_hash(H256([3u8; 32]).pack())
+        .index(Pack::pack(&index))
+        .build();
+    let output = CellOutput::new_builder()
+        .capacity(500u64.pack())
+        .type_(Some(type_script.clone()).pack())
+        .build();
+    LiveCell {
+        out_point,
+        output,
+        data,
+    }
+}
+
+fn client(id: u8) -> core::SpvClient {
+    let mut client: core::SpvClient = packed::SpvClient::new_builder().build().unpack();
+    client.id = id;
+    client
+}
+
+fn instance(flags: u8, tip_client_id: u8) -> SpvInstance {
+    let type_script = type_script(flags);
+    let info = packed::SpvInfo::new_builder()
+        .tip_client_id(tip_client_id.into())
+        .build();
+    let mut cells = (0..CLIENTS_COUNT)
+        .rev()
+        .map(|id| {
+            let packed_client: packed::SpvClient = client(id).pack();
+            live_cell(&type_script, u32::from(id) + 1, packed_client.as_bytes())
+        })
+        .collect::<Vec<_>>();
+    cells.push(live_cell(&type_script, 0, info.as_bytes()));
+    SpvInstance::load(cells).unwrap()
+}
+
+fn output_info(tx: &TransactionView, index: usize) -> core::SpvInfo {
+    let data = tx.outputs_data().get(index).unwrap().raw_data();
+    packed::SpvInfoReader::from_slice(&data).unwrap().unpack()
+}
+
+fn output_client_id(tx: &TransactionView, index: usize) -> u8 {
+    let data = tx.outputs_data().get(index).unwrap().raw_data();
+    packed::SpvClientReader::from_slice(&data)
+        .unwrap()
+        .id()
+        .into()
+}
+
+fn spv_witness_index(tx: &TransactionView) -> Option<usize> {
+    tx.witnesses().into_iter().position(|witness| {
+        WitnessArgs::from_slice(&witness.raw_data())
+            .map(|witness_args| witness_args.output_type().is_some())
+            .unwrap_or(false)
+    })
+}
+
+#[test]
+fn load_instance() {
+    let instance = instance(0, 2);
+    assert_eq!(instance.tip_client_id, 2);
+    assert_eq!(instance.clients.len(), usize::from(CLIENTS_COUNT));
+    for (id, cell) in instance.clients.iter().enumerate() {
+        let data = packed::SpvClientReader::from_slice(&cell.data).unwrap();
+        assert_eq!(usize::from(u8::from(data.id())), id);
+    }
+
+    let type_script = type_script(0);
+    let info = packed::SpvInfo::new_builder().build();
+    let info_cell = live_cell(&type_script, 0, info.as_bytes());
+    let client_cell = |id: u8| {
+        let packed_client: packed::SpvClient = client(id).pack();
+        live_cell(&type_script, u32::from(id) + 1, packed_client.as_bytes())
+    };
+
+    let cells = vec![info_cell.clone(), client_cell(0), client_cell(1)];
+    let result = SpvInstance::load(cells);
+    assert_eq!(result.err(), Some(Error::ClientNotFound(2)));
+
+    let mut cells = (0..CLIENTS_COUNT).map(client_cell).collect::<Vec<_>>();
+    let result = SpvInstance::load(cells.clone());
+    assert_eq!(result.err(), Some(Error::InfoNotFound));
+
+    cells.push(client_cell(1));
+    cells.push(info_cell.clone());
+    let result = SpvInstance::load(cells);
+    assert_eq!(result.err(), Some(Error::ClientDuplicated(1)));
+
+    let mut cells = (0..CLIENTS_COUNT).map(client_cell).collect::<Vec<_>>();
+    cells.push(info_cell.clone());
+    cells.push(info_cell);
+    let result = SpvInstance::load(cells);
+    assert_eq!(result.err(), Some(Error::InfoDuplicated));
+}
+
+#[test]
+fn create() {
+    let header = genesis_block(Network::Bitcoin).header;
+    let bootstrap = packed::SpvBootstrap::new_builder()
+        .height(VPack::pack(&0u32))
+        .header(header.pack())
+        .build();
+    let first_input = CellInput::new_builder()
+        .previous_output(OutPoint::new_builder().index(Pack::pack(&7u32)).build())
+        .build();
+    let params = CreateParams {
+        first_input: first_input.clone(),
+        lock_script: Script::default(),
+        type_script: type_script(0),
+        clients_count: CLIENTS_COUNT,
+        flags: 0,
+        cell_capacity: 500,
+        bootstrap,
+    };
+    let tx = build_create(params).unwrap();
+
+    assert_eq!(
+        tx.inputs().get(0).unwrap().as_slice(),
+        first_input.as_slice()
+    );
+    assert_eq!(tx.outputs().len(), usize::from(CLIENTS_COUNT) + 1);
+    assert_eq!(output_info(&tx, 0).tip_client_id, 0);
+    for id in 0..CLIENTS_COUNT {
+        assert_eq!(output_client_id(&tx, usize::from(id) + 1), id);
+    }
+    assert_eq!(spv_witness_index(&tx), Some(0));
+
+    // All SPV cells have the same type script, which has a new type id.
+    let type_script = tx.outputs().get(0).unwrap().type_().to_opt().unwrap();
+    assert_ne!(type_script.as_slice(), self::type_script(0).as_slice());
+    assert!(tx
+        .outputs()
+        .into_iter()
+        .all(|output| { output.type_().to_opt().unwrap().as_slice() == type_script.as_slice() }));
+}
+
+#[test]
+fn update() {
+    for tip_client_id in 0..CLIENTS_COUNT {
+        let instance = instance(0, tip_client_id);
+        let update = packed::SpvUpdate::new_builder().build();
+        let tx = build_update(&instance, client(0), &update).unwrap();
+
+        let next_client_id = ring::next_client_id(tip_client_id, CLIENTS_COUNT);
+        assert_eq!(tx.cell_deps().len(), 1);
+        assert_eq!(
+            tx.cell_deps().get(0).unwrap().out_point().as_slice(),
+            instance.tip_client().out_point.as_slice()
+        );
+        assert_eq!(
+            tx.inputs().get(1).unwrap().previous_output().as_slice(),
+            instance
+                .client(next_client_id)
+                .unwrap()
+                .out_point
+                .as_slice()
+        );
+        assert_eq!(output_info(&tx, 0).tip_client_id, next_client_id);
+        assert_eq!(output_client_id(&tx, 1), next_client_id);
+        assert_eq!(spv_witness_index(&tx), Some(0));
+
+        let state = SpvState::new(CLIENTS_COUNT, 0, tip_client_id).unwrap();
+        let transition = state.apply(Operation::Update).unwrap();
+        assert_eq!(tx.inputs().len(), transition.inputs.len());
+        assert_eq!(tx.cell_deps().len(), transition.cell_deps.len());
+        assert_eq!(transition.next.unwrap().tip_client_id(), next_client_id);
+    }
+}
+
+#[test]
+fn reorg() {
+    let instance = instance(0, 1);
+    let update = packed::SpvUpdate::new_builder().build();
+
+    // Clients 4, 0 and 1 are replaced.
+    let tx = build_reorg(&instance, 3, client(0), &update).unwrap();
+    assert_eq!(
+        tx.cell_deps().get(0).unwrap().out_point().as_slice(),
+        instance.client(3).unwrap().out_point.as_slice()
+    );
+    assert_eq!(tx.inputs().len(), 4);
+    assert_eq!(output_info(&tx, 0).tip_client_id, 4);
+    let ids = (1..4)
+        .map(|index| output_client_id(&tx, index))
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![4, 0, 1]);
+    assert_eq!(spv_witness_index(&tx), Some(0));
+
+    let state = SpvState::new(CLIENTS_COUNT, 0, 1).unwrap();
+    let transition = state.apply(Operation::Reorg { fork_client_id: 3 }).unwrap();
+    assert_eq!(tx.inputs().len(), transition.inputs.len());
+    assert_eq!(tx.outputs().len(), transition.outputs.len());
+    assert_eq!(transition.next.unwrap().tip_client_id(), 4);
+
+    // Only one client is replaced, it's an update.
+    let result = build_reorg(&instance, 0, client(0), &update);
+    assert_eq!(result.err(), Some(Error::ReorgClientsNotEnough));
+    let result = build_reorg(&instance, 1, client(0), &update);
+    assert_eq!(result.err(), Some(Error::ReorgClientsNotEnough));
+    let result = build_reorg(&instance, CLIENTS_COUNT, client(0), &update);
+    assert_eq!(result.err(), Some(Error::ClientNotFound(CLIENTS_COUNT)));
+}
+
+#[test]
+fn reset_and_destroy() {
+    let header = genesis_block(Network::Testnet).header;
+    let bootstrap = packed::SpvBootstrap::new_builder()
+        .height(VPack::pack(&0u32))
+        .header(header.pack())
+        .build();
+
+    let instance = instance(0, 3);
+    let result = build_reset(&instance, &bootstrap);
+    assert_eq!(result.err(), Some(Error::ResetNotAllowed));
+
+    // The chain type is in the highest two bits of the flags.
+    let testnet_flags = (0..4u8)
+        .map(|bits| bits << 6)
+        .find(|flags| BitcoinChainType::Testnet == (*flags).into())
+        .unwrap();
+    let instance = self::instance(testnet_flags, 3);
+    let tx = build_reset(&instance, &bootstrap).unwrap();
+    assert_eq!(tx.inputs().len(), usize::from(CLIENTS_COUNT) + 1);
+    assert_eq!(tx.outputs().len(), usize::from(CLIENTS_COUNT) + 1);
+    assert_eq!(output_info(&tx, 0).tip_client_id, 0);
+    assert_eq!(spv_witness_index(&tx), Some(0));
+
+    let tx = build_destroy(&instance);
+    assert_eq!(tx.inputs().len(), usize::from(CLIENTS_COUNT) + 1);
+    assert!(tx.outputs().is_empty());
+}
@@ -0,0 +1,263 @@
+//! Names of the errors of contracts, by their exit codes.
+//!
+//! Keep them same as the `error.rs` of each contract, and the `error.rs` of
+//! the consumer crate, which is shared by the contracts which verify Bitcoin
+//! transactions.
+
+pub(crate) fn error_name(contract: &str, code: i8) -> Option<&'static str> {
+    match contract {
+        "ckb-bitcoin-spv-type-lock" => spv_type_lock(code),
+        "can-update-without-ownership-lock" => can_update_without_ownership_lock(code),
+        "ckb-bitcoin-spv-permissionless-update-lock" => permissionless_update_lock(code),
+        "ckb-bitcoin-spv-tx-verifier" => consumer(code),
+        "ckb-bitcoin-spv-quorum-lock" => quorum_lock(code).or_else(|| consumer(code)),
+        "ckb-bitcoin-height-timelock-lock" => height_timelock_lock(code).or_else(|| consumer(code)),
+        "ckb-bitcoin-deposit-mint-type" => deposit_mint_type(code).or_else(|| consumer(code)),
+        "ckb-bitcoin-tx-registry-type" => tx_registry_type(code).or_else(|| consumer(code)),
+        "spv-tx-verifier-caller-type" => caller_type(code).or_else(|| consumer(code)),
+        _ => None,
+    }
+}
+
+fn sdk(code: i8) -> Option<&'static str> {
+    let name = match code {
+        0x01 => "IndexOutOfBound",
+        0x02 => "ItemMissing",
+        0x03 => "LengthNotEnough",
+        0x04 => "Encoding",
+        0x05 => "Unknown",
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn spv_type_lock(code: i8) -> Option<&'static str> {
+    let name = match code {
+        0x01..=0x0f => return sdk(code),
+
+        0x10 => "UnknownOperation",
+
+        0x20 => "CreateNotEnoughCells",
+        0x21 => "CreateShouldBeOrdered",
+        0x22 => "CreateCellsCountNotMatched",
+        0x23 => "CreateIncorrectUniqueId",
+        0x24 => "CreateBadInfoCellData",
+        0x25 => "CreateInfoIndexShouldBeZero",
+        0x26 => "CreateWitnessIsNotExisted",
+        0x27 => "CreateBadClientCellData",
+        0x28 => "CreateNewClientIsIncorrect",
+
+        0x2f => "DestroyNotEnoughCells",
+
+        0x30 => "UpdateInputInfoNotFound",
+        0x31 => "UpdateInputClientNotFound",
+        0x32 => "UpdateInputClientIdIsMismatch",
+        0x33 => "UpdateOutputInfoNotFound",
+        0x34 => "UpdateOutputClientNotFound",
+        0x35 => "UpdateOutputInfoChanged",
+        0x36 => "UpdateCellDepMoreThanOne",
+        0x37 => "UpdateCellDepNotFound",
+        0x38 => "UpdateCellDepClientNotFound",
+        0x39 => "UpdateCellDepClientIdIsMismatch",
+        0x3a => "UpdateWitnessIsNotExisted",
+
+        0x40 => "ReorgNotBetterChain",
+        0x41 => "ReorgInputMalformed",
+        0x42 => "ReorgInputInfoNotFound",
+        0x43 => "ReorgInputClientNotEnough",
+        0x44 => "ReorgInputInfoDuplicated",
+        0x45 => "ReorgInputTipClientNotFound",
+        0x46 => "ReorgInputTipClientLoadFailed",
+        0x47 => "ReorgInputClientIdsIsMismatch",
+        0x48 => "ReorgOutputMalformed",
+        0x49 => "ReorgOutputInfoNotFound",
+        0x4a => "ReorgOutputInfoDuplicated",
+        0x4b => "ReorgOutputTipClientNotFound",
+        0x4c => "ReorgOutputClientIdsIsMismatch",
+        0x4d => "ReorgNewClientIsIncorrect",
+        0x4e => "ReorgCellDepMoreThanOne",
+        0x4f => "ReorgCellDepNotFound",
+        0x50 => "ReorgCellDepClientNotFound",
+        0x51 => "ReorgCellDepClientIdIsMismatch",
+
+        0x60 => "Unreachable",
+        // Different steps may have same error codes.
+        0x61..=0x7f => "BootstrapError or UpdateError (code - 0x60)",
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn can_update_without_ownership_lock(code: i8) -> Option<&'static str> {
+    let name = match code {
+        0x01..=0x0f => return sdk(code),
+
+        0x10 => "ShouldNotBeType",
+        0x11 => "WitnessIsIncorrect",
+        0x12 => "InputsCapacityOverflow",
+        0x13 => "OutputsCapacityOverflow",
+        0x14 => "LostCapacityWithoutOwnership",
+        0x15 => "WitnessMalformed",
+        0x16 => "SignatureMalformed",
+        0x17 => "ArgsMalformed",
+        0x18 => "TypeIsNotMatched",
+        0x19 => "RewardExceeded",
+        0x1a => "SpvInfoNotFound",
+        0x1b => "SpvClientNotFound",
+        0x1c => "TipNotAdvanced",
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn permissionless_update_lock(code: i8) -> Option<&'static str> {
+    let name = match code {
+        0x01..=0x0f => return sdk(code),
+
+        0x10 => "ArgsMalformed",
+        0x11 => "ShouldNotBeType",
+        0x12 => "WitnessMalformed",
+        0x13 => "SignatureMalformed",
+        0x14 => "SignatureIsIncorrect",
+        0x15 => "TypeIsNotMatched",
+        0x16 => "InputsCapacityOverflow",
+        0x17 => "OutputsCapacityOverflow",
+        0x18 => "LostCapacityWithoutOwnership",
+        0x19 => "RewardExceeded",
+        0x1a => "SpvInfoNotFound",
+        0x1b => "SpvClientNotFound",
+        0x1c => "TipNotAdvanced",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// The errors of the consumer crate, which are in 0x01 ~ 0x4f.
+fn consumer(code: i8) -> Option<&'static str> {
+    let name = match code {
+        0x01 => "IndexOutOfBound",
+        0x02 => "ItemMissing",
+        0x03 => "LengthNotEnough",
+        0x04 => "Encoding",
+        0x05 => "Unknown",
+        0x06 => "WaitFailure",
+        0x07 => "InvalidFd",
+        0x08 => "OtherEndClosed",
+        0x09 => "MaxVmsSpawned",
+        0x0a => "MaxFdsCreated",
+
+        0x10 => "ClientCellDepNotFound",
+        0x11 => "ClientCellDepMoreThanOne",
+        0x12 => "RegistryCellMalformed",
+        0x13 => "RegistryKeyIsUsed",
+        0x14 => "RegistryKeyIsNotInserted",
+        0x15 => "InfoCellDepNotFound",
+        0x16 => "InfoCellDepMoreThanOne",
+        0x17 => "TipClientCellDepNotFound",
+        0x18 => "RegistryKeyIsInsertedUnexpectedly",
+
+        0x20 => "BitcoinDataUnexpectedEnd",
+        0x21 => "BitcoinDataTrailingBytes",
+        0x22 => "BitcoinDataNonCanonicalSize",
+        0x23 => "BitcoinTxUnsupportedFlag",
+        0x24 => "BitcoinTxNoInputs",
+        0x25 => "BitcoinTxSizeIs64",
+        0x26 => "BitcoinScriptTruncated",
+        0x27 => "BitcoinTxOutputNotFound",
+        0x28 => "BitcoinHeaderProofMalformed",
+
+        0x30 => "TxProofMalformed",
+        0x31 => "VerifierArgsMalformed",
+        0x32 => "VerifierResultMalformed",
+        0x33 => "VerifierFailed",
+        0x34 => "QuorumConfigMalformed",
+        0x35 => "QuorumProofsCountMismatch",
+        0x36 => "QuorumNotReached",
+        0x37 => "CommitmentNotFound",
+        0x38 => "WitnessProofMalformed",
+        0x39 => "WitnessCommitmentNotFound",
+        0x3a => "WitnessCommitmentMismatch",
+        0x3b => "SpendProofMalformed",
+        0x3c => "OutPointNotSpent",
+        0x3d => "BatchProofMalformed",
+        0x3e => "HeaderUnconfirmed",
+        0x3f => "HeaderMmrProofFailed",
+
+        0x40..=0x4f => "VerifyTxError (code - 0x40)",
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn quorum_lock(code: i8) -> Option<&'static str> {
+    let name = match code {
+        0x50 => "ArgsMalformed",
+        0x51 => "WitnessIsNotExisted",
+        0x52 => "WitnessMalformed",
+        0x53 => "CommitmentNotFound",
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn height_timelock_lock(code: i8) -> Option<&'static str> {
+    let name = match code {
+        0x50 => "ArgsMalformed",
+        0x51 => "TargetHeightOverflow",
+        0x52 => "TargetHeightNotReached",
+        0x53 => "OwnerNotFound",
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn deposit_mint_type(code: i8) -> Option<&'static str> {
+    let name = match code {
+        0x50 => "ArgsMalformed",
+        0x51 => "AmountMalformed",
+        0x52 => "InputsAmountOverflow",
+        0x53 => "OutputsAmountOverflow",
+        0x54 => "MintWithInputs",
+        0x55 => "WitnessIsNotExisted",
+        0x56 => "WitnessMalformed",
+        0x57 => "DepositAmountOverflow",
+        0x58 => "DepositAmountMismatch",
+        0x59 => "RecipientNotFound",
+        0x5a => "RecipientMoreThanOne",
+        0x5b => "RecipientMismatch",
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn tx_registry_type(code: i8) -> Option<&'static str> {
+    let name = match code {
+        0x50 => "UnknownOperation",
+        0x51 => "ArgsMalformed",
+
+        0x60 => "CreateCellsCountNotMatched",
+        0x61 => "CreateIncorrectUniqueId",
+        0x62 => "CreateNotHeadCell",
+
+        0x70 => "InsertDuplicatedKeys",
+        0x71 => "InsertRangeStartNotFound",
+        0x72 => "InsertRangeIsBroken",
+        0x73 => "InsertRangeEndNotFound",
+        0x74 => "InsertUnknownCells",
+        0x75 => "InsertWithoutConsumer",
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn caller_type(code: i8) -> Option<&'static str> {
+    let name = match code {
+        0x50 => "ArgsMalformed",
+        0x51 => "WitnessIsNotExisted",
+        0x52 => "WitnessMalformed",
+        0x53 => "ExecReturned",
+        0x54 => "ResultMismatch",
+        _ => return None,
+    };
+    Some(name)
+}
@@ -0,0 +1,363 @@
+//! Replays a transaction which is dumped into the `failed_txs` folder by
+//! `ContextExt::should_be_passed` or `ContextExt::should_be_failed`, then
+//! explains the result.
+//!
+//! The binaries of known contracts in the dumped transaction are replaced by
+//! their debug builds (`build/debug`), and the scripts which reference those
+//! binaries by data hash are updated too.
+//! Script hashes in args and signatures are not updated, so if they matter,
+//! pass `--as-is` to replay with the dumped binaries.
+//!
+//! Usage:
+//!
+//! ```text
+//! cargo run -p tests --bin replay-failed-tx -- failed_txs/0x<hash>.json [--as-is]
+//! ```
+
+use std::{collections::HashMap, env, fs, process::ExitCode};
+
+use ckb_testtool::{
+    ckb_jsonrpc_types as json,
+    ckb_types::{
+        bytes::Bytes,
+        core::{HeaderView, ScriptHashType, TransactionView},
+        packed::{Byte32, CellOutput, OutPoint, Script, Transaction},
+        prelude::*,
+        H256,
+    },
+    context::Context,
+};
+use serde::Deserialize;
+use tests::{Loader, TestEnv};
+
+mod error_names;
+
+#[cfg(test)]
+mod tests;
+
+// Same as the max cycles of a block on the mainnet.
+const MAX_CYCLES: u64 = 3_500_000_000;
+
+// The messages which are printed by `entry::main` of the SPV type script,
+// after it chooses the operation.
+const SPV_OPERATIONS: &[&str] = &[
+    "create all cells",
+    "destroy all cells",
+    "update a client cell and the info cell",
+    "reorg client cells",
+    "reset all cells",
+    "unknown operation",
+];
+
+#[derive(Deserialize)]
+struct MockTransaction {
+    mock_info: MockInfo,
+    tx: json::Transaction,
+}
+
+#[derive(Deserialize)]
+struct MockInfo {
+    inputs: Vec<MockInput>,
+    cell_deps: Vec<MockCellDep>,
+    #[serde(default)]
+    header_deps: Vec<json::HeaderView>,
+}
+
+#[derive(Deserialize)]
+struct MockInput {
+    input: json::CellInput,
+    output: json::CellOutput,
+    data: json::JsonBytes,
+    header: Option<H256>,
+}
+
+#[derive(Deserialize)]
+struct MockCellDep {
+    cell_dep: json::CellDep,
+    output: json::CellOutput,
+    data: json::JsonBytes,
+    header: Option<H256>,
+}
+
+/// A live cell which is used by the dumped transaction.
+struct MockCell {
+    out_point: OutPoint,
+    output: CellOutput,
+    data: Bytes,
+    header: Option<H256>,
+}
+
+/// All known contracts, the debug builds and the release builds.
+struct Contracts {
+    debug: HashMap<String, Bytes>,
+    release: HashMap<String, Bytes>,
+}
+
+fn main() -> ExitCode {
+    let mut args = env::args().skip(1);
+    let Some(path) = args.next() else {
+        eprintln!("usage: replay-failed-tx <dumped json> [--as-is]");
+        return ExitCode::from(2);
+    };
+    let as_is = args.any(|arg| arg == "--as-is");
+
+    let mock_tx: MockTransaction = match fs::read_to_string(&path)
+        .map_err(|err| err.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|err| err.to_string()))
+    {
+        Ok(mock_tx) => mock_tx,
+        Err(err) => {
+            eprintln!("error: failed to load {path}: {err}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let contracts = Contracts::load();
+    let Prepared {
+        cells,
+        headers,
+        tx,
+        names,
+    } = prepare(mock_tx, &contracts, as_is);
+
+    let mut context = Context::default();
+    context.set_capture_debug(true);
+    for cell in cells {
+        context.create_cell_with_out_point(cell.out_point.clone(), cell.output, cell.data);
+        if let Some(block_hash) = cell.header {
+            context.link_cell_with_block(cell.out_point, block_hash.pack(), 0);
+        }
+    }
+    for header in headers {
+        context.insert_header(header);
+    }
+
+    println!("transaction: {:#x}", tx.hash());
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+
+    println!("debug log:");
+    for message in context.captured_messages() {
+        let name = names.script_name(&message.id);
+        println!("    [{name}] {}", message.message);
+    }
+    let operations = context
+        .captured_messages()
+        .into_iter()
+        .filter(|message| {
+            SPV_OPERATIONS
+                .iter()
+                .any(|operation| message.message.starts_with(operation))
+        })
+        .map(|message| message.message)
+        .collect::<Vec<_>>();
+    if !operations.is_empty() {
+        println!("SPV operation: {}", operations.join(", "));
+    }
+
+    match result {
+        Ok(cycles) => {
+            println!("passed, cycles: {cycles}");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            let err = err.to_string();
+            println!("failed: {err}");
+            if let Some(code) = parse_error_code(&err) {
+                let name = parse_error_source(&err)
+                    .and_then(|source| names.source_name(&tx, source))
+                    .unwrap_or("unknown");
+                let error_name = error_names::error_name(name, code).unwrap_or("unknown");
+                println!("script: {name}, exit code: {code}, error: {error_name}");
+            }
+            ExitCode::from(1)
+        }
+    }
+}
+
+impl Contracts {
+    fn load() -> Self {
+        let load = |env| {
+            Loader::with_test_env(env)
+                .load_binaries()
+                .into_iter()
+                .collect::<HashMap<_, _>>()
+        };
+        Self {
+            debug: load(TestEnv::Debug),
+            release: load(TestEnv::Release),
+        }
+    }
+
+    fn find(&self, data: &[u8]) -> Option<&str> {
+        self.debug
+            .iter()
+            .chain(self.release.iter())
+            .find(|(_, binary)| binary.as_ref() == data)
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+/// The dumped transaction, which is ready to be verified.
+struct Prepared {
+    cells: Vec<MockCell>,
+    headers: Vec<HeaderView>,
+    tx: TransactionView,
+    names: Names,
+}
+
+/// The names of the contracts, indexed by code hashes and script hashes.
+struct Names {
+    inputs: Vec<CellOutput>,
+    code_hashes: HashMap<Byte32, String>,
+    script_hashes: HashMap<Byte32, String>,
+}
+
+impl Names {
+    fn script_name(&self, script_hash: &Byte32) -> &str {
+        self.script_hashes
+            .get(script_hash)
+            .map(String::as_str)
+            .unwrap_or("unknown")
+    }
+
+    /// Finds the contract by the source of the error, such as `Inputs[0].Lock`.
+    fn source_name(&self, tx: &TransactionView, source: (&str, usize, &str)) -> Option<&str> {
+        let script = match source {
+            ("Inputs", index, "Lock") => Some(self.inputs.get(index)?.lock()),
+            ("Inputs", index, "Type") => self.inputs.get(index)?.type_().to_opt(),
+            ("Outputs", index, "Type") => tx.outputs().get(index)?.type_().to_opt(),
+            _ => None,
+        }?;
+        self.code_hashes
+            .get(&script.code_hash())
+            .map(String::as_str)
+    }
+}
+
+/// Replaces the binaries by the debug builds, and finds the names of the
+/// contracts.
+fn prepare(mock_tx: MockTransaction, contracts: &Contracts, as_is: bool) -> Prepared {
+    let MockTransaction { mock_info, tx } = mock_tx;
+    let inputs_start = mock_info.cell_deps.len();
+    let mut cells = mock_info
+        .cell_deps
+        .into_iter()
+        .map(|dep| MockCell {
+            out_point: dep.cell_dep.out_point.into(),
+            output: dep.output.into(),
+            data: dep.data.into_bytes(),
+            header: dep.header,
+        })
+        .chain(mock_info.inputs.into_iter().map(|input| MockCell {
+            out_point: input.input.previous_output.into(),
+            output: input.output.into(),
+            data: input.data.into_bytes(),
+            header: input.header,
+        }))
+        .collect::<Vec<_>>();
+
+    // Replaces the binaries, and records the changes of data hashes.
+    let mut data_hashes = HashMap::new();
+    let mut code_hashes = HashMap::new();
+    for cell in cells.iter_mut() {
+        let Some(name) = contracts.find(&cell.data).map(ToOwned::to_owned) else {
+            continue;
+        };
+        let old_data_hash = CellOutput::calc_data_hash(&cell.data);
+        if !as_is {
+            if let Some(binary) = contracts.debug.get(&name) {
+                cell.data = binary.clone();
+            }
+        }
+        let new_data_hash = CellOutput::calc_data_hash(&cell.data);
+        if old_data_hash != new_data_hash {
+            data_hashes.insert(old_data_hash, new_data_hash.clone());
+        }
+        code_hashes.insert(new_data_hash, name.clone());
+        if let Some(type_script) = cell.output.type_().to_opt() {
+            code_hashes.insert(type_script.calc_script_hash(), name);
+        }
+    }
+
+    // Updates the scripts which reference the binaries by data hash.
+    let rewrite = |script: Script| -> Script {
+        let is_type = script.hash_type() == ScriptHashType::Type.into();
+        match data_hashes.get(&script.code_hash()) {
+            Some(data_hash) if !is_type => script.as_builder().code_hash(data_hash.clone()).build(),
+            _ => script,
+        }
+    };
+    let rewrite_output = |output: CellOutput| -> CellOutput {
+        let lock = rewrite(output.lock());
+        let type_opt = output.type_().to_opt().map(rewrite);
+        output
+            .as_builder()
+            .lock(lock)
+            .type_(type_opt.pack())
+            .build()
+    };
+    for cell in cells.iter_mut() {
+        cell.output = rewrite_output(cell.output.clone());
+    }
+    let tx = Transaction::from(tx).into_view();
+    let outputs = tx
+        .outputs()
+        .into_iter()
+        .map(rewrite_output)
+        .collect::<Vec<_>>();
+    let tx = tx.as_advanced_builder().set_outputs(outputs).build();
+
+    let inputs = cells[inputs_start..]
+        .iter()
+        .map(|cell| cell.output.clone())
+        .collect::<Vec<_>>();
+    let outputs = tx.outputs().into_iter().collect::<Vec<_>>();
+    let script_hashes = inputs
+        .iter()
+        .chain(outputs.iter())
+        .flat_map(|output| {
+            Some(output.lock())
+                .into_iter()
+                .chain(output.type_().to_opt())
+        })
+        .filter_map(|script| {
+            let name = code_hashes.get(&script.code_hash())?;
+            Some((script.calc_script_hash(), name.clone()))
+        })
+        .collect();
+    let headers = mock_info
+        .header_deps
+        .into_iter()
+        .map(HeaderView::from)
+        .collect();
+    let names = Names {
+        inputs,
+        code_hashes,
+        script_hashes,
+    };
+    Prepared {
+        cells,
+        headers,
+        tx,
+        names,
+    }
+}
+
+/// Parses the exit code from an error, such as
+/// "... ValidationFailure: see error code -31 on page ...".
+fn parse_error_code(err: &str) -> Option<i8> {
+    let (_, rest) = err.split_once("error code ")?;
+    let code = rest
+        .split(|c: char| !(c == '-' || c.is_ascii_digit()))
+        .next()?;
+    code.parse().ok()
+}
+
+/// Parses the source of an error, such as "source: Inputs[0].Lock".
+fn parse_error_source(err: &str) -> Option<(&str, usize, &str)> {
+    let (_, rest) = err.split_once("source: ")?;
+    let (group, rest) = rest.split_once('[')?;
+    let (index, rest) = rest.split_once("].")?;
+    let script_type = rest.get(..4)?;
+    Some((group, index.parse().ok()?, script_type))
+}
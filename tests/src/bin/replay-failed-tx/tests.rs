@@ -0,0 +1,65 @@
+use super::{error_names::error_name, parse_error_code, parse_error_source};
+
+// Same as the message of a script error of CKB.
+const LOCK_ERROR: &str = "Script(TransactionScriptError { source: Inputs[0].Lock, cause: \
+    ValidationFailure: see error code 20 on page \
+    https://nervosnetwork.github.io/ckb-script-error-codes/by-type-hash/0x00.html#20 })";
+const TYPE_ERROR: &str = "Script(TransactionScriptError { source: Outputs[12].Type, cause: \
+    ValidationFailure: see error code -31 on page \
+    https://nervosnetwork.github.io/ckb-script-error-codes/by-type-hash/0x00.html#-31 })";
+
+#[test]
+fn parse_error_codes() {
+    assert_eq!(parse_error_code(LOCK_ERROR), Some(20));
+    assert_eq!(parse_error_code(TYPE_ERROR), Some(-31));
+    assert_eq!(parse_error_code("see error code 128 on page"), None);
+    assert_eq!(parse_error_code("see error code on page"), None);
+    assert_eq!(parse_error_code("ExceededMaximumCycles"), None);
+}
+
+#[test]
+fn parse_error_sources() {
+    assert_eq!(parse_error_source(LOCK_ERROR), Some(("Inputs", 0, "Lock")));
+    assert_eq!(
+        parse_error_source(TYPE_ERROR),
+        Some(("Outputs", 12, "Type"))
+    );
+    assert_eq!(parse_error_source("source: Inputs[x].Lock"), None);
+    assert_eq!(parse_error_source("source: Inputs[0]"), None);
+    assert_eq!(parse_error_source("ExceededMaximumCycles"), None);
+}
+
+#[test]
+fn error_names_of_contracts() {
+    let type_lock = "ckb-bitcoin-spv-type-lock";
+    assert_eq!(error_name(type_lock, 0x05), Some("Unknown"));
+    assert_eq!(
+        error_name(type_lock, 0x51),
+        Some("ReorgCellDepClientIdIsMismatch")
+    );
+    assert_eq!(error_name(type_lock, 0x60), Some("Unreachable"));
+    assert_eq!(
+        error_name(type_lock, 0x61),
+        Some("BootstrapError or UpdateError (code - 0x60)")
+    );
+
+    let permissionless = "ckb-bitcoin-spv-permissionless-update-lock";
+    assert_eq!(error_name(permissionless, 0x1c), Some("TipNotAdvanced"));
+    assert_eq!(error_name(permissionless, 0x1d), None);
+
+    // Contracts which use the consumer crate share its errors.
+    let registry = "ckb-bitcoin-tx-registry-type";
+    assert_eq!(error_name(registry, 0x13), Some("RegistryKeyIsUsed"));
+    assert_eq!(error_name(registry, 0x75), Some("InsertWithoutConsumer"));
+    let mint = "ckb-bitcoin-deposit-mint-type";
+    assert_eq!(error_name(mint, 0x3f), Some("HeaderMmrProofFailed"));
+    assert_eq!(error_name(mint, 0x5b), Some("RecipientMismatch"));
+    let verifier = "ckb-bitcoin-spv-tx-verifier";
+    assert_eq!(
+        error_name(verifier, 0x41),
+        Some("VerifyTxError (code - 0x40)")
+    );
+    assert_eq!(error_name(verifier, 0x50), None);
+
+    assert_eq!(error_name("unknown-contract", 0x01), None);
+}
@@ -1,6 +1,14 @@
+use ckb_bitcoin_spv_verifier::types::{packed as spv_packed, prelude::Pack as VPack};
 use ckb_testtool::{
+    builtin::ALWAYS_SUCCESS,
+    ckb_crypto::secp::{Generator, Privkey},
     ckb_hash::blake2b_256,
-    ckb_types::{bytes::Bytes, core::TransactionBuilder, packed::*, prelude::*},
+    ckb_types::{
+        bytes::Bytes,
+        core::{DepType, ScriptHashType, TransactionBuilder, TransactionView},
+        packed::*,
+        prelude::*,
+    },
     context::Context,
 };
 
@@ -19,76 +27,228 @@ fn run_test(case: &Case) {
     let loader = Loader::default();
     let mut context = Context::default();
 
-    const PASSWORD: &[u8] = &[0x12, 0x34, 0x56, 0x78];
-
-    // Deploy the lock script.
-    let lock_script = {
-        let lock_bin = loader.load_binary("can-update-without-ownership-lock");
-        let lock_out_point = context.deploy_cell(lock_bin);
-        let args = blake2b_256(PASSWORD);
-        context
-            .build_script(&lock_out_point, Default::default())
-            .expect("script")
-            .as_builder()
-            .args((args[..]).pack())
-            .build()
+    let owner_key = Generator::random_privkey();
+    let lock_script = deploy_lock_script(&loader, &mut context, &owner_key, &[]);
+
+    let tx = build_tx(
+        &mut context,
+        &lock_script,
+        None,
+        &case.inputs_capacity,
+        &case.outputs_capacity,
+    );
+    let tx = if case.unlocked {
+        let signature = utilities::sign_sighash_all(&tx, &owner_key);
+        utilities::set_signature(&tx, &signature)
+    } else {
+        tx
     };
 
-    let inputs = case
-        .inputs_capacity
+    let inputs_total: u64 = case.inputs_capacity.iter().copied().sum();
+    let outputs_total: u64 = case.outputs_capacity.iter().copied().sum();
+    let expected_result = case.unlocked || inputs_total <= outputs_total;
+    assert_eq!(case.should_pass, expected_result);
+    if case.should_pass {
+        let _ = context.should_be_passed(&tx, MAX_CYCLES);
+    } else {
+        let _ = context.should_be_failed(&tx, MAX_CYCLES);
+    }
+}
+
+fn deploy_lock_script(
+    loader: &Loader,
+    context: &mut Context,
+    owner_key: &Privkey,
+    binding: &[u8],
+) -> Script {
+    let lock_bin = loader.load_binary("can-update-without-ownership-lock");
+    let lock_out_point = context.deploy_cell(lock_bin);
+    let owner_pubkey = owner_key.pubkey().expect("pubkey").serialize();
+    let args = [&blake2b_256(owner_pubkey)[..20], binding].concat();
+    context
+        .build_script(&lock_out_point, Default::default())
+        .expect("script")
+        .as_builder()
+        .args(args.pack())
+        .build()
+}
+
+fn build_tx(
+    context: &mut Context,
+    lock_script: &Script,
+    type_opt: Option<&Script>,
+    inputs_capacity: &[u64],
+    outputs_capacity: &[u64],
+) -> TransactionView {
+    let inputs = inputs_capacity
         .iter()
         .map(|cap| {
             let output = CellOutput::new_builder()
                 .capacity(cap.pack())
                 .lock(lock_script.clone())
+                .type_(type_opt.cloned().pack())
                 .build();
             let out_point = context.create_cell(output, Bytes::new());
             CellInput::new_builder().previous_output(out_point).build()
         })
         .collect::<Vec<_>>();
 
-    let outputs = case
-        .outputs_capacity
+    let outputs = outputs_capacity
         .iter()
         .map(|cap| {
             CellOutput::new_builder()
                 .capacity(cap.pack())
                 .lock(lock_script.clone())
+                .type_(type_opt.cloned().pack())
                 .build()
         })
         .collect::<Vec<_>>();
     let outputs_data = vec![Bytes::new(); outputs.len()];
 
-    let tx_builder = TransactionBuilder::default()
+    let tx = TransactionBuilder::default()
         .inputs(inputs)
         .outputs(outputs)
-        .outputs_data(outputs_data.pack());
-    let tx = if case.unlocked {
-        let witness = {
-            let type_args = BytesOpt::new_builder()
-                .set(Some(Pack::pack(PASSWORD)))
-                .build();
-            let witness_args = WitnessArgs::new_builder().lock(type_args).build();
-            witness_args.as_bytes()
-        };
-        tx_builder.witness(witness.pack())
-    } else {
-        tx_builder
-    }
-    .build();
-    let tx = context.complete_tx(tx);
+        .outputs_data(outputs_data.pack())
+        .build();
+    context.complete_tx(tx)
+}
 
-    let inputs_total: u64 = case.inputs_capacity.iter().copied().sum();
-    let outputs_total: u64 = case.outputs_capacity.iter().copied().sum();
-    let expected_result = case.unlocked || inputs_total <= outputs_total;
-    assert_eq!(case.should_pass, expected_result);
-    if case.should_pass {
-        let _ = context.should_be_passed(&tx, MAX_CYCLES);
-    } else {
+#[test]
+fn replay_owner_witness() {
+    utilities::setup();
+
+    let loader = Loader::default();
+    let mut context = Context::default();
+
+    let owner_key = Generator::random_privkey();
+    let other_key = Generator::random_privkey();
+    let lock_script = deploy_lock_script(&loader, &mut context, &owner_key, &[]);
+
+    // The owner takes some capacity away in an earlier transaction.
+    let earlier_tx = build_tx(&mut context, &lock_script, None, &[1000], &[500]);
+    let signature = utilities::sign_sighash_all(&earlier_tx, &owner_key);
+    let earlier_tx = utilities::set_signature(&earlier_tx, &signature);
+    let _ = context.should_be_passed(&earlier_tx, MAX_CYCLES);
+
+    // Anyone could see the witness, but it couldn't be used again.
+    let tx = build_tx(&mut context, &lock_script, None, &[500], &[1]);
+    let owner_witness = earlier_tx.witnesses().get(0).expect("witness");
+    let replayed_tx = tx
+        .as_advanced_builder()
+        .set_witnesses(vec![owner_witness])
+        .build();
+    let _ = context.should_be_failed(&replayed_tx, MAX_CYCLES);
+
+    // A signature which is not signed by the owner.
+    let signature = utilities::sign_sighash_all(&tx, &other_key);
+    let unauthorized_tx = utilities::set_signature(&tx, &signature);
+    let _ = context.should_be_failed(&unauthorized_tx, MAX_CYCLES);
+
+    let signature = utilities::sign_sighash_all(&tx, &owner_key);
+    let signed_tx = utilities::set_signature(&tx, &signature);
+    let _ = context.should_be_passed(&signed_tx, MAX_CYCLES);
+}
+
+enum Binding {
+    TypeHash,
+    CodeHash,
+}
+
+fn run_binding_test(binding: Binding) {
+    utilities::setup();
+
+    let loader = Loader::default();
+    let mut context = Context::default();
+
+    let owner_key = Generator::random_privkey();
+
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let bound_type_script = context
+        .build_script(&always_success_out_point, Default::default())
+        .expect("type script")
+        .as_builder()
+        .hash_type(ScriptHashType::Data1.into())
+        .build();
+    let other_type_script = bound_type_script
+        .clone()
+        .as_builder()
+        .hash_type(ScriptHashType::Data.into())
+        .build();
+
+    let binding = match binding {
+        Binding::TypeHash => bound_type_script.calc_script_hash().as_slice().to_vec(),
+        Binding::CodeHash => [
+            bound_type_script.code_hash().as_slice(),
+            bound_type_script.hash_type().as_slice(),
+        ]
+        .concat(),
+    };
+    let lock_script = deploy_lock_script(&loader, &mut context, &owner_key, &binding);
+
+    let tx = build_tx(
+        &mut context,
+        &lock_script,
+        Some(&bound_type_script),
+        &[1000],
+        &[1000],
+    );
+    let _ = context.should_be_passed(&tx, MAX_CYCLES);
+
+    for type_opt in [None, Some(&other_type_script)] {
+        let tx = build_tx(&mut context, &lock_script, type_opt, &[1000], &[1000]);
+        let _ = context.should_be_failed(&tx, MAX_CYCLES);
+
+        // The owner could still unlock cells with any type.
+        let signature = utilities::sign_sighash_all(&tx, &owner_key);
+        let signed_tx = utilities::set_signature(&tx, &signature);
+        let _ = context.should_be_passed(&signed_tx, MAX_CYCLES);
+
+        // Cells with the bound type couldn't be changed to other types.
+        let tx = build_tx(
+            &mut context,
+            &lock_script,
+            Some(&bound_type_script),
+            &[1000],
+            &[1000],
+        );
+        let outputs = tx
+            .outputs()
+            .into_iter()
+            .map(|output| output.as_builder().type_(type_opt.cloned().pack()).build())
+            .collect::<Vec<_>>();
+        let tx = tx.as_advanced_builder().set_outputs(outputs).build();
         let _ = context.should_be_failed(&tx, MAX_CYCLES);
+
+        let signature = utilities::sign_sighash_all(&tx, &owner_key);
+        let signed_tx = utilities::set_signature(&tx, &signature);
+        let _ = context.should_be_passed(&signed_tx, MAX_CYCLES);
     }
 }
 
+#[test]
+fn bound_to_type_hash() {
+    run_binding_test(Binding::TypeHash);
+}
+
+#[test]
+fn bound_to_code_hash() {
+    run_binding_test(Binding::CodeHash);
+}
+
+#[test]
+fn malformed_binding() {
+    utilities::setup();
+
+    let loader = Loader::default();
+    let mut context = Context::default();
+
+    let owner_key = Generator::random_privkey();
+    let lock_script = deploy_lock_script(&loader, &mut context, &owner_key, &[0u8; 31]);
+
+    let tx = build_tx(&mut context, &lock_script, None, &[1000], &[1000]);
+    let _ = context.should_be_failed(&tx, MAX_CYCLES);
+}
+
 #[test]
 fn unchanged_case_1() {
     let case = Case {
@@ -220,3 +380,245 @@ fn decrease_case_3b() {
     };
     run_test(&case);
 }
+
+const POOL_CAP: u64 = 10_000;
+const HEIGHT: u32 = 2016 * 400;
+
+struct RewardCase {
+    max_reward: Option<u64>,
+    reward: u64,
+    // Capacity which is moved from SPV cells to the reward pool.
+    spv_cells_lost: u64,
+    new_height: u32,
+    tip_changed: bool,
+    // The data of the reward pool cells is the SPV type hash.
+    pool_marked: bool,
+    should_pass: bool,
+}
+
+#[test]
+fn reward_case_1() {
+    let case = RewardCase {
+        max_reward: Some(100),
+        reward: 100,
+        spv_cells_lost: 0,
+        new_height: HEIGHT + 1,
+        tip_changed: true,
+        pool_marked: true,
+        should_pass: true,
+    };
+    run_reward_test(&case);
+}
+
+#[test]
+fn reward_case_2() {
+    let case = RewardCase {
+        max_reward: Some(100),
+        reward: 0,
+        spv_cells_lost: 0,
+        new_height: HEIGHT,
+        tip_changed: false,
+        pool_marked: true,
+        should_pass: true,
+    };
+    run_reward_test(&case);
+}
+
+#[test]
+fn reward_case_3() {
+    let case = RewardCase {
+        max_reward: Some(100),
+        reward: 101,
+        spv_cells_lost: 0,
+        new_height: HEIGHT + 1,
+        tip_changed: true,
+        pool_marked: true,
+        should_pass: false,
+    };
+    run_reward_test(&case);
+}
+
+#[test]
+fn reward_case_4() {
+    let case = RewardCase {
+        max_reward: Some(100),
+        reward: 100,
+        spv_cells_lost: 0,
+        new_height: HEIGHT,
+        tip_changed: true,
+        pool_marked: true,
+        should_pass: false,
+    };
+    run_reward_test(&case);
+}
+
+#[test]
+fn reward_case_5() {
+    let case = RewardCase {
+        max_reward: Some(100),
+        reward: 100,
+        spv_cells_lost: 0,
+        new_height: HEIGHT + 1,
+        tip_changed: false,
+        pool_marked: true,
+        should_pass: false,
+    };
+    run_reward_test(&case);
+}
+
+#[test]
+fn reward_case_6() {
+    let case = RewardCase {
+        max_reward: Some(100),
+        reward: 0,
+        spv_cells_lost: 100,
+        new_height: HEIGHT + 1,
+        tip_changed: true,
+        pool_marked: true,
+        should_pass: false,
+    };
+    run_reward_test(&case);
+}
+
+#[test]
+fn reward_case_7() {
+    let case = RewardCase {
+        max_reward: None,
+        reward: 100,
+        spv_cells_lost: 0,
+        new_height: HEIGHT + 1,
+        tip_changed: true,
+        pool_marked: true,
+        should_pass: false,
+    };
+    run_reward_test(&case);
+}
+
+#[test]
+fn reward_case_8() {
+    let case = RewardCase {
+        max_reward: Some(100),
+        reward: 100,
+        spv_cells_lost: 0,
+        new_height: HEIGHT + 1,
+        tip_changed: true,
+        pool_marked: false,
+        should_pass: false,
+    };
+    run_reward_test(&case);
+}
+
+fn run_reward_test(case: &RewardCase) {
+    utilities::setup();
+
+    let loader = Loader::default();
+    let mut context = Context::default();
+
+    let owner_key = Generator::random_privkey();
+
+    // Use the always success script to mock the Bitcoin SPV type script.
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let spv_type_script = context
+        .build_script(&always_success_out_point, Bytes::from(vec![1]))
+        .expect("type script")
+        .as_builder()
+        .hash_type(ScriptHashType::Data1.into())
+        .build();
+    let spv_type_hash = spv_type_script.calc_script_hash();
+    let always_success_lock_script = context
+        .build_script(&always_success_out_point, Default::default())
+        .expect("lock script");
+
+    let mut binding = spv_type_hash.as_slice().to_vec();
+    if let Some(max_reward) = case.max_reward {
+        binding.extend_from_slice(&max_reward.to_le_bytes());
+    }
+    let lock_script = deploy_lock_script(&loader, &mut context, &owner_key, &binding);
+
+    let spv_cell = |capacity: u64| {
+        CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(lock_script.clone())
+            .type_(Some(spv_type_script.clone()).pack())
+            .build()
+    };
+    let pool_data = if case.pool_marked {
+        spv_type_hash.as_bytes()
+    } else {
+        Bytes::new()
+    };
+    let pool_cell = |capacity: u64| {
+        CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(lock_script.clone())
+            .build()
+    };
+    let spv_info = |tip_client_id: u8| {
+        spv_packed::SpvInfo::new_builder()
+            .tip_client_id(tip_client_id.into())
+            .build()
+            .as_bytes()
+    };
+    let spv_client = |id: u8, height: u32| {
+        let block = utilities::MockBlock::mine(height, vec![]);
+        let mut client = block.bootstrap().tip_client();
+        client.id = id;
+        let packed_client: spv_packed::SpvClient = client.pack();
+        packed_client.as_bytes()
+    };
+
+    // The current tip client.
+    let cell_dep = {
+        let output = CellOutput::new_builder()
+            .capacity(SPV_CELL_CAP.pack())
+            .lock(always_success_lock_script)
+            .type_(Some(spv_type_script.clone()).pack())
+            .build();
+        let out_point = context.create_cell(output, spv_client(0, HEIGHT));
+        CellDep::new_builder()
+            .out_point(out_point)
+            .dep_type(DepType::Code.into())
+            .build()
+    };
+
+    let inputs = [
+        (spv_cell(SPV_CELL_CAP), spv_info(0)),
+        (spv_cell(SPV_CELL_CAP), spv_client(1, HEIGHT - 1)),
+        (pool_cell(POOL_CAP), pool_data.clone()),
+    ]
+    .into_iter()
+    .map(|(output, data)| {
+        let out_point = context.create_cell(output, data);
+        CellInput::new_builder().previous_output(out_point).build()
+    })
+    .collect::<Vec<_>>();
+
+    let new_tip_client_id = if case.tip_changed { 1 } else { 0 };
+    let (outputs, outputs_data): (Vec<_>, Vec<_>) = [
+        (spv_cell(SPV_CELL_CAP), spv_info(new_tip_client_id)),
+        (
+            spv_cell(SPV_CELL_CAP - case.spv_cells_lost),
+            spv_client(1, case.new_height),
+        ),
+        (
+            pool_cell(POOL_CAP - case.reward + case.spv_cells_lost),
+            pool_data.clone(),
+        ),
+    ]
+    .into_iter()
+    .unzip();
+
+    let tx = TransactionBuilder::default()
+        .cell_dep(cell_dep)
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    if case.should_pass {
+        let _ = context.should_be_passed(&tx, MAX_CYCLES);
+    } else {
+        let _ = context.should_be_failed(&tx, MAX_CYCLES);
+    }
+}
@@ -0,0 +1,445 @@
+use bitcoin::{
+    absolute::LockTime, hashes::Hash as _, transaction::Version, Amount, OutPoint, ScriptBuf,
+    Sequence, Transaction, TxIn, TxOut, Witness,
+};
+use ckb_bitcoin_spv_verifier::types::{core, packed, prelude::Pack as VPack};
+use ckb_testtool::{
+    builtin::ALWAYS_SUCCESS,
+    ckb_types::{
+        bytes::Bytes,
+        core::{DepType, TransactionBuilder},
+        packed::*,
+        prelude::*,
+    },
+    context::Context,
+};
+
+use crate::{prelude::*, utilities, Loader};
+
+const HEIGHT: u32 = 2016 * 400;
+const CUSTODY_SCRIPT: [u8; 22] = [
+    0x00, 0x14, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+    0x0f, 0x10, 0x11, 0x12, 0x13, 0x14,
+];
+
+struct Case {
+    deposited: u64,
+    minted: u128,
+    confirmations: u32,
+    to_recipient: bool,
+    replayed: bool,
+    other_key_inserted: bool,
+    should_pass: bool,
+}
+
+const REGISTRY_TYPE_ID: [u8; 32] = [7u8; 32];
+// A key which is less than any txid in tests.
+const OTHER_KEY: [u8; 32] = {
+    let mut key = [0u8; 32];
+    key[31] = 1;
+    key
+};
+
+fn registry_cell_data(key: &[u8], next: &[u8]) -> Bytes {
+    let mut data = key.to_vec();
+    data.extend_from_slice(next);
+    Bytes::from(data)
+}
+
+fn deposit_transaction(deposited: u64, recipient: &[u8]) -> Transaction {
+    let mut op_return = vec![0x6a, 0x20];
+    op_return.extend_from_slice(recipient);
+    Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::default(),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::default(),
+        }],
+        output: vec![
+            TxOut {
+                value: Amount::from_sat(deposited),
+                script_pubkey: ScriptBuf::from_bytes(CUSTODY_SCRIPT.to_vec()),
+            },
+            TxOut {
+                value: Amount::ZERO,
+                script_pubkey: ScriptBuf::from_bytes(op_return),
+            },
+        ],
+    }
+}
+
+// The code hash, the hash type and the type id of the registry.
+fn mint_registry_args(
+    context: &mut Context,
+    registry_out_point: &ckb_testtool::ckb_types::packed::OutPoint,
+) -> Vec<u8> {
+    let script = context
+        .build_script(registry_out_point, Bytes::new())
+        .expect("registry type script");
+    let mut args = script.code_hash().as_slice().to_vec();
+    args.extend_from_slice(script.hash_type().as_slice());
+    args.extend_from_slice(&REGISTRY_TYPE_ID);
+    args
+}
+
+// The registry which is dedicated to the mint type script.
+fn registry_type_script(
+    context: &mut Context,
+    registry_out_point: &ckb_testtool::ckb_types::packed::OutPoint,
+    mint_type_script: &Script,
+) -> Script {
+    let mut args = REGISTRY_TYPE_ID.to_vec();
+    args.extend_from_slice(mint_type_script.calc_script_hash().as_slice());
+    context
+        .build_script(registry_out_point, Bytes::from(args))
+        .expect("registry type script")
+}
+
+fn run_test(case: &Case) {
+    utilities::setup();
+
+    let loader = Loader::default();
+    let mut context = Context::default();
+
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let recipient_lock_script = context
+        .build_script(&always_success_out_point, Bytes::from(vec![1]))
+        .expect("lock script");
+    let other_lock_script = context
+        .build_script(&always_success_out_point, Bytes::from(vec![2]))
+        .expect("lock script");
+
+    let spv_type_script = {
+        let args = packed::SpvTypeArgs::new_builder()
+            .type_id(core::Hash::from_bytes_ref(&[0u8; 32]).pack())
+            .clients_count(3u8.into())
+            .build();
+        let bin = loader.load_binary("ckb-bitcoin-spv-type-lock");
+        let out_point = context.deploy_cell(bin);
+        context
+            .build_script(&out_point, Default::default())
+            .expect("spv type script")
+            .as_builder()
+            .args(args.as_slice().pack())
+            .build()
+    };
+
+    let recipient = recipient_lock_script.calc_script_hash();
+    let deposit_tx = deposit_transaction(case.deposited, recipient.as_slice());
+    let block = utilities::MockBlock::mine(HEIGHT, vec![deposit_tx.clone()]);
+
+    let cell_dep_spv_client = {
+        let mut client = block.bootstrap().tip_client();
+        client.id = 1;
+        let spv_client: packed::SpvClient = client.pack();
+        let output = CellOutput::new_builder()
+            .capacity(SPV_CELL_CAP.pack())
+            .lock(recipient_lock_script.clone())
+            .type_(Some(spv_type_script.clone()).pack())
+            .build();
+        let out_point = context.create_cell(output, spv_client.as_bytes());
+        CellDep::new_builder()
+            .out_point(out_point)
+            .dep_type(DepType::Code.into())
+            .build()
+    };
+
+    let registry_out_point = {
+        let bin = loader.load_binary("ckb-bitcoin-tx-registry-type");
+        context.deploy_cell(bin)
+    };
+
+    let mint_type_script = {
+        let mut args = spv_type_script.calc_script_hash().as_slice().to_vec();
+        args.extend_from_slice(&mint_registry_args(&mut context, &registry_out_point));
+        args.extend_from_slice(&case.confirmations.to_le_bytes());
+        args.extend_from_slice(&CUSTODY_SCRIPT);
+        let bin = loader.load_binary("ckb-bitcoin-deposit-mint-type");
+        let out_point = context.deploy_cell(bin);
+        context
+            .build_script(&out_point, Bytes::from(args))
+            .expect("mint type script")
+    };
+    let registry_type_script =
+        registry_type_script(&mut context, &registry_out_point, &mint_type_script);
+
+    let input = {
+        let output = CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(other_lock_script.clone())
+            .build();
+        let out_point = context.create_cell(output, Bytes::new());
+        CellInput::new_builder().previous_output(out_point).build()
+    };
+
+    let output = {
+        let lock_script = if case.to_recipient {
+            recipient_lock_script
+        } else {
+            other_lock_script
+        };
+        CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(lock_script)
+            .type_(Some(mint_type_script).pack())
+            .build()
+    };
+    let output_data = case.minted.to_le_bytes().to_vec();
+
+    let registry_cell = CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(other_lock_script.clone())
+        .type_(Some(registry_type_script).pack())
+        .build();
+    let txid = deposit_tx.txid().to_byte_array();
+    let (registry_inputs_data, registry_outputs_data) = if case.replayed {
+        // The txid is inserted before, the registry is unchanged.
+        let data = vec![registry_cell_data(&txid, &[0xff; 32])];
+        (data.clone(), data)
+    } else {
+        let inputs_data = vec![registry_cell_data(&[0x00; 32], &[0xff; 32])];
+        let outputs_data = if case.other_key_inserted {
+            vec![
+                registry_cell_data(&[0x00; 32], &OTHER_KEY),
+                registry_cell_data(&OTHER_KEY, &txid),
+                registry_cell_data(&txid, &[0xff; 32]),
+            ]
+        } else {
+            vec![
+                registry_cell_data(&[0x00; 32], &txid),
+                registry_cell_data(&txid, &[0xff; 32]),
+            ]
+        };
+        (inputs_data, outputs_data)
+    };
+    let registry_inputs = registry_inputs_data
+        .into_iter()
+        .map(|data| {
+            let out_point = context.create_cell(registry_cell.clone(), data);
+            CellInput::new_builder().previous_output(out_point).build()
+        })
+        .collect::<Vec<_>>();
+    let registry_outputs = registry_outputs_data
+        .iter()
+        .map(|_| registry_cell.clone())
+        .collect::<Vec<_>>();
+
+    let witness = {
+        let raw_tx = bitcoin::consensus::serialize(&deposit_tx);
+        let tx_proof = block.transaction_proof(1);
+        let items = BytesVec::new_builder()
+            .push(Pack::pack(&raw_tx))
+            .push(Pack::pack(tx_proof.as_slice()))
+            .build();
+        let type_args = BytesOpt::new_builder()
+            .set(Some(Pack::pack(items.as_slice())))
+            .build();
+        let witness_args = WitnessArgs::new_builder().output_type(type_args).build();
+        witness_args.as_bytes()
+    };
+
+    let tx = TransactionBuilder::default()
+        .cell_dep(cell_dep_spv_client)
+        .input(input)
+        .inputs(registry_inputs)
+        .output(output)
+        .output_data(Pack::pack(&output_data))
+        .outputs(registry_outputs)
+        .outputs_data(registry_outputs_data.pack())
+        .witness(Pack::pack(&witness))
+        .build();
+    let tx = context.complete_tx(tx);
+
+    if case.should_pass {
+        let _ = context.should_be_passed(&tx, MAX_CYCLES);
+    } else {
+        let _ = context.should_be_failed(&tx, MAX_CYCLES);
+    }
+}
+
+#[test]
+fn normal_case_1() {
+    let case = Case {
+        deposited: 100_000,
+        minted: 100_000,
+        confirmations: 0,
+        to_recipient: true,
+        replayed: false,
+        other_key_inserted: false,
+        should_pass: true,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn mint_more_than_deposited() {
+    let case = Case {
+        deposited: 100_000,
+        minted: 100_001,
+        confirmations: 0,
+        to_recipient: true,
+        replayed: false,
+        other_key_inserted: false,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn mint_less_than_deposited() {
+    let case = Case {
+        deposited: 100_000,
+        minted: 99_999,
+        confirmations: 0,
+        to_recipient: true,
+        replayed: false,
+        other_key_inserted: false,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn mint_without_enough_confirmations() {
+    let case = Case {
+        deposited: 100_000,
+        minted: 100_000,
+        confirmations: 6,
+        to_recipient: true,
+        replayed: false,
+        other_key_inserted: false,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn mint_to_others() {
+    let case = Case {
+        deposited: 100_000,
+        minted: 100_000,
+        confirmations: 0,
+        to_recipient: false,
+        replayed: false,
+        other_key_inserted: false,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn mint_with_a_replayed_deposit() {
+    let case = Case {
+        deposited: 100_000,
+        minted: 100_000,
+        confirmations: 0,
+        to_recipient: true,
+        replayed: true,
+        other_key_inserted: false,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn mint_with_other_keys_inserted() {
+    let case = Case {
+        deposited: 100_000,
+        minted: 100_000,
+        confirmations: 0,
+        to_recipient: true,
+        replayed: false,
+        other_key_inserted: true,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn transfer_without_proof() {
+    test_transfer(false);
+}
+
+#[test]
+fn transfer_with_a_key_inserted() {
+    test_transfer(true);
+}
+
+fn test_transfer(with_key_inserted: bool) {
+    utilities::setup();
+
+    let loader = Loader::default();
+    let mut context = Context::default();
+
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let lock_script = context
+        .build_script(&always_success_out_point, Bytes::from(vec![1]))
+        .expect("lock script");
+
+    let registry_out_point = {
+        let bin = loader.load_binary("ckb-bitcoin-tx-registry-type");
+        context.deploy_cell(bin)
+    };
+
+    let mint_type_script = {
+        let mut args = vec![0u8; 32];
+        args.extend_from_slice(&mint_registry_args(&mut context, &registry_out_point));
+        args.extend_from_slice(&0u32.to_le_bytes());
+        args.extend_from_slice(&CUSTODY_SCRIPT);
+        let bin = loader.load_binary("ckb-bitcoin-deposit-mint-type");
+        let out_point = context.deploy_cell(bin);
+        context
+            .build_script(&out_point, Bytes::from(args))
+            .expect("mint type script")
+    };
+
+    let token_cell = CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(lock_script.clone())
+        .type_(Some(mint_type_script.clone()).pack())
+        .build();
+    let input = {
+        let data = Bytes::from(100u128.to_le_bytes().to_vec());
+        let out_point = context.create_cell(token_cell.clone(), data);
+        CellInput::new_builder().previous_output(out_point).build()
+    };
+    let mut inputs = vec![input];
+    let mut outputs = vec![token_cell.clone(), token_cell];
+    let mut outputs_data = vec![
+        Bytes::from(60u128.to_le_bytes().to_vec()),
+        Bytes::from(40u128.to_le_bytes().to_vec()),
+    ];
+
+    if with_key_inserted {
+        let registry_type_script =
+            registry_type_script(&mut context, &registry_out_point, &mint_type_script);
+        let registry_cell = CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(lock_script.clone())
+            .type_(Some(registry_type_script).pack())
+            .build();
+        let data = registry_cell_data(&[0x00; 32], &[0xff; 32]);
+        let out_point = context.create_cell(registry_cell.clone(), data);
+        inputs.push(CellInput::new_builder().previous_output(out_point).build());
+        outputs.push(registry_cell.clone());
+        outputs.push(registry_cell);
+        outputs_data.push(registry_cell_data(&[0x00; 32], &OTHER_KEY));
+        outputs_data.push(registry_cell_data(&OTHER_KEY, &[0xff; 32]));
+    }
+
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    if with_key_inserted {
+        let _ = context.should_be_failed(&tx, MAX_CYCLES);
+    } else {
+        let _ = context.should_be_passed(&tx, MAX_CYCLES);
+    }
+}
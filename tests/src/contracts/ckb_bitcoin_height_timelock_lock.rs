@@ -0,0 +1,232 @@
+use ckb_bitcoin_spv_verifier::types::{core, packed, prelude::Pack as VPack};
+use ckb_testtool::{
+    builtin::ALWAYS_SUCCESS,
+    ckb_types::{
+        bytes::Bytes,
+        core::{DepType, TransactionBuilder},
+        packed::*,
+        prelude::*,
+    },
+    context::Context,
+};
+
+use crate::{prelude::*, utilities, Loader};
+
+// Heights of the clients, the index is the client id.
+const CLIENTS_HEIGHTS: [u32; 2] = [2016 * 400, 2016 * 401];
+
+struct Case {
+    height: u32,
+    confirmations: u32,
+    tip_client_id: u8,
+    with_info: bool,
+    with_owner: bool,
+    should_pass: bool,
+}
+
+#[test]
+fn normal_case_1() {
+    let case = Case {
+        height: CLIENTS_HEIGHTS[0],
+        confirmations: 0,
+        tip_client_id: 0,
+        with_info: true,
+        with_owner: true,
+        should_pass: true,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn normal_case_2() {
+    let case = Case {
+        height: CLIENTS_HEIGHTS[0],
+        confirmations: 2016,
+        tip_client_id: 1,
+        with_info: true,
+        with_owner: true,
+        should_pass: true,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn height_not_reached() {
+    let case = Case {
+        height: CLIENTS_HEIGHTS[0] + 1,
+        confirmations: 0,
+        tip_client_id: 0,
+        with_info: true,
+        with_owner: true,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn confirmations_not_enough() {
+    let case = Case {
+        height: CLIENTS_HEIGHTS[0],
+        confirmations: 2017,
+        tip_client_id: 1,
+        with_info: true,
+        with_owner: true,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn height_only_reached_by_stale_client() {
+    let case = Case {
+        height: CLIENTS_HEIGHTS[1],
+        confirmations: 0,
+        tip_client_id: 0,
+        with_info: true,
+        with_owner: true,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn without_info_cell() {
+    let case = Case {
+        height: CLIENTS_HEIGHTS[0],
+        confirmations: 0,
+        tip_client_id: 0,
+        with_info: false,
+        with_owner: true,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn without_owner() {
+    let case = Case {
+        height: CLIENTS_HEIGHTS[0],
+        confirmations: 0,
+        tip_client_id: 0,
+        with_info: true,
+        with_owner: false,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+fn run_test(case: &Case) {
+    utilities::setup();
+
+    let loader = Loader::default();
+    let mut context = Context::default();
+
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let owner_lock_script = context
+        .build_script(&always_success_out_point, Bytes::from(vec![1]))
+        .expect("lock script");
+    let other_lock_script = context
+        .build_script(&always_success_out_point, Bytes::from(vec![2]))
+        .expect("lock script");
+
+    let spv_type_script = {
+        let args = packed::SpvTypeArgs::new_builder()
+            .type_id(core::Hash::from_bytes_ref(&[0u8; 32]).pack())
+            .clients_count(2u8.into())
+            .build();
+        let bin = loader.load_binary("ckb-bitcoin-spv-type-lock");
+        let out_point = context.deploy_cell(bin);
+        context
+            .build_script(&out_point, Default::default())
+            .expect("spv type script")
+            .as_builder()
+            .args(args.as_slice().pack())
+            .build()
+    };
+    let spv_cell = CellOutput::new_builder()
+        .capacity(SPV_CELL_CAP.pack())
+        .lock(other_lock_script.clone())
+        .type_(Some(spv_type_script.clone()).pack())
+        .build();
+
+    let mut cell_deps = Vec::new();
+    if case.with_info {
+        let spv_info = packed::SpvInfo::new_builder()
+            .tip_client_id(case.tip_client_id.into())
+            .build();
+        let out_point = context.create_cell(spv_cell.clone(), spv_info.as_bytes());
+        let cell_dep = CellDep::new_builder()
+            .out_point(out_point)
+            .dep_type(DepType::Code.into())
+            .build();
+        cell_deps.push(cell_dep);
+    }
+    for (id, height) in CLIENTS_HEIGHTS.into_iter().enumerate() {
+        let block = utilities::MockBlock::mine(height, vec![]);
+        let mut client = block.bootstrap().tip_client();
+        client.id = id as u8;
+        let spv_client: packed::SpvClient = client.pack();
+        let out_point = context.create_cell(spv_cell.clone(), spv_client.as_bytes());
+        let cell_dep = CellDep::new_builder()
+            .out_point(out_point)
+            .dep_type(DepType::Code.into())
+            .build();
+        cell_deps.push(cell_dep);
+    }
+
+    let timelock_lock_script = {
+        let mut args = spv_type_script.calc_script_hash().as_slice().to_vec();
+        args.extend_from_slice(&case.height.to_le_bytes());
+        args.extend_from_slice(&case.confirmations.to_le_bytes());
+        args.extend_from_slice(owner_lock_script.calc_script_hash().as_slice());
+        let bin = loader.load_binary("ckb-bitcoin-height-timelock-lock");
+        let out_point = context.deploy_cell(bin);
+        context
+            .build_script(&out_point, Bytes::from(args))
+            .expect("timelock lock script")
+    };
+
+    let mut inputs = Vec::new();
+    {
+        let output = CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(timelock_lock_script)
+            .build();
+        let out_point = context.create_cell(output, Bytes::new());
+        let input = CellInput::new_builder().previous_output(out_point).build();
+        inputs.push(input);
+    }
+    {
+        let lock_script = if case.with_owner {
+            owner_lock_script.clone()
+        } else {
+            other_lock_script.clone()
+        };
+        let output = CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(lock_script)
+            .build();
+        let out_point = context.create_cell(output, Bytes::new());
+        let input = CellInput::new_builder().previous_output(out_point).build();
+        inputs.push(input);
+    }
+
+    let output = CellOutput::new_builder()
+        .capacity(2000u64.pack())
+        .lock(owner_lock_script)
+        .build();
+
+    let tx = TransactionBuilder::default()
+        .cell_deps(cell_deps)
+        .inputs(inputs)
+        .output(output)
+        .output_data(Pack::pack(&Bytes::new()))
+        .build();
+    let tx = context.complete_tx(tx);
+
+    if case.should_pass {
+        let _ = context.should_be_passed(&tx, MAX_CYCLES);
+    } else {
+        let _ = context.should_be_failed(&tx, MAX_CYCLES);
+    }
+}
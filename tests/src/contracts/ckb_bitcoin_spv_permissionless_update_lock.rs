@@ -0,0 +1,775 @@
+use ckb_bitcoin_spv_verifier::types::{packed as spv_packed, prelude::Pack as VPack};
+use ckb_testtool::{
+    builtin::ALWAYS_SUCCESS,
+    ckb_crypto::secp::Generator,
+    ckb_hash::blake2b_256,
+    ckb_types::{
+        bytes::Bytes,
+        core::{DepType, ScriptHashType, TransactionBuilder},
+        packed::*,
+        prelude::*,
+    },
+    context::Context,
+};
+
+use crate::{prelude::*, utilities, Loader};
+
+#[derive(Clone, Copy)]
+enum Signer {
+    // No signature in the witness.
+    Nobody,
+    Owner,
+    // A valid signature, but not signed by the owner.
+    Other,
+    // The signature of the owner, but it is broken.
+    Corrupted,
+    // The signature of the owner, but for another transaction.
+    Replayed,
+}
+
+#[derive(Clone, Copy)]
+enum Type {
+    Spv,
+    // Same code hash as the SPV type, but the hash type is different.
+    Mismatched,
+    // Same code hash and hash type as the SPV type, but another instance.
+    Foreign,
+    Nothing,
+}
+
+struct Case {
+    inputs_capacity: Vec<u64>,
+    outputs_capacity: Vec<u64>,
+    inputs_type: Type,
+    outputs_type: Type,
+    signer: Signer,
+    lock_as_type: bool,
+    should_pass: bool,
+}
+
+#[test]
+fn unchanged_case_1() {
+    let case = Case {
+        inputs_capacity: vec![1000],
+        outputs_capacity: vec![1000],
+        inputs_type: Type::Spv,
+        outputs_type: Type::Spv,
+        signer: Signer::Nobody,
+        lock_as_type: false,
+        should_pass: true,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn unchanged_case_2() {
+    let case = Case {
+        inputs_capacity: vec![499, 501],
+        outputs_capacity: vec![1000],
+        inputs_type: Type::Spv,
+        outputs_type: Type::Spv,
+        signer: Signer::Nobody,
+        lock_as_type: false,
+        should_pass: true,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn increase_case_1() {
+    let case = Case {
+        inputs_capacity: vec![1000],
+        outputs_capacity: vec![499, 502],
+        inputs_type: Type::Spv,
+        outputs_type: Type::Spv,
+        signer: Signer::Nobody,
+        lock_as_type: false,
+        should_pass: true,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn decrease_case_1() {
+    let case = Case {
+        inputs_capacity: vec![1000],
+        outputs_capacity: vec![999],
+        inputs_type: Type::Spv,
+        outputs_type: Type::Spv,
+        signer: Signer::Nobody,
+        lock_as_type: false,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn decrease_case_2() {
+    let case = Case {
+        inputs_capacity: vec![499, 501],
+        outputs_capacity: vec![499, 500],
+        inputs_type: Type::Spv,
+        outputs_type: Type::Spv,
+        signer: Signer::Nobody,
+        lock_as_type: false,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn owner_case_1() {
+    let case = Case {
+        inputs_capacity: vec![1000],
+        outputs_capacity: vec![1],
+        inputs_type: Type::Spv,
+        outputs_type: Type::Spv,
+        signer: Signer::Owner,
+        lock_as_type: false,
+        should_pass: true,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn owner_case_2() {
+    let case = Case {
+        inputs_capacity: vec![499, 501],
+        outputs_capacity: vec![],
+        inputs_type: Type::Spv,
+        outputs_type: Type::Nothing,
+        signer: Signer::Owner,
+        lock_as_type: false,
+        should_pass: true,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn owner_case_3() {
+    let case = Case {
+        inputs_capacity: vec![1000],
+        outputs_capacity: vec![1000],
+        inputs_type: Type::Nothing,
+        outputs_type: Type::Mismatched,
+        signer: Signer::Owner,
+        lock_as_type: false,
+        should_pass: true,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn not_owner_case_1() {
+    let case = Case {
+        inputs_capacity: vec![1000],
+        outputs_capacity: vec![1000],
+        inputs_type: Type::Spv,
+        outputs_type: Type::Spv,
+        signer: Signer::Other,
+        lock_as_type: false,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn not_owner_case_2() {
+    let case = Case {
+        inputs_capacity: vec![1000],
+        outputs_capacity: vec![1000],
+        inputs_type: Type::Spv,
+        outputs_type: Type::Spv,
+        signer: Signer::Corrupted,
+        lock_as_type: false,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn not_owner_case_3() {
+    let case = Case {
+        inputs_capacity: vec![1000],
+        outputs_capacity: vec![1],
+        inputs_type: Type::Spv,
+        outputs_type: Type::Spv,
+        signer: Signer::Replayed,
+        lock_as_type: false,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn type_case_1() {
+    let case = Case {
+        inputs_capacity: vec![1000],
+        outputs_capacity: vec![1000],
+        inputs_type: Type::Nothing,
+        outputs_type: Type::Spv,
+        signer: Signer::Nobody,
+        lock_as_type: false,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn type_case_2() {
+    let case = Case {
+        inputs_capacity: vec![1000],
+        outputs_capacity: vec![1000],
+        inputs_type: Type::Spv,
+        outputs_type: Type::Nothing,
+        signer: Signer::Nobody,
+        lock_as_type: false,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn type_case_3() {
+    let case = Case {
+        inputs_capacity: vec![1000],
+        outputs_capacity: vec![1000],
+        inputs_type: Type::Mismatched,
+        outputs_type: Type::Spv,
+        signer: Signer::Nobody,
+        lock_as_type: false,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn type_case_4() {
+    let case = Case {
+        inputs_capacity: vec![1000],
+        outputs_capacity: vec![1000],
+        inputs_type: Type::Spv,
+        outputs_type: Type::Mismatched,
+        signer: Signer::Nobody,
+        lock_as_type: false,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn type_case_5() {
+    let case = Case {
+        inputs_capacity: vec![1000],
+        outputs_capacity: vec![1000],
+        inputs_type: Type::Foreign,
+        outputs_type: Type::Foreign,
+        signer: Signer::Nobody,
+        lock_as_type: false,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn type_case_6() {
+    let case = Case {
+        inputs_capacity: vec![1000],
+        outputs_capacity: vec![1000],
+        inputs_type: Type::Spv,
+        outputs_type: Type::Foreign,
+        signer: Signer::Nobody,
+        lock_as_type: false,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn lock_as_type_case_1() {
+    let case = Case {
+        inputs_capacity: vec![1000],
+        outputs_capacity: vec![1000],
+        inputs_type: Type::Spv,
+        outputs_type: Type::Spv,
+        signer: Signer::Nobody,
+        lock_as_type: true,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn lock_as_type_case_2() {
+    let case = Case {
+        inputs_capacity: vec![1000],
+        outputs_capacity: vec![1000],
+        inputs_type: Type::Spv,
+        outputs_type: Type::Spv,
+        signer: Signer::Owner,
+        lock_as_type: true,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn malformed_args() {
+    utilities::setup();
+
+    let loader = Loader::default();
+    let mut context = Context::default();
+
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let lock_script = {
+        let lock_bin = loader.load_binary("ckb-bitcoin-spv-permissionless-update-lock");
+        let lock_out_point = context.deploy_cell(lock_bin);
+        // Lack of the owner public key hash.
+        let args = [0u8; 32];
+        context
+            .build_script(&lock_out_point, Default::default())
+            .expect("lock script")
+            .as_builder()
+            .args((args[..]).pack())
+            .build()
+    };
+    let type_script = context
+        .build_script(&always_success_out_point, Default::default())
+        .expect("type script");
+
+    let output = CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(lock_script)
+        .type_(Some(type_script).pack())
+        .build();
+    let out_point = context.create_cell(output.clone(), Bytes::new());
+    let input = CellInput::new_builder().previous_output(out_point).build();
+
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(output)
+        .output_data(Bytes::new().pack())
+        .build();
+    let tx = context.complete_tx(tx);
+    let _ = context.should_be_failed(&tx, MAX_CYCLES);
+}
+
+fn run_test(case: &Case) {
+    utilities::setup();
+
+    let loader = Loader::default();
+    let mut context = Context::default();
+
+    let owner_key = Generator::random_privkey();
+    let other_key = Generator::random_privkey();
+
+    // Use the always success script to mock the Bitcoin SPV type script.
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let spv_type_script = context
+        .build_script(&always_success_out_point, Default::default())
+        .expect("type script")
+        .as_builder()
+        .hash_type(ScriptHashType::Data1.into())
+        .build();
+    let mismatched_type_script = spv_type_script
+        .clone()
+        .as_builder()
+        .hash_type(ScriptHashType::Data.into())
+        .build();
+    let foreign_type_script = spv_type_script
+        .clone()
+        .as_builder()
+        .args(Bytes::from(vec![1]).pack())
+        .build();
+    let always_success_lock_script = context
+        .build_script(&always_success_out_point, Default::default())
+        .expect("lock script");
+
+    // Deploy the lock script.
+    let lock_script = {
+        let lock_bin = loader.load_binary("ckb-bitcoin-spv-permissionless-update-lock");
+        let lock_out_point = context.deploy_cell(lock_bin);
+        let owner_pubkey = owner_key.pubkey().expect("pubkey").serialize();
+        let owner_pubkey_hash = &blake2b_256(owner_pubkey)[..20];
+        let args = [
+            spv_type_script.calc_script_hash().as_slice(),
+            owner_pubkey_hash,
+        ]
+        .concat();
+        context
+            .build_script(&lock_out_point, Default::default())
+            .expect("lock script")
+            .as_builder()
+            .args((args[..]).pack())
+            .build()
+    };
+
+    let type_opt = |type_: Type| -> ScriptOpt {
+        match type_ {
+            Type::Spv => Some(spv_type_script.clone()),
+            Type::Mismatched => Some(mismatched_type_script.clone()),
+            Type::Foreign => Some(foreign_type_script.clone()),
+            Type::Nothing => None,
+        }
+        .pack()
+    };
+
+    let inputs = case
+        .inputs_capacity
+        .iter()
+        .map(|cap| {
+            let output = CellOutput::new_builder()
+                .capacity(cap.pack())
+                .lock(lock_script.clone())
+                .type_(type_opt(case.inputs_type))
+                .build();
+            let out_point = context.create_cell(output, Bytes::new());
+            CellInput::new_builder().previous_output(out_point).build()
+        })
+        .collect::<Vec<_>>();
+
+    let mut outputs = case
+        .outputs_capacity
+        .iter()
+        .map(|cap| {
+            CellOutput::new_builder()
+                .capacity(cap.pack())
+                .lock(lock_script.clone())
+                .type_(type_opt(case.outputs_type))
+                .build()
+        })
+        .collect::<Vec<_>>();
+    if case.lock_as_type {
+        let output = CellOutput::new_builder()
+            .capacity(100u64.pack())
+            .lock(always_success_lock_script)
+            .type_(Some(lock_script.clone()).pack())
+            .build();
+        outputs.push(output);
+    }
+    let outputs_data = vec![Bytes::new(); outputs.len()];
+
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    let tx = match case.signer {
+        Signer::Nobody => tx,
+        Signer::Owner => {
+            let signature = utilities::sign_sighash_all(&tx, &owner_key);
+            utilities::set_signature(&tx, &signature)
+        }
+        Signer::Other => {
+            let signature = utilities::sign_sighash_all(&tx, &other_key);
+            utilities::set_signature(&tx, &signature)
+        }
+        Signer::Corrupted => {
+            let mut signature = utilities::sign_sighash_all(&tx, &owner_key).to_vec();
+            signature[0] ^= 0x01;
+            utilities::set_signature(&tx, &signature)
+        }
+        Signer::Replayed => {
+            let other_tx = tx.as_advanced_builder().set_outputs(Vec::new()).build();
+            let signature = utilities::sign_sighash_all(&other_tx, &owner_key);
+            utilities::set_signature(&tx, &signature)
+        }
+    };
+
+    if case.should_pass {
+        let _ = context.should_be_passed(&tx, MAX_CYCLES);
+    } else {
+        let _ = context.should_be_failed(&tx, MAX_CYCLES);
+    }
+}
+
+const POOL_CAP: u64 = 10_000;
+const HEIGHT: u32 = 2016 * 400;
+
+struct RewardCase {
+    max_reward: Option<u64>,
+    reward: u64,
+    // Capacity which is moved from SPV cells to the reward pool.
+    spv_cells_lost: u64,
+    new_height: u32,
+    tip_changed: bool,
+    // The SPV cells are of another SPV instance, which uses the same type
+    // script code, and is locked by the same lock script.
+    foreign_instance: bool,
+    // The data of the reward pool cells is the SPV type hash.
+    pool_marked: bool,
+    should_pass: bool,
+}
+
+#[test]
+fn reward_case_1() {
+    let case = RewardCase {
+        max_reward: Some(100),
+        reward: 100,
+        spv_cells_lost: 0,
+        new_height: HEIGHT + 1,
+        tip_changed: true,
+        foreign_instance: false,
+        pool_marked: true,
+        should_pass: true,
+    };
+    run_reward_test(&case);
+}
+
+#[test]
+fn reward_case_2() {
+    let case = RewardCase {
+        max_reward: Some(100),
+        reward: 0,
+        spv_cells_lost: 0,
+        new_height: HEIGHT,
+        tip_changed: false,
+        foreign_instance: false,
+        pool_marked: true,
+        should_pass: true,
+    };
+    run_reward_test(&case);
+}
+
+#[test]
+fn reward_case_3() {
+    let case = RewardCase {
+        max_reward: Some(100),
+        reward: 101,
+        spv_cells_lost: 0,
+        new_height: HEIGHT + 1,
+        tip_changed: true,
+        foreign_instance: false,
+        pool_marked: true,
+        should_pass: false,
+    };
+    run_reward_test(&case);
+}
+
+#[test]
+fn reward_case_4() {
+    let case = RewardCase {
+        max_reward: Some(100),
+        reward: 100,
+        spv_cells_lost: 0,
+        new_height: HEIGHT,
+        tip_changed: true,
+        foreign_instance: false,
+        pool_marked: true,
+        should_pass: false,
+    };
+    run_reward_test(&case);
+}
+
+#[test]
+fn reward_case_5() {
+    let case = RewardCase {
+        max_reward: Some(100),
+        reward: 100,
+        spv_cells_lost: 0,
+        new_height: HEIGHT + 1,
+        tip_changed: false,
+        foreign_instance: false,
+        pool_marked: true,
+        should_pass: false,
+    };
+    run_reward_test(&case);
+}
+
+#[test]
+fn reward_case_6() {
+    let case = RewardCase {
+        max_reward: Some(100),
+        reward: 0,
+        spv_cells_lost: 100,
+        new_height: HEIGHT + 1,
+        tip_changed: true,
+        foreign_instance: false,
+        pool_marked: true,
+        should_pass: false,
+    };
+    run_reward_test(&case);
+}
+
+#[test]
+fn reward_case_7() {
+    let case = RewardCase {
+        max_reward: None,
+        reward: 0,
+        spv_cells_lost: 0,
+        new_height: HEIGHT + 1,
+        tip_changed: true,
+        foreign_instance: false,
+        pool_marked: true,
+        should_pass: false,
+    };
+    run_reward_test(&case);
+}
+
+#[test]
+fn reward_case_8() {
+    let case = RewardCase {
+        max_reward: Some(100),
+        reward: 100,
+        spv_cells_lost: 0,
+        new_height: HEIGHT + 1,
+        tip_changed: true,
+        foreign_instance: true,
+        pool_marked: true,
+        should_pass: false,
+    };
+    run_reward_test(&case);
+}
+
+#[test]
+fn reward_case_9() {
+    let case = RewardCase {
+        max_reward: Some(100),
+        reward: 100,
+        spv_cells_lost: 0,
+        new_height: HEIGHT + 1,
+        tip_changed: true,
+        foreign_instance: false,
+        pool_marked: false,
+        should_pass: false,
+    };
+    run_reward_test(&case);
+}
+
+fn run_reward_test(case: &RewardCase) {
+    utilities::setup();
+
+    let loader = Loader::default();
+    let mut context = Context::default();
+
+    let owner_key = Generator::random_privkey();
+
+    // Use the always success script to mock the Bitcoin SPV type script.
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let spv_type_script = context
+        .build_script(&always_success_out_point, Bytes::from(vec![1]))
+        .expect("type script")
+        .as_builder()
+        .hash_type(ScriptHashType::Data1.into())
+        .build();
+    let spv_type_hash = spv_type_script.calc_script_hash();
+    let foreign_type_script = spv_type_script
+        .clone()
+        .as_builder()
+        .args(Bytes::from(vec![2]).pack())
+        .build();
+    let always_success_lock_script = context
+        .build_script(&always_success_out_point, Default::default())
+        .expect("lock script");
+
+    let lock_script = {
+        let lock_bin = loader.load_binary("ckb-bitcoin-spv-permissionless-update-lock");
+        let lock_out_point = context.deploy_cell(lock_bin);
+        let owner_pubkey = owner_key.pubkey().expect("pubkey").serialize();
+        let owner_pubkey_hash = &blake2b_256(owner_pubkey)[..20];
+        let mut args = [spv_type_hash.as_slice(), owner_pubkey_hash].concat();
+        if let Some(max_reward) = case.max_reward {
+            args.extend_from_slice(&max_reward.to_le_bytes());
+        }
+        context
+            .build_script(&lock_out_point, Default::default())
+            .expect("lock script")
+            .as_builder()
+            .args((args[..]).pack())
+            .build()
+    };
+
+    let instance_type_script = if case.foreign_instance {
+        foreign_type_script
+    } else {
+        spv_type_script
+    };
+    let spv_cell = |capacity: u64| {
+        CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(lock_script.clone())
+            .type_(Some(instance_type_script.clone()).pack())
+            .build()
+    };
+    let pool_data = if case.pool_marked {
+        spv_type_hash.as_bytes()
+    } else {
+        Bytes::new()
+    };
+    let pool_cell = |capacity: u64| {
+        CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(lock_script.clone())
+            .build()
+    };
+    let spv_info = |tip_client_id: u8| {
+        spv_packed::SpvInfo::new_builder()
+            .tip_client_id(tip_client_id.into())
+            .build()
+            .as_bytes()
+    };
+    let spv_client = |id: u8, height: u32| {
+        let block = utilities::MockBlock::mine(height, vec![]);
+        let mut client = block.bootstrap().tip_client();
+        client.id = id;
+        let packed_client: spv_packed::SpvClient = client.pack();
+        packed_client.as_bytes()
+    };
+
+    // The current tip client.
+    let cell_dep = {
+        let output = CellOutput::new_builder()
+            .capacity(SPV_CELL_CAP.pack())
+            .lock(always_success_lock_script)
+            .type_(Some(instance_type_script.clone()).pack())
+            .build();
+        let out_point = context.create_cell(output, spv_client(0, HEIGHT));
+        CellDep::new_builder()
+            .out_point(out_point)
+            .dep_type(DepType::Code.into())
+            .build()
+    };
+
+    let inputs = [
+        (spv_cell(SPV_CELL_CAP), spv_info(0)),
+        (spv_cell(SPV_CELL_CAP), spv_client(1, HEIGHT - 1)),
+        (pool_cell(POOL_CAP), pool_data.clone()),
+    ]
+    .into_iter()
+    .map(|(output, data)| {
+        let out_point = context.create_cell(output, data);
+        CellInput::new_builder().previous_output(out_point).build()
+    })
+    .collect::<Vec<_>>();
+
+    let new_tip_client_id = if case.tip_changed { 1 } else { 0 };
+    let (outputs, outputs_data): (Vec<_>, Vec<_>) = [
+        (spv_cell(SPV_CELL_CAP), spv_info(new_tip_client_id)),
+        (
+            spv_cell(SPV_CELL_CAP - case.spv_cells_lost),
+            spv_client(1, case.new_height),
+        ),
+        (
+            pool_cell(POOL_CAP - case.reward + case.spv_cells_lost),
+            pool_data.clone(),
+        ),
+    ]
+    .into_iter()
+    .unzip();
+
+    let tx = TransactionBuilder::default()
+        .cell_dep(cell_dep)
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    if case.should_pass {
+        let _ = context.should_be_passed(&tx, MAX_CYCLES);
+    } else {
+        let _ = context.should_be_failed(&tx, MAX_CYCLES);
+    }
+}
@@ -0,0 +1,283 @@
+use bitcoin::{
+    absolute::LockTime, transaction::Version, Amount, OutPoint, ScriptBuf, Sequence, Transaction,
+    TxIn, TxOut, Witness,
+};
+use ckb_bitcoin_spv_verifier::types::{core, packed, prelude::Pack as VPack};
+use ckb_testtool::{
+    builtin::ALWAYS_SUCCESS,
+    ckb_types::{
+        bytes::Bytes,
+        core::{DepType, TransactionBuilder},
+        packed::*,
+        prelude::*,
+    },
+    context::Context,
+};
+
+use crate::{prelude::*, utilities, Loader};
+
+const HEIGHT: u32 = 2016 * 400;
+const INSTANCES_COUNT: usize = 3;
+
+#[derive(Clone, Copy)]
+enum Proof {
+    Valid,
+    Empty,
+    Invalid,
+}
+
+#[derive(Clone, Copy)]
+enum Commitment {
+    Valid,
+    OtherLock,
+    OtherInput,
+}
+
+struct Case {
+    threshold: u8,
+    proofs: [Proof; INSTANCES_COUNT],
+    commitment: Commitment,
+    should_pass: bool,
+}
+
+#[test]
+fn normal_case_1() {
+    let case = Case {
+        threshold: 2,
+        proofs: [Proof::Valid; INSTANCES_COUNT],
+        commitment: Commitment::Valid,
+        should_pass: true,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn normal_case_2() {
+    let case = Case {
+        threshold: 2,
+        proofs: [Proof::Valid, Proof::Empty, Proof::Valid],
+        commitment: Commitment::Valid,
+        should_pass: true,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn normal_case_3() {
+    let case = Case {
+        threshold: 3,
+        proofs: [Proof::Valid; INSTANCES_COUNT],
+        commitment: Commitment::Valid,
+        should_pass: true,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn quorum_not_reached() {
+    let case = Case {
+        threshold: 2,
+        proofs: [Proof::Empty, Proof::Empty, Proof::Valid],
+        commitment: Commitment::Valid,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn quorum_with_an_invalid_proof() {
+    let case = Case {
+        threshold: 2,
+        proofs: [Proof::Valid, Proof::Invalid, Proof::Valid],
+        commitment: Commitment::Valid,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn threshold_is_too_large() {
+    let case = Case {
+        threshold: 4,
+        proofs: [Proof::Valid; INSTANCES_COUNT],
+        commitment: Commitment::Valid,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn transaction_without_commitment() {
+    let case = Case {
+        threshold: 2,
+        proofs: [Proof::Valid; INSTANCES_COUNT],
+        commitment: Commitment::OtherLock,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn transaction_commits_to_other_input() {
+    let case = Case {
+        threshold: 2,
+        proofs: [Proof::Valid; INSTANCES_COUNT],
+        commitment: Commitment::OtherInput,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+fn bitcoin_transaction(lock_hash: &[u8], out_point: &[u8]) -> Transaction {
+    let mut op_return = vec![0x6a, 0x44];
+    op_return.extend_from_slice(lock_hash);
+    op_return.extend_from_slice(out_point);
+    Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::default(),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::default(),
+        }],
+        output: vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: ScriptBuf::from_bytes(op_return),
+        }],
+    }
+}
+
+fn run_test(case: &Case) {
+    utilities::setup();
+
+    let loader = Loader::default();
+    let mut context = Context::default();
+
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let lock_script = context
+        .build_script(&always_success_out_point, Default::default())
+        .expect("lock script");
+
+    let spv_type_scripts = {
+        let bin = loader.load_binary("ckb-bitcoin-spv-type-lock");
+        let out_point = context.deploy_cell(bin);
+        (1..=INSTANCES_COUNT)
+            .map(|n| {
+                // Different SPV instances have different type ids.
+                let args = packed::SpvTypeArgs::new_builder()
+                    .type_id(core::Hash::from_bytes_ref(&[n as u8; 32]).pack())
+                    .clients_count(3u8.into())
+                    .build();
+                context
+                    .build_script(&out_point, Default::default())
+                    .expect("spv type script")
+                    .as_builder()
+                    .args(args.as_slice().pack())
+                    .build()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let quorum_lock_script = {
+        let mut args = vec![case.threshold];
+        args.extend_from_slice(&0u32.to_le_bytes());
+        for spv_type_script in &spv_type_scripts {
+            args.extend_from_slice(spv_type_script.calc_script_hash().as_slice());
+        }
+        let bin = loader.load_binary("ckb-bitcoin-spv-quorum-lock");
+        let out_point = context.deploy_cell(bin);
+        context
+            .build_script(&out_point, Bytes::from(args))
+            .expect("quorum lock script")
+    };
+
+    let input_out_point = {
+        let output = CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(quorum_lock_script.clone())
+            .build();
+        context.create_cell(output, Bytes::new())
+    };
+    let input = CellInput::new_builder()
+        .previous_output(input_out_point.clone())
+        .build();
+
+    let btc_tx = match case.commitment {
+        Commitment::Valid => bitcoin_transaction(
+            quorum_lock_script.calc_script_hash().as_slice(),
+            input_out_point.as_slice(),
+        ),
+        Commitment::OtherLock => bitcoin_transaction(
+            lock_script.calc_script_hash().as_slice(),
+            input_out_point.as_slice(),
+        ),
+        Commitment::OtherInput => {
+            // The proof for a cell which was unlocked before.
+            let other_out_point = input_out_point.as_builder().index(1u32.pack()).build();
+            bitcoin_transaction(
+                quorum_lock_script.calc_script_hash().as_slice(),
+                other_out_point.as_slice(),
+            )
+        }
+    };
+    let block = utilities::MockBlock::mine(HEIGHT, vec![btc_tx.clone()]);
+
+    let cell_deps = spv_type_scripts
+        .iter()
+        .map(|spv_type_script| {
+            let mut client = block.bootstrap().tip_client();
+            client.id = 1;
+            let spv_client: packed::SpvClient = client.pack();
+            let output = CellOutput::new_builder()
+                .capacity(SPV_CELL_CAP.pack())
+                .lock(lock_script.clone())
+                .type_(Some(spv_type_script.clone()).pack())
+                .build();
+            let out_point = context.create_cell(output, spv_client.as_bytes());
+            CellDep::new_builder()
+                .out_point(out_point)
+                .dep_type(DepType::Code.into())
+                .build()
+        })
+        .collect::<Vec<_>>();
+
+    let output = CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(lock_script)
+        .build();
+
+    let witness = {
+        let raw_tx = bitcoin::consensus::serialize(&btc_tx);
+        let mut items = BytesVec::new_builder().push(Pack::pack(&raw_tx));
+        for proof in case.proofs {
+            let tx_proof = match proof {
+                Proof::Valid => block.transaction_proof(1).as_slice().to_vec(),
+                Proof::Empty => Vec::new(),
+                // The proof of the coinbase transaction.
+                Proof::Invalid => block.transaction_proof(0).as_slice().to_vec(),
+            };
+            items = items.push(Pack::pack(&tx_proof));
+        }
+        let lock_args = BytesOpt::new_builder()
+            .set(Some(Pack::pack(items.build().as_slice())))
+            .build();
+        let witness_args = WitnessArgs::new_builder().lock(lock_args).build();
+        witness_args.as_bytes()
+    };
+
+    let tx = TransactionBuilder::default()
+        .cell_deps(cell_deps)
+        .input(input)
+        .output(output)
+        .output_data(Pack::pack(&Bytes::new()))
+        .witness(Pack::pack(&witness))
+        .build();
+    let tx = context.complete_tx(tx);
+
+    if case.should_pass {
+        let _ = context.should_be_passed(&tx, MAX_CYCLES);
+    } else {
+        let _ = context.should_be_failed(&tx, MAX_CYCLES);
+    }
+}
@@ -0,0 +1,204 @@
+use bitcoin::{
+    absolute::LockTime, transaction::Version, Amount, OutPoint, ScriptBuf, Sequence, Transaction,
+    TxIn, TxOut, Witness,
+};
+use ckb_bitcoin_spv_verifier::types::{core, packed, prelude::Pack as VPack};
+use ckb_testtool::{
+    builtin::ALWAYS_SUCCESS,
+    ckb_types::{
+        bytes::Bytes,
+        core::{DepType, ScriptHashType, TransactionBuilder},
+        packed::*,
+        prelude::*,
+    },
+    context::Context,
+};
+
+use crate::{prelude::*, utilities, Loader};
+
+const HEIGHT: u32 = 2016 * 400;
+
+struct Case {
+    confirmations: u32,
+    by_spawn: bool,
+    should_pass: bool,
+}
+
+#[test]
+fn exec_case_1() {
+    let case = Case {
+        confirmations: 0,
+        by_spawn: false,
+        should_pass: true,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn exec_without_enough_confirmations() {
+    let case = Case {
+        confirmations: 6,
+        by_spawn: false,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn spawn_case_1() {
+    let case = Case {
+        confirmations: 0,
+        by_spawn: true,
+        should_pass: true,
+    };
+    run_test(&case);
+}
+
+#[test]
+fn spawn_without_enough_confirmations() {
+    let case = Case {
+        confirmations: 6,
+        by_spawn: true,
+        should_pass: false,
+    };
+    run_test(&case);
+}
+
+fn bitcoin_transaction() -> Transaction {
+    Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::default(),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::default(),
+        }],
+        output: vec![TxOut {
+            value: Amount::from_sat(100_000),
+            script_pubkey: ScriptBuf::from_bytes(vec![0x51]),
+        }],
+    }
+}
+
+fn run_test(case: &Case) {
+    utilities::setup();
+
+    let loader = Loader::default();
+    let mut context = Context::default();
+
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let lock_script = context
+        .build_script(&always_success_out_point, Default::default())
+        .expect("lock script");
+
+    let spv_type_script = {
+        let args = packed::SpvTypeArgs::new_builder()
+            .type_id(core::Hash::from_bytes_ref(&[0u8; 32]).pack())
+            .clients_count(3u8.into())
+            .build();
+        let bin = loader.load_binary("ckb-bitcoin-spv-type-lock");
+        let out_point = context.deploy_cell(bin);
+        context
+            .build_script(&out_point, Default::default())
+            .expect("spv type script")
+            .as_builder()
+            .args(args.as_slice().pack())
+            .build()
+    };
+
+    let btc_tx = bitcoin_transaction();
+    let block = utilities::MockBlock::mine(HEIGHT, vec![btc_tx.clone()]);
+
+    let cell_dep_spv_client = {
+        let mut client = block.bootstrap().tip_client();
+        client.id = 1;
+        let spv_client: packed::SpvClient = client.pack();
+        let output = CellOutput::new_builder()
+            .capacity(SPV_CELL_CAP.pack())
+            .lock(lock_script.clone())
+            .type_(Some(spv_type_script.clone()).pack())
+            .build();
+        let out_point = context.create_cell(output, spv_client.as_bytes());
+        CellDep::new_builder()
+            .out_point(out_point)
+            .dep_type(DepType::Code.into())
+            .build()
+    };
+
+    let (cell_dep_verifier, verifier_code_hash) = {
+        let bin = loader.load_binary("ckb-bitcoin-spv-tx-verifier");
+        let code_hash = CellOutput::calc_data_hash(&bin);
+        let out_point = context.deploy_cell(bin);
+        let cell_dep = CellDep::new_builder()
+            .out_point(out_point)
+            .dep_type(DepType::Code.into())
+            .build();
+        (cell_dep, code_hash)
+    };
+
+    let caller_type_script = {
+        let mut args = spv_type_script.calc_script_hash().as_slice().to_vec();
+        args.extend_from_slice(verifier_code_hash.as_slice());
+        args.extend_from_slice(&case.confirmations.to_le_bytes());
+        args.push(u8::from(case.by_spawn));
+        let bin = loader.load_binary("spv-tx-verifier-caller-type");
+        let out_point = context.deploy_cell(bin);
+        let hash_type = if case.by_spawn {
+            ScriptHashType::Data2
+        } else {
+            ScriptHashType::Data1
+        };
+        context
+            .build_script(&out_point, Bytes::from(args))
+            .expect("caller type script")
+            .as_builder()
+            .hash_type(hash_type.into())
+            .build()
+    };
+
+    let input = {
+        let output = CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(lock_script.clone())
+            .build();
+        let out_point = context.create_cell(output, Bytes::new());
+        CellInput::new_builder().previous_output(out_point).build()
+    };
+
+    let output = CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(lock_script)
+        .type_(Some(caller_type_script).pack())
+        .build();
+
+    let witness = {
+        let raw_tx = bitcoin::consensus::serialize(&btc_tx);
+        let tx_proof = block.transaction_proof(1);
+        let items = BytesVec::new_builder()
+            .push(Pack::pack(&raw_tx))
+            .push(Pack::pack(tx_proof.as_slice()))
+            .build();
+        let type_args = BytesOpt::new_builder()
+            .set(Some(Pack::pack(items.as_slice())))
+            .build();
+        let witness_args = WitnessArgs::new_builder().output_type(type_args).build();
+        witness_args.as_bytes()
+    };
+
+    let tx = TransactionBuilder::default()
+        .cell_dep(cell_dep_spv_client)
+        .cell_dep(cell_dep_verifier)
+        .input(input)
+        .output(output)
+        .output_data(Pack::pack(&Bytes::new()))
+        .witness(Pack::pack(&witness))
+        .build();
+    let tx = context.complete_tx(tx);
+
+    if case.should_pass {
+        let _ = context.should_be_passed(&tx, MAX_CYCLES);
+    } else {
+        let _ = context.should_be_failed(&tx, MAX_CYCLES);
+    }
+}
@@ -110,7 +110,7 @@ fn test_normal(case: NormalCase) {
             .build_script(&out_point, Default::default())
             .expect("lock script")
             .as_builder()
-            .args([0u8, 1, 2, 3].pack())
+            .args(Pack::pack(&[0u8; 20][..]))
             .build()
     };
 
@@ -1,4 +1,5 @@
 mod create;
 mod destroy;
 mod reorg;
+mod tx_builder;
 mod update;
@@ -0,0 +1,310 @@
+//! Verify transactions which are built by the transaction builder against
+//! the real Bitcoin SPV type script.
+
+use ckb_bitcoin_spv_prover::DummyService;
+use ckb_bitcoin_spv_tx_builder::{
+    build_create, build_reorg, build_reset, build_update,
+    cost::{occupied_capacity, CyclesModel},
+    CreateParams, LiveCell, SpvInstance,
+};
+use ckb_bitcoin_spv_verifier::types::{
+    core::{self, BitcoinChainType},
+    packed,
+    prelude::{Pack as VPack, Unpack as VUnpack},
+};
+use ckb_testtool::{
+    ckb_types::{
+        bytes::Bytes,
+        core::{Cycle, TransactionView},
+        packed::*,
+        prelude::*,
+    },
+    context::Context,
+};
+use ckb_types::prelude::Entity as BuilderEntity;
+
+use crate::{prelude::*, utilities, Loader};
+
+const HEADERS_PATH: &str = "main-chain/headers/continuous/case-0822528_0830592";
+const START_HEIGHT: u32 = 822528;
+const STALE_HEIGHT: u32 = 823226;
+const CLIENTS_COUNT: u8 = 5;
+const SHANNONS_PER_CKB: u64 = 100_000_000;
+
+#[test]
+fn create_and_update() {
+    utilities::setup();
+
+    let mut env = Env::new(0);
+    let mut service = env.create(START_HEIGHT);
+
+    // Go around the ring of clients more than once.
+    for round in 0..(CLIENTS_COUNT * 2) {
+        let start = START_HEIGHT + u32::from(round) * 10 + 1;
+        env.update(&mut service, main_headers(start, start + 10));
+        assert_eq!(env.instance().tip_client_id, (round + 1) % CLIENTS_COUNT);
+    }
+}
+
+#[test]
+fn reorg() {
+    utilities::setup();
+
+    let mut env = Env::new(0);
+    let mut service = env.create(START_HEIGHT);
+
+    // Stop at the grandparent block of the stale block.
+    let headers = main_headers(START_HEIGHT + 1, STALE_HEIGHT - 1);
+    for chunk in headers.chunks(SPV_HEADERS_GROUP_SIZE) {
+        env.update(&mut service, chunk.to_vec());
+    }
+    let fork_client = service.tip_client();
+    let fork_client_id = env.instance().tip_client_id;
+
+    // Two clients after the fork client, the tip one is on a stale block.
+    env.update(&mut service, main_headers(STALE_HEIGHT - 1, STALE_HEIGHT));
+    env.update(&mut service, vec![stale_header(STALE_HEIGHT)]);
+
+    service.rollback_to(fork_client).unwrap();
+    let headers = main_headers(STALE_HEIGHT - 1, STALE_HEIGHT + 2);
+    let headers_count = headers.len() as u32;
+    let update = service.update(headers).unwrap();
+    let tx = build_reorg(
+        &env.instance(),
+        fork_client_id,
+        service.tip_client(),
+        &update,
+    )
+    .unwrap();
+    let cycles = env.submit(&tx);
+    assert_cycles_in_model(cycles, headers_count, 2);
+    let instance = env.instance();
+    assert_eq!(instance.tip_client_id, fork_client_id + 1);
+    let tip_client = packed::SpvClient::from_slice(&instance.tip_client().data).unwrap();
+    let expected: packed::SpvClient = {
+        let mut client = service.tip_client();
+        client.id = instance.tip_client_id;
+        client.pack()
+    };
+    assert_eq!(tip_client.as_slice(), expected.as_slice());
+}
+
+#[test]
+fn reset() {
+    utilities::setup();
+
+    // The chain type is in the highest two bits of the flags.
+    let testnet_flags = (0..4u8)
+        .map(|bits| bits << 6)
+        .find(|flags| BitcoinChainType::Testnet == (*flags).into())
+        .unwrap();
+    let mut env = Env::new(testnet_flags);
+    let mut service = env.create(START_HEIGHT);
+
+    env.update(
+        &mut service,
+        main_headers(START_HEIGHT + 1, START_HEIGHT + 11),
+    );
+
+    let height = START_HEIGHT + 2016;
+    let tx = build_reset(&env.instance(), &bootstrap(height)).unwrap();
+    env.submit(&tx);
+    let instance = env.instance();
+    assert_eq!(instance.tip_client_id, 0);
+    let tip_client = packed::SpvClient::from_slice(&instance.tip_client().data).unwrap();
+    let max_height: u32 = tip_client.headers_mmr_root().max_height().unpack();
+    assert_eq!(max_height, height);
+}
+
+#[test]
+fn cycles_model() {
+    utilities::setup();
+
+    let mut env = Env::new(0);
+    let mut service = env.create(START_HEIGHT);
+
+    let mut start = START_HEIGHT + 1;
+    for headers_count in [1, 10, SPV_HEADERS_GROUP_SIZE as u32] {
+        let cycles = env.update(&mut service, main_headers(start, start + headers_count));
+        assert_cycles_in_model(cycles, headers_count, 1);
+        start += headers_count;
+    }
+}
+
+#[test]
+fn spv_cell_capacity() {
+    utilities::setup();
+
+    let env = Env::new(0);
+    // The capacity of each SPV cell in deployments, in CKB.
+    assert!(env.cell_capacity() <= SPV_CELL_CAP * SHANNONS_PER_CKB);
+}
+
+// The default model should be an upper bound of the measured cycles, but not
+// more than twice of them.
+fn assert_cycles_in_model(measured: Cycle, headers_count: u32, clients_count: u8) {
+    let estimated = CyclesModel::default().cycles(headers_count, clients_count);
+    println!(
+        "{headers_count} headers, {clients_count} clients: \
+        measured {measured} cycles, estimated {estimated} cycles"
+    );
+    assert!(measured <= estimated, "the model is too low");
+    assert!(estimated <= measured * 2, "the model is too high");
+}
+
+// The context, with the live cells of the SPV instance.
+struct Env {
+    context: Context,
+    lock_script: Script,
+    type_script: Script,
+    flags: u8,
+    cells: Vec<LiveCell>,
+}
+
+impl Env {
+    fn new(flags: u8) -> Self {
+        let loader = Loader::default();
+        let mut context = Context::default();
+
+        let lock_script = {
+            let bin = loader.load_binary("can-update-without-ownership-lock");
+            let out_point = context.deploy_cell(bin);
+            context
+                .build_script(&out_point, Default::default())
+                .expect("lock script")
+                .as_builder()
+                .args(Pack::pack(&[0u8; 20][..]))
+                .build()
+        };
+        // The args will be replaced by the builder.
+        let type_script = {
+            let bin = loader.load_binary("ckb-bitcoin-spv-type-lock");
+            let out_point = context.deploy_cell(bin);
+            context
+                .build_script(&out_point, Default::default())
+                .expect("type script")
+        };
+
+        Self {
+            context,
+            lock_script,
+            type_script,
+            flags,
+            cells: Vec::new(),
+        }
+    }
+
+    // The minimal capacity of SPV cells, which covers both the info cell
+    // and the client cells.
+    fn cell_capacity(&self) -> u64 {
+        let type_args = packed::SpvTypeArgs::new_builder()
+            .clients_count(CLIENTS_COUNT.into())
+            .build();
+        let type_script: ckb_types::packed::Script = convert(&self.type_script);
+        let type_script = type_script
+            .as_builder()
+            .args(ckb_types::prelude::Pack::pack(type_args.as_slice()))
+            .build();
+        let capacity = occupied_capacity(&convert(&self.lock_script), &type_script).unwrap();
+        capacity.info.max(capacity.client)
+    }
+
+    fn create(&mut self, height: u32) -> DummyService {
+        let cell_capacity = self.cell_capacity();
+        let first_input = {
+            let capacity = cell_capacity * (u64::from(CLIENTS_COUNT) + 1);
+            let output = CellOutput::new_builder()
+                .capacity(capacity.pack())
+                .lock(self.lock_script.clone())
+                .build();
+            let out_point = self.context.create_cell(output, Bytes::new());
+            CellInput::new_builder().previous_output(out_point).build()
+        };
+        let params = CreateParams {
+            first_input: convert(&first_input),
+            lock_script: convert(&self.lock_script),
+            type_script: convert(&self.type_script),
+            clients_count: CLIENTS_COUNT,
+            flags: self.flags,
+            cell_capacity,
+            bootstrap: bootstrap(height),
+        };
+        let tx = build_create(params).unwrap();
+        self.submit(&tx);
+        DummyService::bootstrap(height, main_header(height)).unwrap()
+    }
+
+    fn update(&mut self, service: &mut DummyService, headers: Vec<core::Header>) -> Cycle {
+        let update = service.update(headers).unwrap();
+        let tx = build_update(&self.instance(), service.tip_client(), &update).unwrap();
+        self.submit(&tx)
+    }
+
+    fn instance(&self) -> SpvInstance {
+        SpvInstance::load(self.cells.clone()).unwrap()
+    }
+
+    // Verifies the transaction, then replaces the consumed SPV cells with
+    // the new ones.
+    //
+    // Returns the cycles of the transaction.
+    fn submit(&mut self, tx: &ckb_types::core::TransactionView) -> Cycle {
+        let tx = self.context.complete_tx(to_testtool_tx(tx));
+        let cycles = self.context.should_be_passed(&tx, MAX_CYCLES).unwrap();
+
+        self.cells.retain(|cell| {
+            !tx.inputs().into_iter().any(|input| {
+                input.previous_output().as_slice() == BuilderEntity::as_slice(&cell.out_point)
+            })
+        });
+        for (index, (output, data)) in tx.outputs_with_data_iter().enumerate() {
+            let out_point = OutPoint::new(tx.hash(), index as u32);
+            self.context.create_cell_with_out_point(
+                out_point.clone(),
+                output.clone(),
+                data.clone(),
+            );
+            self.cells.push(LiveCell {
+                out_point: convert(&out_point),
+                output: convert(&output),
+                data: data.to_vec().into(),
+            });
+        }
+        cycles
+    }
+}
+
+// The transaction builder may use another version of `ckb-types`, so the
+// structures are converted through their serialized bytes.
+fn convert<T: BuilderEntity>(entity: &impl Entity) -> T {
+    T::from_slice(entity.as_slice()).expect("same structure")
+}
+
+fn to_testtool_tx(tx: &ckb_types::core::TransactionView) -> TransactionView {
+    Transaction::from_slice(BuilderEntity::as_slice(&tx.data()))
+        .expect("same structure")
+        .into_view()
+}
+
+fn main_header(height: u32) -> core::Header {
+    let header_bin = utilities::find_bin_file(HEADERS_PATH, &format!("{height:07}.bin"));
+    utilities::decode_from_bin_file(&header_bin).unwrap()
+}
+
+// The headers in `[start, end)`.
+fn main_headers(start: u32, end: u32) -> Vec<core::Header> {
+    (start..end).map(main_header).collect()
+}
+
+fn stale_header(height: u32) -> core::Header {
+    let header_bin =
+        utilities::find_bin_file("main-chain/headers/stale", &format!("{height:07}.bin"));
+    utilities::decode_from_bin_file(&header_bin).unwrap()
+}
+
+fn bootstrap(height: u32) -> packed::SpvBootstrap {
+    packed::SpvBootstrap::new_builder()
+        .height(VPack::pack(&height))
+        .header(main_header(height).pack())
+        .build()
+}
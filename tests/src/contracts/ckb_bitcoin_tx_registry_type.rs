@@ -0,0 +1,285 @@
+use ckb_testtool::{
+    builtin::ALWAYS_SUCCESS,
+    ckb_types::{bytes::Bytes, core::TransactionBuilder, packed::*, prelude::*},
+    context::Context,
+};
+
+use crate::{prelude::*, utilities, Loader};
+
+const MIN_KEY: [u8; 32] = [0x00; 32];
+const MAX_KEY: [u8; 32] = [0xff; 32];
+const CONSUMER_TYPE_HASH: [u8; 32] = [9u8; 32];
+
+struct Case {
+    inputs: Vec<([u8; 32], [u8; 32])>,
+    outputs: Vec<([u8; 32], [u8; 32])>,
+    should_pass: bool,
+}
+
+fn key(n: u8) -> [u8; 32] {
+    [n; 32]
+}
+
+fn cell_data(key: &[u8; 32], next: &[u8; 32]) -> Bytes {
+    let mut data = key.to_vec();
+    data.extend_from_slice(next);
+    Bytes::from(data)
+}
+
+#[test]
+fn create_case_1() {
+    test_create(true, true);
+}
+
+#[test]
+fn create_with_incorrect_type_id() {
+    test_create(false, true);
+}
+
+#[test]
+fn create_without_head_cell() {
+    test_create(true, false);
+}
+
+#[test]
+fn insert_case_1() {
+    let case = Case {
+        inputs: vec![(MIN_KEY, MAX_KEY)],
+        outputs: vec![(MIN_KEY, key(1)), (key(1), MAX_KEY)],
+        should_pass: true,
+    };
+    test_insert(&case, true);
+}
+
+#[test]
+fn insert_case_2() {
+    let case = Case {
+        inputs: vec![(key(1), key(9))],
+        outputs: vec![(key(3), key(5)), (key(1), key(3)), (key(5), key(9))],
+        should_pass: true,
+    };
+    test_insert(&case, true);
+}
+
+#[test]
+fn insert_case_3() {
+    let case = Case {
+        inputs: vec![(key(5), key(9)), (key(1), key(3))],
+        outputs: vec![
+            (key(1), key(2)),
+            (key(2), key(3)),
+            (key(5), key(7)),
+            (key(7), key(9)),
+        ],
+        should_pass: true,
+    };
+    test_insert(&case, true);
+}
+
+#[test]
+fn insert_an_existed_key() {
+    let case = Case {
+        inputs: vec![(key(1), key(3))],
+        outputs: vec![(key(1), key(3)), (key(1), key(3))],
+        should_pass: false,
+    };
+    test_insert(&case, true);
+}
+
+#[test]
+fn insert_out_of_range() {
+    let case = Case {
+        inputs: vec![(key(1), key(3))],
+        outputs: vec![(key(1), key(4)), (key(4), key(3))],
+        should_pass: false,
+    };
+    test_insert(&case, true);
+}
+
+#[test]
+fn insert_with_broken_range() {
+    let case = Case {
+        inputs: vec![(key(1), key(5))],
+        outputs: vec![(key(1), key(2)), (key(3), key(5))],
+        should_pass: false,
+    };
+    test_insert(&case, true);
+}
+
+#[test]
+fn insert_with_unknown_cells() {
+    let case = Case {
+        inputs: vec![(key(1), key(5))],
+        outputs: vec![(key(1), key(3)), (key(3), key(5)), (key(7), key(8))],
+        should_pass: false,
+    };
+    test_insert(&case, true);
+}
+
+#[test]
+fn remove_a_key() {
+    let case = Case {
+        inputs: vec![(key(1), key(3)), (key(3), key(5))],
+        outputs: vec![(key(1), key(5))],
+        should_pass: false,
+    };
+    test_insert(&case, true);
+}
+
+#[test]
+fn destroy_a_registry() {
+    let case = Case {
+        inputs: vec![(MIN_KEY, MAX_KEY)],
+        outputs: vec![],
+        should_pass: false,
+    };
+    test_insert(&case, true);
+}
+
+#[test]
+fn insert_without_consumer() {
+    let case = Case {
+        inputs: vec![(MIN_KEY, MAX_KEY)],
+        outputs: vec![(MIN_KEY, key(1)), (key(1), MAX_KEY)],
+        should_pass: false,
+    };
+    test_insert(&case, false);
+}
+
+fn registry_args(type_id: &[u8]) -> Bytes {
+    let mut args = type_id.to_vec();
+    args.extend_from_slice(&CONSUMER_TYPE_HASH);
+    Bytes::from(args)
+}
+
+fn test_create(with_correct_type_id: bool, with_head_cell: bool) {
+    utilities::setup();
+
+    let loader = Loader::default();
+    let mut context = Context::default();
+
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let lock_script = context
+        .build_script(&always_success_out_point, Default::default())
+        .expect("lock script");
+
+    let input = {
+        let output = CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(lock_script.clone())
+            .build();
+        let out_point = context.create_cell(output, Bytes::new());
+        CellInput::new_builder().previous_output(out_point).build()
+    };
+
+    let type_script = {
+        let type_id = if with_correct_type_id {
+            utilities::calculate_type_id(input.clone(), 0)
+        } else {
+            utilities::calculate_type_id(input.clone(), 1)
+        };
+        let bin = loader.load_binary("ckb-bitcoin-tx-registry-type");
+        let out_point = context.deploy_cell(bin);
+        context
+            .build_script(&out_point, registry_args(&type_id))
+            .expect("registry type script")
+    };
+
+    let output = CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(lock_script)
+        .type_(Some(type_script).pack())
+        .build();
+    let output_data = if with_head_cell {
+        cell_data(&MIN_KEY, &MAX_KEY)
+    } else {
+        cell_data(&MIN_KEY, &key(1))
+    };
+
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(output)
+        .output_data(output_data.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    if with_correct_type_id && with_head_cell {
+        let _ = context.should_be_passed(&tx, MAX_CYCLES);
+    } else {
+        let _ = context.should_be_failed(&tx, MAX_CYCLES);
+    }
+}
+
+fn test_insert(case: &Case, with_consumer: bool) {
+    utilities::setup();
+
+    let loader = Loader::default();
+    let mut context = Context::default();
+
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let lock_script = context
+        .build_script(&always_success_out_point, Default::default())
+        .expect("lock script");
+
+    // The consumer is an always-success type script, its hash is saved in
+    // the args of the registry.
+    let consumer_type_script = context
+        .build_script(&always_success_out_point, Bytes::from(vec![1]))
+        .expect("consumer type script");
+    let type_script = {
+        let mut args = vec![0u8; 32];
+        args.extend_from_slice(consumer_type_script.calc_script_hash().as_slice());
+        let bin = loader.load_binary("ckb-bitcoin-tx-registry-type");
+        let out_point = context.deploy_cell(bin);
+        context
+            .build_script(&out_point, Bytes::from(args))
+            .expect("registry type script")
+    };
+
+    let registry_cell = CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(lock_script.clone())
+        .type_(Some(type_script).pack())
+        .build();
+
+    let inputs = case
+        .inputs
+        .iter()
+        .map(|(key, next)| {
+            let out_point = context.create_cell(registry_cell.clone(), cell_data(key, next));
+            CellInput::new_builder().previous_output(out_point).build()
+        })
+        .collect::<Vec<_>>();
+    let mut outputs = case
+        .outputs
+        .iter()
+        .map(|_| registry_cell.clone())
+        .collect::<Vec<_>>();
+    let mut outputs_data = case
+        .outputs
+        .iter()
+        .map(|(key, next)| cell_data(key, next))
+        .collect::<Vec<_>>();
+    if with_consumer {
+        let consumer_cell = CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(lock_script.clone())
+            .type_(Some(consumer_type_script).pack())
+            .build();
+        outputs.push(consumer_cell);
+        outputs_data.push(Bytes::new());
+    }
+
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    if case.should_pass {
+        let _ = context.should_be_passed(&tx, MAX_CYCLES);
+    } else {
+        let _ = context.should_be_failed(&tx, MAX_CYCLES);
+    }
+}
@@ -1,2 +1,8 @@
 mod can_update_without_ownership_lock;
+mod ckb_bitcoin_deposit_mint_type;
+mod ckb_bitcoin_height_timelock_lock;
+mod ckb_bitcoin_spv_permissionless_update_lock;
+mod ckb_bitcoin_spv_quorum_lock;
+mod ckb_bitcoin_spv_tx_verifier;
 mod ckb_bitcoin_spv_type_lock;
+mod ckb_bitcoin_tx_registry_type;
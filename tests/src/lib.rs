@@ -68,7 +68,7 @@ impl Default for Loader {
 }
 
 impl Loader {
-    fn with_test_env(env: TestEnv) -> Self {
+    pub fn with_test_env(env: TestEnv) -> Self {
         let load_prefix = match env {
             TestEnv::Debug => "debug",
             TestEnv::Release => "release",
@@ -108,6 +108,22 @@ impl Loader {
         }
         result.unwrap().into()
     }
+
+    /// Loads all binaries, with their names; returns nothing if they are not built.
+    pub fn load_binaries(&self) -> Vec<(String, Bytes)> {
+        let Ok(entries) = fs::read_dir(&self.0) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file())
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                let binary = fs::read(entry.path()).ok()?;
+                Some((name, binary.into()))
+            })
+            .collect()
+    }
 }
 
 impl prelude::ContextExt for Context {
@@ -0,0 +1,86 @@
+//! Build Bitcoin blocks for a mock chain, without real proof-of-work.
+
+use bitcoin::{
+    absolute::LockTime, block, consensus::serialize, hashes::Hash as _, transaction::Version,
+    Amount, Block, BlockHash, CompactTarget, MerkleBlock, OutPoint, ScriptBuf, Sequence,
+    Transaction, TxIn, TxMerkleNode, TxOut, Txid, Witness,
+};
+use ckb_bitcoin_spv_prover::DummyService;
+use ckb_bitcoin_spv_verifier::types::{core, packed, prelude::Pack as VPack};
+use ckb_testtool::ckb_types::{packed::Byte, prelude::*};
+
+// The easiest target, same as the `regtest`.
+const EASIEST_BITS: u32 = 0x207f_ffff;
+
+pub(crate) struct MockBlock {
+    pub(crate) height: u32,
+    pub(crate) block: Block,
+}
+
+impl MockBlock {
+    /// Mines a block which contains the provided transactions, after a
+    /// coinbase transaction.
+    pub(crate) fn mine(height: u32, txs: Vec<Transaction>) -> Self {
+        let coinbase = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::from_bytes(height.to_le_bytes().to_vec()),
+                sequence: Sequence::MAX,
+                witness: Witness::default(),
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(50 * 100_000_000),
+                script_pubkey: ScriptBuf::new(),
+            }],
+        };
+        let header = block::Header {
+            version: block::Version::ONE,
+            prev_blockhash: BlockHash::all_zeros(),
+            merkle_root: TxMerkleNode::all_zeros(),
+            time: 1_700_000_000,
+            bits: CompactTarget::from_consensus(EASIEST_BITS),
+            nonce: 0,
+        };
+        let txdata = [coinbase].into_iter().chain(txs).collect();
+        let mut block = Block { header, txdata };
+        block.header.merkle_root = block.compute_merkle_root().unwrap();
+        while block.header.validate_pow(block.header.target()).is_err() {
+            block.header.nonce += 1;
+        }
+        Self { height, block }
+    }
+
+    pub(crate) fn header(&self) -> core::Header {
+        self.block.header
+    }
+
+    /// Bootstraps a service with current block, so the block is the only
+    /// one in the MMR of the SPV client.
+    pub(crate) fn bootstrap(&self) -> DummyService {
+        DummyService::bootstrap(self.height, self.header()).unwrap()
+    }
+
+    /// Builds the proof for the transaction at `tx_index` of current block.
+    ///
+    /// Since current block is the only one in the MMR, the header proof is empty.
+    pub(crate) fn transaction_proof(&self, tx_index: u32) -> packed::TransactionProof {
+        let txid: Txid = self.block.txdata[tx_index as usize].txid();
+        let merkle_block = MerkleBlock::from_block_with_predicate(&self.block, |t| *t == txid);
+        let txout_proof = packed::Bytes::new_builder()
+            .set(
+                serialize(&merkle_block)
+                    .into_iter()
+                    .map(Byte::new)
+                    .collect(),
+            )
+            .build();
+        packed::TransactionProof::new_builder()
+            .tx_index(VPack::pack(&tx_index))
+            .height(VPack::pack(&self.height))
+            .transaction_proof(txout_proof)
+            .header_proof(Default::default())
+            .build()
+    }
+}
@@ -4,10 +4,14 @@ use env_logger::{Builder, Target};
 use log::LevelFilter;
 
 mod data_helper;
+mod mock_chain;
+mod sighash;
 mod type_id;
 
 pub(crate) use ckb_bitcoin_spv_prover::utilities::decode_from_bin_file;
 pub(crate) use data_helper::{find_bin_file, find_bin_files};
+pub(crate) use mock_chain::MockBlock;
+pub(crate) use sighash::{set_signature, sign_sighash_all};
 pub(crate) use type_id::calculate_type_id;
 
 pub(crate) fn setup() {
@@ -0,0 +1,45 @@
+use ckb_testtool::{
+    ckb_crypto::secp::Privkey,
+    ckb_hash::new_blake2b,
+    ckb_types::{bytes::Bytes, core::TransactionView, packed::*, prelude::*, H256},
+};
+
+const SIGNATURE_SIZE: usize = 65;
+
+/// Signs a transaction in the same way as `secp256k1_blake160_sighash_all`.
+///
+/// All inputs of the transaction should be in the same script group, and
+/// there should be no other witnesses.
+pub(crate) fn sign_sighash_all(tx: &TransactionView, key: &Privkey) -> Bytes {
+    let inputs_count = tx.inputs().len();
+    let zeroed_witness = witness_with_lock(&[0u8; SIGNATURE_SIZE]);
+    let message = {
+        let mut hasher = new_blake2b();
+        hasher.update(tx.hash().as_slice());
+        hasher.update(&(zeroed_witness.len() as u64).to_le_bytes());
+        hasher.update(&zeroed_witness);
+        for _ in 1..inputs_count {
+            hasher.update(&0u64.to_le_bytes());
+        }
+        let mut message = [0u8; 32];
+        hasher.finalize(&mut message);
+        H256::from(message)
+    };
+    key.sign_recoverable(&message)
+        .expect("sign")
+        .serialize()
+        .into()
+}
+
+/// Puts the signature into the lock field of the first witness, and leaves
+/// witnesses of other inputs empty.
+pub(crate) fn set_signature(tx: &TransactionView, signature: &[u8]) -> TransactionView {
+    let mut witnesses = vec![witness_with_lock(signature).pack()];
+    witnesses.resize(tx.inputs().len(), Bytes::new().pack());
+    tx.as_advanced_builder().set_witnesses(witnesses).build()
+}
+
+fn witness_with_lock(lock: &[u8]) -> Bytes {
+    let lock = BytesOpt::new_builder().set(Some(Pack::pack(lock))).build();
+    WitnessArgs::new_builder().lock(lock).build().as_bytes()
+}